@@ -0,0 +1,161 @@
+//! The `sudoku_grid_checked!` macro, re-exported by `bitsudoku` with the
+//! `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{LitInt, LitStr, Token};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+
+
+/// One square of the grid as written, with where it was written.
+struct Cell {
+    digit: u8,
+    span: Span
+}
+
+
+/// The 81 squares of a grid, either as a puzzle string or as values.
+struct Cells(Vec<Cell>);
+
+
+/// A value in the list form: a digit literal or `_`.
+struct Value(Cell);
+
+
+impl Parse for Value {
+    fn parse(input: ParseStream) -> syn::Result<Value>
+    {
+        if input.peek(Token![_]) {
+            let blank: Token![_] = input.parse()?;
+            return Ok(Value(Cell { digit: 0, span: blank.span }));
+        }
+
+        let literal: LitInt = input.parse()?;
+        match literal.base10_parse::<u8>() {
+            Ok(digit) if digit <= 9 => Ok(Value(Cell { digit, span: literal.span() })),
+            _ => Err(syn::Error::new(literal.span(), "expected a digit from 1 to 9, or 0 or _ for a blank"))
+        }
+    }
+}
+
+
+impl Parse for Cells {
+    fn parse(input: ParseStream) -> syn::Result<Cells>
+    {
+        if input.peek(LitStr) {
+            let literal: LitStr = input.parse()?;
+            let mut cells = Vec::with_capacity(81);
+            for character in literal.value().chars() {
+                let digit = match character {
+                    '1'..='9' => character as u8 - b'0',
+                    '.' | '0' => 0,
+                    _ => return Err(syn::Error::new(
+                        literal.span(),
+                        format!("invalid character {:?} in puzzle string", character)
+                    ))
+                };
+                cells.push(Cell { digit, span: literal.span() });
+            }
+            return Ok(Cells(cells));
+        }
+
+        let values = Punctuated::<Value, Token![,]>::parse_terminated(input)?;
+        Ok(Cells(values.into_iter().map(|Value(cell)| cell).collect()))
+    }
+}
+
+
+/// The row, column and box of each square, numbered from 1.
+fn units(index: usize) -> [(&'static str, usize); 3]
+{
+    let (r, c) = (index / 9, index % 9);
+    [("row", r + 1), ("column", c + 1), ("box", 3*(r / 3) + c / 3 + 1)]
+}
+
+fn check(cells: &[Cell]) -> syn::Result<()>
+{
+    if cells.len() != 81 {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!("expected 81 values, found {}", cells.len())
+        ));
+    }
+
+    let mut seen = [0u16; 27];
+    for (i, cell) in cells.iter().enumerate().filter(|(_, cell)| cell.digit != 0) {
+        let bit = 1 << (cell.digit - 1);
+        for (k, &(kind, number)) in units(i).iter().enumerate() {
+            let slot = &mut seen[9*k + number - 1];
+            if *slot & bit != 0 {
+                return Err(syn::Error::new(
+                    cell.span,
+                    format!("digit {} appears twice in {} {}", cell.digit, kind, number)
+                ));
+            }
+            *slot |= bit;
+        }
+    }
+    Ok(())
+}
+
+
+/// Build a `bitsudoku::SudokuGrid` like `sudoku_grid!`, from 81 values in
+/// row-major order with `0` or `_` for blanks, or from an 81 character
+/// puzzle string. The wrong number of values, a value that isn't a digit
+/// or a digit given twice in a row, column or box is a compile error.
+#[proc_macro]
+pub fn sudoku_grid_checked(input: TokenStream) -> TokenStream
+{
+    let Cells(cells) = syn::parse_macro_input!(input as Cells);
+    if let Err(err) = check(&cells) {
+        return err.to_compile_error().into();
+    }
+
+    let placements = cells.iter().enumerate().filter(|(_, cell)| cell.digit != 0).map(|(i, cell)| {
+        let (row, col, digit) = ((i / 9 + 1) as u8, (i % 9 + 1) as u8, cell.digit);
+        quote! { grid.set_unchecked(#row, #col, #digit); }
+    });
+    quote! {{
+        let mut grid = ::bitsudoku::SudokuGrid::new();
+        #(#placements)*
+        grid
+    }}.into()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> syn::Result<()>
+    {
+        let Cells(cells) = syn::parse_str(source)?;
+        check(&cells)
+    }
+
+    #[test]
+    fn test_accepts_puzzles()
+    {
+        let puzzle = "\"53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79\"";
+        assert!(parse(puzzle).is_ok());
+        let values = ["_"; 80].join(", ");
+        assert!(parse(&format!("5, {}", values)).is_ok());
+        assert!(parse(&format!("5, {},", values)).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_bad_grids()
+    {
+        let blanks = ["0"; 79].join(", ");
+        let message = |source: &str| parse(source).unwrap_err().to_string();
+        assert_eq!(message(&format!("5, {}", blanks)), "expected 81 values, found 80");
+        assert_eq!(message(&format!("5, 5, {}", blanks)), "digit 5 appears twice in row 1");
+        assert!(message(&format!("10, 0, {}", blanks)).starts_with("expected a digit"));
+        assert!(message("\"x\"").starts_with("invalid character 'x'"));
+
+        let column = format!("7, {}, 7, {}", ["_"; 8].join(", "), ["_"; 71].join(", "));
+        assert_eq!(message(&column), "digit 7 appears twice in column 1");
+    }
+}
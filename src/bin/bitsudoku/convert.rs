@@ -0,0 +1,96 @@
+//! The `convert` subcommand.
+
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, ValueEnum};
+
+use bitsudoku::{SudokuError, SudokuGrid};
+use bitsudoku::io::{read_sdm, read_ss, write_sdm};
+
+use crate::Layout;
+use crate::input;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// SudoCue `.sdm`: one puzzle per line, `0` for empty squares.
+    Sdm,
+    /// Simple Sudoku `.ss` grids, separated by blank lines.
+    Ss,
+    /// One puzzle per line, `.` for empty squares.
+    Line,
+    /// Drawn grids as printed by the other subcommands.
+    Grid
+}
+
+
+#[derive(Debug, Args)]
+pub struct ConvertArgs {
+    /// File to read puzzles from; standard input if absent or `-`.
+    input: Option<PathBuf>,
+
+    /// Input format; without it each line is taken as a whole puzzle or
+    /// as part of a drawn grid, whichever fits.
+    #[arg(long, value_enum)]
+    from: Option<Format>,
+
+    #[arg(long, value_enum, default_value_t = Format::Line)]
+    to: Format
+}
+
+
+fn read(format: Option<Format>, mut reader: Box<dyn io::BufRead>)
+    -> Result<Vec<Result<SudokuGrid, SudokuError>>, SudokuError>
+{
+    Ok(match format {
+        Some(Format::Sdm) => read_sdm(reader).collect(),
+        Some(Format::Ss) => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            text.split("\n\n")
+                .filter(|block| !block.trim().is_empty())
+                .map(|block| read_ss(block.as_bytes()))
+                .collect()
+        },
+        None | Some(Format::Line) | Some(Format::Grid) => input::read_puzzles(reader)?
+    })
+}
+
+
+/// Read puzzles in one format and write them in another. Puzzles that
+/// can't be read are reported on standard error and left out.
+pub fn run(args: &ConvertArgs) -> Result<ExitCode, SudokuError>
+{
+    let mut grids = Vec::new();
+    let mut failed = false;
+    for (n, puzzle) in read(args.from, input::open(args.input.as_deref())?)?.into_iter().enumerate() {
+        match puzzle {
+            Ok(grid) => grids.push(grid),
+            Err(err) => {
+                eprintln!("puzzle {}: {}", n + 1, err);
+                failed = true;
+            }
+        }
+    }
+
+    let layout = match args.to {
+        Format::Sdm => {
+            write_sdm(io::stdout().lock(), grids.iter())?;
+            None
+        },
+        Format::Ss => Some(Layout::Ss),
+        Format::Grid => Some(Layout::Grid),
+        Format::Line => Some(Layout::Line)
+    };
+    if let Some(layout) = layout {
+        for (n, grid) in grids.iter().enumerate() {
+            if n > 0 && layout != Layout::Line {
+                println!();
+            }
+            println!("{}", layout.render(grid));
+        }
+    }
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
@@ -0,0 +1,88 @@
+//! Reading puzzles in whatever plain text form they come in.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use bitsudoku::{SudokuError, SudokuGrid};
+
+
+/// Open `path` for reading, or standard input for `-` or no path.
+pub fn open(path: Option<&Path>) -> io::Result<Box<dyn BufRead>>
+{
+    match path {
+        Some(path) if path != Path::new("-") => Ok(Box::new(BufReader::new(File::open(path)?))),
+        _ => Ok(Box::new(BufReader::new(io::stdin())))
+    }
+}
+
+/// Read every puzzle in `reader`. A line that is a whole puzzle on its
+/// own counts as one; other lines contribute their digits and `.` or `0`
+/// blanks to a grid drawn over several lines, so `.ss` files and the
+/// crate's own display form both work. Anything else on such lines, such
+/// as `|`, `+` or `-`, is ignored.
+pub fn read_puzzles<R: BufRead>(reader: R) -> io::Result<Vec<Result<SudokuGrid, SudokuError>>>
+{
+    let mut puzzles = Vec::new();
+    let mut cells = String::with_capacity(81);
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if cells.is_empty() && line.chars().count() == 81 {
+            puzzles.push(line.parse());
+            continue;
+        }
+
+        cells.extend(line.chars().filter_map(|ch| match ch {
+            '1'..='9' | '.' | '0' => Some(ch),
+            'X' | 'x' => Some('.'),
+            _ => None
+        }));
+        if cells.len() >= 81 {
+            puzzles.push(cells.parse());
+            cells.clear();
+        }
+    }
+    if !cells.is_empty() {
+        puzzles.push(Err(SudokuError::InvalidLength { length: cells.len() }));
+    }
+    Ok(puzzles)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixed_formats()
+    {
+        let text = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79\n",
+            "\n",
+            "53.|.7.|...\n6..|195|...\n.98|...|.6.\n-----------\n",
+            "8..|.6.|..3\n4..|8.3|..1\n7..|.2.|..6\n-----------\n",
+            ".6.|...|28.\n...|419|..5\n...|.8.|.79\n",
+            "12345\n"
+        );
+        let puzzles = read_puzzles(text.as_bytes()).unwrap();
+        assert_eq!(puzzles.len(), 3);
+        assert_eq!(puzzles[0], puzzles[1]);
+        assert!(puzzles[0].is_ok());
+        assert_eq!(puzzles[2], Err(SudokuError::InvalidLength { length: 5 }));
+    }
+
+    #[test]
+    fn test_display_form()
+    {
+        let grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        let puzzles = read_puzzles(grid.to_string().as_bytes()).unwrap();
+        assert_eq!(puzzles, vec![Ok(grid)]);
+    }
+}
@@ -0,0 +1,87 @@
+//! Command-line front end to the bitsudoku library.
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use bitsudoku::SudokuGrid;
+use bitsudoku::io::write_ss;
+use bitsudoku::render;
+
+mod convert;
+mod generate;
+mod input;
+mod rate;
+mod solve;
+
+
+#[derive(Debug, Parser)]
+#[command(name = "bitsudoku", version, about = "Solve, generate and rate sudoku puzzles")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command
+}
+
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Solve puzzles read from a file or standard input.
+    Solve(solve::SolveArgs),
+    /// Generate new puzzles.
+    Generate(generate::GenerateArgs),
+    /// Grade puzzles by the techniques needed to solve them.
+    Rate(rate::RateArgs),
+    /// Translate puzzle files between formats.
+    Convert(convert::ConvertArgs)
+}
+
+
+/// How grids are written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Layout {
+    /// 81 characters on one line, `.` for empty squares.
+    Line,
+    /// A drawn grid with box separators.
+    Grid,
+    /// The Simple Sudoku `.ss` layout.
+    Ss,
+    /// A drawn grid coloured for the terminal.
+    Color
+}
+
+
+impl Layout {
+
+    pub fn render(self, grid: &SudokuGrid) -> String
+    {
+        match self {
+            Layout::Line => grid.to_line_string(),
+            Layout::Grid => grid.to_string(),
+            Layout::Ss => {
+                let mut out = Vec::new();
+                write_ss(&mut out, grid).expect("Writing to memory doesn't fail.");
+                String::from_utf8(out).expect("The .ss layout is ASCII.").trim_end().to_string()
+            },
+            Layout::Color => render::ansi(grid, &[])
+        }
+    }
+}
+
+
+fn main() -> ExitCode
+{
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Solve(args) => solve::run(&args),
+        Command::Generate(args) => generate::run(&args),
+        Command::Rate(args) => rate::run(&args),
+        Command::Convert(args) => convert::run(&args)
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("bitsudoku: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! The `rate` subcommand.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Args;
+
+use bitsudoku::{SudokuError, SudokuGrid};
+use bitsudoku::rating::{Rating, assess};
+
+use crate::Layout;
+use crate::input;
+
+
+#[derive(Debug, Args)]
+pub struct RateArgs {
+    /// File to read puzzles from; standard input if absent or `-`.
+    input: Option<PathBuf>,
+
+    /// Write comma separated values with a header row instead of a table.
+    #[arg(long)]
+    csv: bool
+}
+
+
+fn row(grid: &SudokuGrid, rating: &Rating) -> [String; 4]
+{
+    [
+        Layout::Line.render(grid),
+        grid.filled_count().to_string(),
+        rating.difficulty.to_string(),
+        rating.hardest.map_or("", |t| t.name()).to_string()
+    ]
+}
+
+
+/// Print the grade, hardest technique and clue count of every puzzle.
+/// Puzzles that can't be read or rated are reported on standard error.
+pub fn run(args: &RateArgs) -> Result<ExitCode, SudokuError>
+{
+    let puzzles = input::read_puzzles(input::open(args.input.as_deref())?)?;
+
+    if args.csv {
+        println!("puzzle,clues,difficulty,hardest");
+    }
+    let mut failed = false;
+    for (n, puzzle) in puzzles.iter().enumerate() {
+        let rated = puzzle.as_ref().map_err(|err| *err)
+            .and_then(|grid| Ok((grid, assess(grid)?)));
+        match rated {
+            Ok((grid, rating)) => {
+                let [line, clues, difficulty, hardest] = row(grid, &rating);
+                if args.csv {
+                    println!("{},{},{},{}", line, clues, difficulty, hardest);
+                } else {
+                    println!("{}  {:>2}  {:<10}  {}", line, clues, difficulty, hardest);
+                }
+            },
+            Err(err) => {
+                eprintln!("puzzle {}: {}", n + 1, err);
+                failed = true;
+            }
+        }
+    }
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
@@ -0,0 +1,57 @@
+//! The `solve` subcommand.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
+
+use clap::Args;
+
+use bitsudoku::SudokuError;
+
+use crate::Layout;
+use crate::input;
+
+
+#[derive(Debug, Args)]
+pub struct SolveArgs {
+    /// File to read puzzles from; standard input if absent or `-`.
+    input: Option<PathBuf>,
+
+    /// How to print each solution.
+    #[arg(long, value_enum, default_value_t = Layout::Line)]
+    format: Layout,
+
+    /// Report the time taken for each puzzle on standard error.
+    #[arg(long)]
+    timing: bool
+}
+
+
+/// Print a solution for every puzzle and a summary on standard error.
+/// Fails when any puzzle couldn't be read or solved.
+pub fn run(args: &SolveArgs) -> Result<ExitCode, SudokuError>
+{
+    let puzzles = input::read_puzzles(input::open(args.input.as_deref())?)?;
+
+    let start = Instant::now();
+    let mut solved = 0;
+    for (n, puzzle) in puzzles.iter().enumerate() {
+        let puzzle_start = Instant::now();
+        match puzzle.as_ref().map_err(|err| *err).and_then(|grid| grid.solve()) {
+            Ok(solution) => {
+                println!("{}", args.format.render(&solution));
+                solved += 1;
+            },
+            Err(err) => {
+                println!();
+                eprintln!("puzzle {}: {}", n + 1, err);
+            }
+        }
+        if args.timing {
+            eprintln!("puzzle {}: {:?}", n + 1, puzzle_start.elapsed());
+        }
+    }
+
+    eprintln!("solved {} of {} puzzles in {:?}", solved, puzzles.len(), start.elapsed());
+    Ok(if solved == puzzles.len() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
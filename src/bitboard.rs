@@ -0,0 +1,260 @@
+//! Per-digit occupancy masks kept alongside the squares.
+//!
+//! Bit `i` of the board for a digit is set when square `i`, counted in
+//! row-major order from 0, holds that digit. Combined with the masks in
+//! `tables`, questions about units become a few popcounts. The digits
+//! present in each unit, and a count of repeats, are kept as well so that
+//! conflict queries don't need to look at the squares at all.
+//!
+//! The boards are updated by the grid's own setters. Squares handed out
+//! through `iter_mut` may change behind the grid's back, so doing that
+//! marks the boards stale: readers rebuild them from the squares until
+//! the next edit through the grid rebuilds them once and clears the mark.
+
+use crate::{SudokuGrid, SudokuSquare};
+use crate::tables::{UNIT_MASKS, UNITS_OF, ZOBRIST};
+
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DigitBoards {
+    boards: [u128; 9],
+    /// The digits set in each unit, numbered as in `tables::UNIT_MASKS`.
+    units: [u16; 27],
+    /// Squares whose digit is already set elsewhere in one of their units,
+    /// counted once per unit.
+    repeats: u16,
+    /// The Zobrist keys of the set squares XORed together.
+    hash: u64,
+    stale: bool
+}
+
+
+impl DigitBoards {
+
+    pub(crate) fn from_squares(squares: &[SudokuSquare; 81]) -> DigitBoards
+    {
+        let mut boards = DigitBoards::default();
+        for (i, sq) in squares.iter().enumerate() {
+            if let Ok(digit) = sq.get() {
+                boards.insert(i, digit);
+            }
+        }
+        boards
+    }
+
+    pub(crate) fn board(&self, digit: u8) -> u128
+    {
+        self.boards[usize::from(digit - 1)]
+    }
+
+    pub(crate) fn insert(&mut self, index: usize, digit: u8)
+    {
+        let bit = 0x0001 << (digit - 1);
+        for &u in UNITS_OF[index].iter() {
+            let u = usize::from(u);
+            if self.units[u] & bit != 0 {
+                self.repeats += 1;
+            }
+            self.units[u] |= bit;
+        }
+        self.boards[usize::from(digit - 1)] |= 1 << index;
+        self.hash ^= ZOBRIST[index][usize::from(digit - 1)];
+    }
+
+    /// Clear square `index`, if it holds a digit.
+    pub(crate) fn remove(&mut self, index: usize)
+    {
+        let d = match self.boards.iter().position(|b| b & (1 << index) != 0) {
+            Some(d) => d,
+            None => return
+        };
+        self.boards[d] &= !(1 << index);
+        self.hash ^= ZOBRIST[index][d];
+        for &u in UNITS_OF[index].iter() {
+            let u = usize::from(u);
+            if self.boards[d] & UNIT_MASKS[u] != 0 {
+                self.repeats -= 1;
+            } else {
+                self.units[u] &= !(0x0001 << d);
+            }
+        }
+    }
+
+    /// The digits set in the row, column and box of square `index`, as a
+    /// 9-bit mask.
+    pub(crate) fn seen_digits(&self, index: usize) -> u16
+    {
+        UNITS_OF[index].iter().fold(0u16, |m, &u| m | self.units[usize::from(u)])
+    }
+
+    /// The digits set in unit `unit`.
+    pub(crate) fn unit_digits(&self, unit: usize) -> u16
+    {
+        self.units[unit]
+    }
+
+    /// The number of squares holding a digit.
+    pub(crate) fn filled(&self) -> usize
+    {
+        self.boards.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Whether some unit holds a digit more than once.
+    pub(crate) fn has_repeats(&self) -> bool
+    {
+        self.repeats != 0
+    }
+
+    pub(crate) fn hash(&self) -> u64
+    {
+        self.hash
+    }
+
+    /// Whether squares were handed out since the boards were last kept.
+    pub(crate) fn is_stale(&self) -> bool
+    {
+        self.stale
+    }
+
+    /// How many times `digit` is set in unit `unit`, numbered as in
+    /// `tables::UNIT_MASKS`.
+    pub(crate) fn count_in_unit(&self, unit: usize, digit: u8) -> u32
+    {
+        (self.board(digit) & UNIT_MASKS[unit]).count_ones()
+    }
+}
+
+
+impl SudokuGrid {
+
+    /// The squares holding `digit` as an 81-bit mask, with bit `i` for the
+    /// square at row `i / 9 + 1`, column `i % 9 + 1`.
+    pub fn digit_board(&self, digit: u8) -> u128
+    {
+        match digit {
+            1..=9 => self.boards().board(digit),
+            _ => 0
+        }
+    }
+
+    /// A 64-bit Zobrist hash of the set squares, kept up to date as
+    /// squares are set and cleared so reading it costs nothing, as for
+    /// `has_conflicts`. Grids with
+    /// the same digits in the same squares hash alike whatever their
+    /// candidates, which suits transposition tables and deduplication.
+    pub fn position_hash(&self) -> u64
+    {
+        self.boards().hash()
+    }
+
+    pub(crate) fn boards(&self) -> DigitBoards
+    {
+        if self.1.is_stale() {
+            DigitBoards::from_squares(&self.0)
+        } else {
+            self.1
+        }
+    }
+
+    /// Rebuild the digit boards after the squares were written directly.
+    pub(crate) fn sync_boards(&mut self)
+    {
+        self.1 = DigitBoards::from_squares(&self.0);
+    }
+
+    /// Rebuild the digit boards if squares were handed out since they were
+    /// last kept, so the edit about to be made can update them in place.
+    pub(crate) fn refresh_boards(&mut self)
+    {
+        if self.1.is_stale() {
+            self.sync_boards();
+        }
+    }
+
+    pub(crate) fn mark_boards_stale(&mut self)
+    {
+        self.1.stale = true;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boards_follow_set_and_clear()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 5).unwrap();
+        grid.set((9, 9), 5).unwrap();
+        assert_eq!(grid.digit_board(5), 1 | (1 << 80));
+
+        grid.set((1, 1), 3).unwrap();
+        assert_eq!(grid.digit_board(5), 1 << 80);
+        assert_eq!(grid.digit_board(3), 1);
+
+        grid.clear((9, 9)).unwrap();
+        assert_eq!(grid.digit_board(5), 0);
+        assert_eq!(grid.1.boards, DigitBoards::from_squares(&grid.0).boards);
+        assert_eq!(grid.1.seen_digits(2), 0b100);
+    }
+
+    #[test]
+    fn test_repeats_counted_per_unit()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 4).unwrap();
+        grid.set((2, 2), 4).unwrap();
+        grid.set((1, 9), 4).unwrap();
+        assert_eq!(grid.1.repeats, 2);
+
+        grid.clear((1, 1)).unwrap();
+        assert_eq!(grid.1.repeats, 0);
+        assert_eq!(grid.1.unit_digits(0), 0b1000);
+
+        grid.set((2, 2), 5).unwrap();
+        assert!(!grid.1.has_repeats());
+        assert_eq!(grid.1.unit_digits(18), 0b10000);
+    }
+
+    #[test]
+    fn test_position_hash_is_incremental()
+    {
+        let mut grid = SudokuGrid::new();
+        assert_eq!(grid.position_hash(), 0);
+        grid.set((1, 1), 5).unwrap();
+        grid.set((4, 7), 2).unwrap();
+        let hash = grid.position_hash();
+        assert_eq!(hash, DigitBoards::from_squares(&grid.0).hash);
+
+        // The order of the edits and the candidates don't matter.
+        let mut other = SudokuGrid::new();
+        other.set((4, 7), 2).unwrap();
+        other.set((1, 1), 3).unwrap();
+        other.set((1, 1), 5).unwrap();
+        other.eliminate_all().unwrap();
+        assert_eq!(other.position_hash(), hash);
+
+        grid.set((9, 9), 1).unwrap();
+        assert_ne!(grid.position_hash(), hash);
+        grid.clear((9, 9)).unwrap();
+        assert_eq!(grid.position_hash(), hash);
+    }
+
+    #[test]
+    fn test_boards_rebuilt_after_iter_mut()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 5).unwrap();
+        grid.iter_mut().next().unwrap().unset();
+        assert_eq!(grid.digit_board(5), 0);
+        assert!(grid.1.stale);
+
+        // The next edit rebuilds the boards once and keeps them again.
+        grid.set((2, 2), 7).unwrap();
+        assert!(!grid.1.stale);
+        assert_eq!(grid.1.boards, DigitBoards::from_squares(&grid.0).boards);
+        assert_eq!(grid.filled_count(), 1);
+    }
+}
@@ -0,0 +1,213 @@
+//! Minimal lexicographic canonical forms.
+//!
+//! Two puzzles are isomorphic when one can be turned into the other by
+//! transposing, permuting bands, stacks, rows within a band and columns
+//! within a stack, and relabeling the digits. The canonical form is the
+//! member of the class whose 81 character string, with `0` for empty
+//! squares, is smallest.
+
+use crate::SudokuGrid;
+use crate::solver::backtrack::grid_values;
+
+
+const PERMS3: [[usize; 3]; 6] = [
+    [0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]
+];
+
+
+/// All 1296 orderings of the nine lines that keep groups of three together.
+pub(crate) fn line_orders() -> Vec<[usize; 9]>
+{
+    let mut orders = Vec::with_capacity(1296);
+    for outer in PERMS3.iter() {
+        for p0 in PERMS3.iter() {
+            for p1 in PERMS3.iter() {
+                for p2 in PERMS3.iter() {
+                    let inner = [p0, p1, p2];
+                    orders.push(std::array::from_fn(|k| {
+                        3*outer[k / 3] + inner[k / 3][k % 3]
+                    }));
+                }
+            }
+        }
+    }
+    orders
+}
+
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+
+struct Search<'a> {
+    values: &'a [u8; 81],
+    cols: [usize; 9],
+    current: [u8; 81],
+    best: [u8; 81]
+}
+
+
+impl<'a> Search<'a> {
+
+    /// Place rows into slots `slot..9`, labelling digits in order of first
+    /// appearance, and keep the smallest completed grid in `best`. Rows of
+    /// a band stay together, so a slot that opens a band may take a row
+    /// from any unused band and the others must follow within it.
+    fn run(&mut self, slot: usize, labels: [u8; 10], next: u8, used: u16, band: usize)
+    {
+        if slot == 9 {
+            if self.current < self.best {
+                self.best = self.current;
+            }
+            return;
+        }
+
+        let candidates: Vec<usize> = if slot.is_multiple_of(3) {
+            (0..9).filter(|&r| used & (0x0007 << (3*(r / 3))) == 0).collect()
+        } else {
+            (3*band..3*band + 3).filter(|&r| used & (0x0001 << r) == 0).collect()
+        };
+
+        for row in candidates {
+            let mut row_labels = labels;
+            let mut row_next = next;
+            for k in 0..9 {
+                self.current[9*slot + k] = label(
+                    self.values[9*row + self.cols[k]], &mut row_labels, &mut row_next
+                );
+            }
+            let end = 9*(slot + 1);
+            if self.current[..end] > self.best[..end] {
+                continue;
+            }
+            self.run(slot + 1, row_labels, row_next, used | (0x0001 << row), row / 3);
+        }
+    }
+}
+
+
+fn label(value: u8, labels: &mut [u8; 10], next: &mut u8) -> u8
+{
+    if value == 0 {
+        return 0;
+    }
+    let v = usize::from(value);
+    if labels[v] == 0 {
+        labels[v] = *next;
+        *next += 1;
+    }
+    labels[v]
+}
+
+
+fn canonical_values(values: &[u8; 81]) -> [u8; 81]
+{
+    let transposed: [u8; 81] = std::array::from_fn(|i| values[9*(i % 9) + i / 9]);
+    let mut best = [10u8; 81];
+
+    for grid in [values, &transposed].iter() {
+        for cols in line_orders() {
+            let mut search = Search { values: grid, cols, current: [0; 81], best };
+            search.run(0, [0; 10], 1, 0, 0);
+            best = search.best;
+        }
+    }
+    best
+}
+
+
+impl SudokuGrid {
+
+    /// The canonical representative of the grid's isomorphism class. Only
+    /// the set squares are taken into account; candidates are dropped.
+    pub fn canonical_form(&self) -> SudokuGrid
+    {
+        let values = canonical_values(&grid_values(self));
+        let mut grid = SudokuGrid::new();
+        for (i, &v) in values.iter().enumerate() {
+            if v != 0 {
+                grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, v);
+            }
+        }
+        grid
+    }
+
+    /// A short identifier for the grid's isomorphism class, 16 hex digits
+    /// of an FNV-1a hash of the canonical form. Isomorphic grids share it,
+    /// and it doesn't change between runs, platforms or releases, so it
+    /// can be stored to recognise a puzzle later.
+    pub fn fingerprint(&self) -> String
+    {
+        let hash = canonical_values(&grid_values(self)).iter().fold(FNV_OFFSET, |h, &v| {
+            (h ^ u64::from(v)).wrapping_mul(FNV_PRIME)
+        });
+        format!("{:016x}", hash)
+    }
+
+    /// Whether `other` can be reached from this grid by the symmetries of
+    /// sudoku and a relabeling of the digits.
+    pub fn is_isomorphic_to(&self, other: &SudokuGrid) -> bool
+    {
+        canonical_values(&grid_values(self)) == canonical_values(&grid_values(other))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_orders()
+    {
+        let orders = line_orders();
+        assert_eq!(orders.len(), 1296);
+        assert_eq!(orders[0], [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_isomorphic_puzzles_share_canonical_form()
+    {
+        let grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        let other = grid.rotate90()
+            .swap_bands(1, 2).unwrap()
+            .swap_cols(4, 6).unwrap()
+            .relabel(&[3, 1, 2, 9, 8, 7, 4, 5, 6]).unwrap();
+
+        let canonical = grid.canonical_form();
+        assert_eq!(canonical, other.canonical_form());
+        assert!(grid.is_isomorphic_to(&other));
+        assert!(canonical.to_line_string_with('0') <= grid.to_line_string_with('0'));
+
+        assert_eq!(grid.fingerprint(), other.fingerprint());
+        // Pinned, since stored fingerprints must keep matching.
+        assert_eq!(grid.fingerprint(), "8786c367507f01c8");
+
+        let mut changed = other;
+        let sq = *changed.iter().find(|sq| sq.is_set()).unwrap();
+        changed.clear((sq.row(), sq.col())).unwrap();
+        assert!(!grid.is_isomorphic_to(&changed));
+        assert_ne!(grid.fingerprint(), changed.fingerprint());
+    }
+
+    #[test]
+    fn test_canonical_solution_starts_with_first_row()
+    {
+        let grid = sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ];
+        let canonical = grid.canonical_form();
+        assert!(canonical.check().unwrap());
+        assert!(canonical.to_line_string_with('0').starts_with("123456789"));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{Pos, Position, SudokuGrid, Unit};
+use crate::tables::UNIT_MASKS;
+
+
+/// The units of a grid that don't hold every digit exactly once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckReport {
+    pub failed_rows: Vec<u8>,
+    pub failed_cols: Vec<u8>,
+    pub failed_boxes: Vec<u8>,
+    /// Digits that are set more than once in a unit.
+    pub conflicts: Vec<(Unit, u8)>
+}
+
+
+impl CheckReport {
+
+    pub fn is_valid(&self) -> bool
+    {
+        self.failed_rows.is_empty() && self.failed_cols.is_empty()
+            && self.failed_boxes.is_empty()
+    }
+}
+
+
+impl SudokuGrid {
+
+    /// Check every unit of a complete grid, reporting the units that are
+    /// missing a digit or hold one twice. Empty squares count as missing.
+    pub fn check_detailed(&self) -> CheckReport
+    {
+        let boards = self.boards();
+        let mut report = CheckReport::default();
+        for (k, unit) in Unit::all().enumerate() {
+            let mut failed = boards.unit_digits(k) != 0x01FF;
+            if boards.has_repeats() {
+                for digit in (1..=9).filter(|&d| boards.count_in_unit(k, d) > 1) {
+                    report.conflicts.push((unit, digit));
+                    failed = true;
+                }
+            }
+
+            if failed {
+                match unit {
+                    Unit::Row(r) => report.failed_rows.push(r),
+                    Unit::Col(c) => report.failed_cols.push(c),
+                    Unit::Box(b) => report.failed_boxes.push(b)
+                }
+            }
+        }
+        report
+    }
+
+    /// The number of set squares.
+    pub fn filled_count(&self) -> usize
+    {
+        self.boards().filled()
+    }
+
+    /// The number of squares still to fill.
+    pub fn empty_count(&self) -> usize
+    {
+        81 - self.filled_count()
+    }
+
+    /// Whether every square is set, whether or not the digits clash.
+    pub fn is_complete(&self) -> bool
+    {
+        self.filled_count() == 81
+    }
+
+    /// Whether every square is set and no unit holds a digit twice.
+    pub fn is_solved(&self) -> bool
+    {
+        let boards = self.boards();
+        boards.filled() == 81 && !boards.has_repeats()
+    }
+
+    /// Whether some unit holds a digit more than once. This is kept up to
+    /// date as squares are set and cleared, so costs nothing to ask unless
+    /// squares were changed through `iter_mut` since the last edit.
+    pub fn has_conflicts(&self) -> bool
+    {
+        self.boards().has_repeats()
+    }
+
+    /// Pairs of squares that hold the same digit in a shared unit, with
+    /// the digit. Each pair is listed once, the earlier square in row-major
+    /// order first.
+    pub fn conflicts(&self) -> Vec<(Position, Position, u8)>
+    {
+        let boards = self.boards();
+        let mut pairs = Vec::new();
+        if !boards.has_repeats() {
+            return pairs;
+        }
+
+        for digit in 1..=9 {
+            for mask in UNIT_MASKS.iter() {
+                let cells = boards.board(digit) & mask;
+                if cells.count_ones() < 2 {
+                    continue;
+                }
+                let squares: Vec<usize> = (0..81).filter(|&i| cells & (1 << i) != 0).collect();
+                for (k, &i) in squares.iter().enumerate() {
+                    for &j in squares[k + 1..].iter() {
+                        let pair = (position(i), position(j), digit);
+                        if !pairs.contains(&pair) {
+                            pairs.push(pair);
+                        }
+                    }
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Whether a partly filled grid can still be valid: no unit holds a
+    /// digit twice and every empty square has a candidate left once the
+    /// digits of its peers are taken away.
+    pub fn is_consistent(&self) -> bool
+    {
+        let boards = self.boards();
+        if boards.has_repeats() {
+            return false;
+        }
+
+        (0..81).filter(|&i| !self.0[i].is_set()).all(|i| {
+            self.0[i].digit_bits() & !boards.seen_digits(i) != 0
+        })
+    }
+}
+
+
+fn position(index: usize) -> Position
+{
+    Pos::from_index(index).into()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_points_at_conflict()
+    {
+        let grid = sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 4, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ];
+
+        let report = grid.check_detailed();
+        assert!(!report.is_valid());
+        assert_eq!(report.failed_rows, vec![2]);
+        assert_eq!(report.failed_cols, vec![2]);
+        assert_eq!(report.failed_boxes, vec![1]);
+        assert!(report.conflicts.contains(&(Unit::Row(2), 4)));
+        assert!(report.conflicts.contains(&(Unit::Col(2), 4)));
+        assert_eq!(grid.conflicts(), vec![
+            ((2, 2), (2, 3), 4),
+            ((2, 2), (6, 2), 4)
+        ]);
+    }
+
+    #[test]
+    fn test_conflicts_listed_once()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 7).unwrap();
+        grid.set((1, 2), 7).unwrap();
+        grid.set((5, 5), 7).unwrap();
+        assert_eq!(grid.conflicts(), vec![((1, 1), (1, 2), 7)]);
+
+        grid.clear((1, 2)).unwrap();
+        assert!(grid.conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_is_consistent()
+    {
+        let mut grid = SudokuGrid::new();
+        assert!(grid.is_consistent());
+
+        grid.set((1, 1), 1).unwrap();
+        grid.set((5, 5), 1).unwrap();
+        assert!(grid.is_consistent());
+
+        grid.set((9, 1), 1).unwrap();
+        assert!(!grid.is_consistent());
+        assert!(grid.has_conflicts());
+
+        grid.clear((9, 1)).unwrap();
+        assert!(!grid.has_conflicts());
+    }
+
+    #[test]
+    fn test_is_consistent_finds_dead_square()
+    {
+        // (1, 9) sees all of 1 to 8 and has lost 9 from its candidates.
+        let mut grid = SudokuGrid::new();
+        for c in 1..=8 {
+            grid.set((1, c), c).unwrap();
+        }
+        grid.0[8].remove_possibility(9).unwrap();
+        assert!(!grid.is_consistent());
+    }
+
+    #[test]
+    fn test_progress_queries()
+    {
+        let mut grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        assert_eq!(grid.filled_count(), 30);
+        assert_eq!(grid.empty_count(), 51);
+        assert!(!grid.is_complete());
+
+        grid = grid.solve().unwrap();
+        assert!(grid.is_complete() && grid.is_solved());
+
+        let digit = grid.iter().next().unwrap().get().unwrap();
+        grid.set((1, 2), digit).unwrap();
+        assert!(grid.is_complete());
+        assert!(!grid.is_solved());
+        assert!(!grid.check().unwrap());
+    }
+
+    #[test]
+    fn test_report_on_empty_grid()
+    {
+        let report = SudokuGrid::new().check_detailed();
+        assert_eq!(report.failed_rows.len(), 9);
+        assert!(report.conflicts.is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+use std::convert::TryFrom;
+
+use crate::{Pos, Position, SudokuError, SudokuGrid, SudokuSquare};
+use crate::techniques::Elimination;
+
+use super::{Constraint, Eliminations};
+
+
+/// Two squares that share an edge.
+pub type Edge = (Position, Position);
+
+
+/// The digits a square may still hold: its value if set, otherwise its
+/// candidates.
+fn options(sq: &SudokuSquare) -> u16
+{
+    match sq.get() {
+        Ok(digit) => 0x0001 << (digit - 1),
+        Err(_) => sq.digit_bits()
+    }
+}
+
+/// Indices of the squares of an edge, failing unless they are in the grid
+/// and next to each other.
+pub(crate) fn edge_indices(edge: Edge) -> Result<(usize, usize), SudokuError>
+{
+    let first = Pos::try_from(edge.0)?;
+    let second = Pos::try_from(edge.1)?;
+    if first.row().abs_diff(second.row()) + first.col().abs_diff(second.col()) != 1 {
+        return Err(SudokuError::InvalidEdge { first: edge.0, second: edge.1 });
+    }
+    Ok((first.index(), second.index()))
+}
+
+/// Every pair of squares that share an edge.
+pub(crate) fn all_edges() -> impl Iterator<Item = (usize, usize)>
+{
+    (0..81).flat_map(|i| {
+        let right = (i % 9 < 8).then_some((i, i + 1));
+        let below = (i < 72).then_some((i, i + 9));
+        right.into_iter().chain(below)
+    })
+}
+
+
+/// A relation that the digits of each listed pair of squares must keep,
+/// stored as the digits each digit may sit beside.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PairRule {
+    pairs: Vec<(usize, usize)>,
+    support: [u16; 9]
+}
+
+
+impl PairRule {
+
+    pub(crate) fn new<F>(pairs: Vec<(usize, usize)>, allowed: F) -> PairRule
+    where
+        F: Fn(u8, u8) -> bool
+    {
+        let support = std::array::from_fn(|a| {
+            (1..=9).filter(|&b| allowed(a as u8 + 1, b))
+                .fold(0, |mask, b| mask | 0x0001 << (b - 1))
+        });
+        PairRule { pairs, support }
+    }
+
+    pub(crate) fn pairs(&self) -> &[(usize, usize)]
+    {
+        &self.pairs
+    }
+
+    /// The digits with some partner among `options`.
+    fn supported(&self, options: u16) -> u16
+    {
+        (0..9).filter(|&d| options & (0x0001 << d) != 0)
+            .fold(0, |mask, d| mask | self.support[d])
+    }
+
+    pub(crate) fn eliminate(&self, grid: &mut SudokuGrid, eliminations: &mut Eliminations)
+    {
+        for &(a, b) in self.pairs.iter() {
+            for &(square, other) in [(a, b), (b, a)].iter() {
+                let keep = self.supported(options(&grid.0[other]));
+                let sq = &mut grid.0[square];
+                let removed = if sq.is_set() { 0 } else { sq.digit_bits() & !keep };
+                for digit in (1..=9).filter(|&d| removed & (0x0001 << (d - 1)) != 0) {
+                    sq.remove_possibility_unchecked(digit);
+                    eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit });
+                }
+            }
+        }
+    }
+
+    pub(crate) fn is_satisfied(&self, grid: &SudokuGrid) -> bool
+    {
+        self.pairs.iter().all(|&(a, b)| match (grid.0[a].get(), grid.0[b].get()) {
+            (Ok(x), Ok(y)) => self.support[usize::from(x - 1)] & (0x0001 << (y - 1)) != 0,
+            _ => true
+        })
+    }
+}
+
+
+/// No two squares sharing an edge may hold consecutive digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonConsecutive(PairRule);
+
+
+impl Default for NonConsecutive {
+
+    fn default() -> NonConsecutive
+    {
+        NonConsecutive::new()
+    }
+}
+
+
+impl NonConsecutive {
+
+    pub fn new() -> NonConsecutive
+    {
+        NonConsecutive(PairRule::new(all_edges().collect(), |a, b| a.abs_diff(b) != 1))
+    }
+}
+
+
+impl Constraint for NonConsecutive {
+
+    fn eliminate(&self, grid: &mut SudokuGrid) -> Eliminations
+    {
+        let mut eliminations = Vec::new();
+        self.0.eliminate(grid, &mut eliminations);
+        eliminations
+    }
+
+    fn is_satisfied(&self, grid: &SudokuGrid) -> bool
+    {
+        self.0.is_satisfied(grid)
+    }
+}
+
+
+/// Kropki dots on the edges between squares: a white dot joins
+/// consecutive digits and a black dot digits where one is twice the
+/// other. Edges without a dot are unconstrained.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kropki {
+    white: PairRule,
+    black: PairRule
+}
+
+
+impl Kropki {
+
+    pub fn new(white: &[Edge], black: &[Edge]) -> Result<Kropki, SudokuError>
+    {
+        let white = white.iter().map(|&e| edge_indices(e)).collect::<Result<_, _>>()?;
+        let black = black.iter().map(|&e| edge_indices(e)).collect::<Result<_, _>>()?;
+        Ok(Kropki {
+            white: PairRule::new(white, |a, b| a.abs_diff(b) == 1),
+            black: PairRule::new(black, |a, b| a == 2*b || b == 2*a)
+        })
+    }
+}
+
+
+impl Constraint for Kropki {
+
+    fn eliminate(&self, grid: &mut SudokuGrid) -> Eliminations
+    {
+        let mut eliminations = Vec::new();
+        self.white.eliminate(grid, &mut eliminations);
+        self.black.eliminate(grid, &mut eliminations);
+        eliminations
+    }
+
+    fn is_satisfied(&self, grid: &SudokuGrid) -> bool
+    {
+        self.white.is_satisfied(grid) && self.black.is_satisfied(grid)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConstraintSet;
+    use crate::solver::SolverConfig;
+
+    #[test]
+    fn test_edges()
+    {
+        assert_eq!(all_edges().count(), 144);
+        assert_eq!(edge_indices(((2, 3), (2, 4))), Ok((11, 12)));
+        assert_eq!(
+            edge_indices(((2, 3), (3, 4))),
+            Err(SudokuError::InvalidEdge { first: (2, 3), second: (3, 4) })
+        );
+        assert!(edge_indices(((9, 9), (9, 10))).is_err());
+    }
+
+    #[test]
+    fn test_kropki_filters_candidates()
+    {
+        let kropki = Kropki::new(&[((1, 1), (1, 2))], &[((5, 5), (6, 5))]).unwrap();
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 9).unwrap();
+        let eliminations = kropki.eliminate(&mut grid);
+        assert_eq!(grid.0[1].digit_bits(), 0x0080);
+
+        // Only 1, 2, 3, 4, 6 and 8 have a double or half on the grid.
+        assert_eq!(grid.0[40].digit_bits(), 0x00AF);
+        assert_eq!(grid.0[49].digit_bits(), 0x00AF);
+        assert_eq!(eliminations.len(), 8 + 3 + 3);
+
+        grid.set((1, 2), 7).unwrap();
+        assert!(!kropki.is_satisfied(&grid));
+    }
+
+    #[test]
+    fn test_non_consecutive_solve()
+    {
+        let constraints = ConstraintSet::standard().with(NonConsecutive::new());
+        let config = SolverConfig::new().constraints(constraints.clone());
+        let puzzle: SudokuGrid = format!("{:.<81}", "175936428842751693396284175").parse().unwrap();
+        let solution = puzzle.solve_with(&config).unwrap();
+        assert!(constraints.check(&solution));
+        assert!(!NonConsecutive::new().is_satisfied(&puzzle.solve().unwrap()));
+        for (a, b) in all_edges() {
+            let (x, y) = (solution.0[a].get().unwrap(), solution.0[b].get().unwrap());
+            assert_ne!(x.abs_diff(y), 1);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::convert::TryInto;
+use std::str::FromStr;
+
+use crate::{Pos, Position, SudokuError, SudokuGrid, SudokuSquare};
+use crate::solver::SolverConfig;
+
+use super::{ConstraintSet, UniqueDigits};
+
+
+/// The regions of a jigsaw sudoku, numbered 1 to 9, for each square in
+/// row-major order. Each region must have nine squares; they need not be
+/// boxes or even connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionMap([u8; 81]);
+
+
+impl RegionMap {
+
+    pub fn new(regions: [u8; 81]) -> Result<RegionMap, SudokuError>
+    {
+        let mut sizes = [0; 9];
+        for &region in regions.iter() {
+            if region == 0 || region > 9 {
+                return Err(SudokuError::InvalidRegion { region });
+            }
+            sizes[usize::from(region - 1)] += 1;
+        }
+        match sizes.iter().position(|&size| size != 9) {
+            Some(k) => Err(SudokuError::InvalidRegion { region: k as u8 + 1 }),
+            None => Ok(RegionMap(regions))
+        }
+    }
+
+    /// The nine boxes of classic sudoku.
+    pub fn standard() -> RegionMap
+    {
+        RegionMap(std::array::from_fn(|i| (3*(i / 27) + (i % 9) / 3 + 1) as u8))
+    }
+
+    /// The region holding the square at `pos`.
+    pub fn region<P>(&self, pos: P) -> Result<u8, SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        Ok(self.0[pos.index()])
+    }
+
+    fn indices(&self, region: u8) -> [usize; 9]
+    {
+        let mut indices = [0; 9];
+        for (slot, i) in indices.iter_mut().zip((0..81).filter(|&i| self.0[i] == region)) {
+            *slot = i;
+        }
+        indices
+    }
+
+    /// The squares of a region in row-major order.
+    pub fn squares(&self, region: u8) -> [Position; 9]
+    {
+        self.indices(region).map(|i| ((i / 9 + 1) as u8, (i % 9 + 1) as u8))
+    }
+}
+
+
+impl From<&RegionMap> for UniqueDigits {
+
+    fn from(map: &RegionMap) -> UniqueDigits
+    {
+        UniqueDigits::from_indices((1..=9).map(|region| map.indices(region)).collect())
+    }
+}
+
+
+impl FromStr for RegionMap {
+    type Err = SudokuError;
+
+    /// Parse 81 region numbers from 1 to 9 in row-major order, ignoring
+    /// whitespace so the map can be laid out as a grid.
+    fn from_str(s: &str) -> Result<RegionMap, SudokuError>
+    {
+        let mut regions = [0; 81];
+        let mut length = 0;
+        for (position, character) in s.chars().filter(|ch| !ch.is_whitespace()).enumerate() {
+            let region = match character {
+                '1'..='9' => character as u8 - b'0',
+                _ => return Err(SudokuError::InvalidCharacter { position, character })
+            };
+            if let Some(slot) = regions.get_mut(position) {
+                *slot = region;
+            }
+            length += 1;
+        }
+        if length != 81 {
+            return Err(SudokuError::InvalidLength { length });
+        }
+        RegionMap::new(regions)
+    }
+}
+
+
+/// A grid whose boxes are the regions of a `RegionMap`. The squares are
+/// an ordinary `SudokuGrid`, but boxes, checking and solving follow the
+/// regions rather than the classic 3x3 boxes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JigsawGrid {
+    grid: SudokuGrid,
+    regions: RegionMap
+}
+
+
+impl JigsawGrid {
+
+    /// An empty grid with the regions of `regions`.
+    pub fn new(regions: RegionMap) -> JigsawGrid
+    {
+        JigsawGrid::from_grid(SudokuGrid::new(), regions)
+    }
+
+    /// The squares of `grid` under the regions of `regions`.
+    pub fn from_grid(grid: SudokuGrid, regions: RegionMap) -> JigsawGrid
+    {
+        JigsawGrid { grid, regions }
+    }
+
+    pub fn grid(&self) -> &SudokuGrid
+    {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> SudokuGrid
+    {
+        self.grid
+    }
+
+    pub fn regions(&self) -> &RegionMap
+    {
+        &self.regions
+    }
+
+    /// The rows, columns and regions the digits must keep to.
+    pub fn constraints(&self) -> ConstraintSet
+    {
+        ConstraintSet::jigsaw(&self.regions)
+    }
+
+    /// Set the square at `pos` to `digit`, as `SudokuGrid::set`.
+    pub fn set<P>(&mut self, pos: P, digit: u8) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        self.grid.set::<Pos, u8>(pos, digit)
+    }
+
+    pub fn get<P>(&self, pos: P) -> Result<Option<u8>, SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        self.grid.get(pos)
+    }
+
+    pub fn clear<P>(&mut self, pos: P) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        self.grid.clear(pos)
+    }
+
+    /// The squares of region `region`, numbered 1 to 9, in row-major
+    /// order. Panics for other region numbers, like `SudokuGrid::get_box`.
+    pub fn get_box(&self, region: u8) -> [&SudokuSquare; 9]
+    {
+        assert!((1..=9).contains(&region), "Invalid region {}.", region);
+        let indices = self.regions.indices(region);
+        std::array::from_fn(|k| &self.grid.0[indices[k]])
+    }
+
+    /// Whether the grid is complete and every row, column and region
+    /// holds each digit once.
+    pub fn check(&self) -> Result<bool, SudokuError>
+    {
+        Ok(self.constraints().check(&self.grid))
+    }
+
+    /// Find a solution under the regions. When there is more than one, the
+    /// first found is returned.
+    pub fn solve(&self) -> Result<JigsawGrid, SudokuError>
+    {
+        let grid = self.grid.solve_with(&SolverConfig::new().constraints(self.constraints()))?;
+        Ok(JigsawGrid::from_grid(grid, self.regions))
+    }
+
+    /// Count the solutions under the regions, stopping once `limit` have
+    /// been found.
+    pub fn count_solutions(&self, limit: usize) -> Result<usize, SudokuError>
+    {
+        self.grid.count_solutions_with(&SolverConfig::new().constraints(self.constraints()), limit)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Symmetry, generate_constrained};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    /// Each region takes the same place in every box. Reordering the rows
+    /// and columns turns these regions into the classic boxes, so grids
+    /// keeping to them exist.
+    fn regions() -> RegionMap
+    {
+        "123123123 456456456 789789789 ".repeat(3).parse().unwrap()
+    }
+
+    #[test]
+    fn test_region_map()
+    {
+        let map = regions();
+        assert_eq!(map.region((3, 3)), Ok(9));
+        assert_eq!(map.region((5, 8)), Ok(5));
+        assert_eq!(map.region((0, 8)), Err(SudokuError::InvalidPosition { row: 0, col: 8 }));
+        assert_eq!(map.region((5, 10)), Err(SudokuError::InvalidPosition { row: 5, col: 10 }));
+        assert_eq!(map.squares(2)[3], (4, 2));
+        assert_eq!(RegionMap::standard().squares(5)[0], (4, 4));
+
+        let mut bad = RegionMap::standard().0;
+        bad[0] = 2;
+        assert_eq!(RegionMap::new(bad), Err(SudokuError::InvalidRegion { region: 1 }));
+        assert_eq!("12".parse::<RegionMap>(), Err(SudokuError::InvalidLength { length: 2 }));
+
+        let grid = SudokuGrid::new();
+        let jigsaw = JigsawGrid::new(RegionMap::standard());
+        for b in 1..=9 {
+            let boxed: Vec<_> = grid.get_box(b).iter().map(|sq| (sq.row(), sq.col())).collect();
+            let region: Vec<_> = jigsaw.get_box(b).iter().map(|sq| (sq.row(), sq.col())).collect();
+            assert_eq!(boxed, region);
+        }
+    }
+
+    #[test]
+    fn test_jigsaw_solve()
+    {
+        let constraints = ConstraintSet::jigsaw(&regions());
+        let config = SolverConfig::new().constraints(constraints.clone());
+        let puzzle = generate_constrained(&mut StdRng::seed_from_u64(9), &constraints, Symmetry::None)
+            .unwrap();
+        assert_eq!(puzzle.count_solutions_with(&config, 2), Ok(1));
+
+        let solution = puzzle.solve_with(&config).unwrap();
+        assert_eq!(solution.check_with(&constraints), Ok(true));
+    }
+
+    #[test]
+    fn test_jigsaw_grid_follows_regions()
+    {
+        let constraints = ConstraintSet::jigsaw(&regions());
+        let puzzle = generate_constrained(&mut StdRng::seed_from_u64(9), &constraints, Symmetry::None)
+            .unwrap();
+        let jigsaw = JigsawGrid::from_grid(puzzle, regions());
+        assert_eq!(jigsaw.count_solutions(2), Ok(1));
+        assert_eq!(jigsaw.check(), Ok(false));
+
+        let solution = jigsaw.solve().unwrap();
+        assert_eq!(solution.check(), Ok(true));
+        let region: Vec<u8> = solution.get_box(2).iter().map(|sq| sq.get().unwrap()).collect();
+        assert!((1..=9).all(|d| region.contains(&d)));
+
+        // The classic boxes needn't hold every digit, so the plain check
+        // disagrees.
+        assert_eq!(solution.grid().check(), Ok(false));
+        let mut broken = solution;
+        let digit = broken.get((1, 1)).unwrap().unwrap();
+        let other = broken.get_box(regions().region((1, 1)).unwrap())[1];
+        let (row, col) = (other.row(), other.col());
+        broken.clear((row, col)).unwrap();
+        broken.set((row, col), digit).unwrap();
+        assert_eq!(broken.check(), Ok(false));
+    }
+}
@@ -0,0 +1,396 @@
+//! Rules a solution must follow, as pluggable constraints.
+//!
+//! The usual rows, columns and boxes are three [`UniqueDigits`]
+//! constraints and make up [`ConstraintSet::standard`]. Variants add their
+//! own rules to a set, which a solve then respects when it is passed in
+//! through `SolverConfig::constraints`.
+
+mod adjacency;
+mod jigsaw;
+mod parity;
+mod xv;
+
+pub use adjacency::{Edge, Kropki, NonConsecutive};
+pub use jigsaw::{JigsawGrid, RegionMap};
+pub use parity::{EvenOdd, Parity};
+pub use xv::XV;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{Pos, Position, SudokuError, SudokuGrid, Unit};
+use crate::techniques::Elimination;
+
+
+/// The candidates removed by a pass of a constraint.
+pub type Eliminations = Vec<Elimination>;
+
+
+/// A rule on the digits of a grid.
+pub trait Constraint: fmt::Debug + Send + Sync {
+
+    /// Remove the candidates of empty squares that the set squares rule
+    /// out, returning those removed.
+    fn eliminate(&self, grid: &mut SudokuGrid) -> Eliminations;
+
+    /// Whether the set squares keep to the rule. Empty squares never
+    /// break it, so partial grids can be tested as they are filled.
+    fn is_satisfied(&self, grid: &SudokuGrid) -> bool;
+}
+
+
+/// Groups of nine squares that may not repeat a digit, and so hold each
+/// digit once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueDigits {
+    regions: Vec<[usize; 9]>
+}
+
+
+impl UniqueDigits {
+
+    /// Regions given by the positions of their squares. Fails with
+    /// `InvalidPosition` for a square outside the grid.
+    pub fn new(regions: &[[Position; 9]]) -> Result<UniqueDigits, SudokuError>
+    {
+        let mut indices = Vec::with_capacity(regions.len());
+        for region in regions.iter() {
+            let mut squares = [0; 9];
+            for (slot, &position) in squares.iter_mut().zip(region.iter()) {
+                *slot = Pos::try_from(position)?.index();
+            }
+            indices.push(squares);
+        }
+        Ok(UniqueDigits { regions: indices })
+    }
+
+    pub(crate) fn from_indices(regions: Vec<[usize; 9]>) -> UniqueDigits
+    {
+        UniqueDigits { regions }
+    }
+
+    fn from_units<I: Iterator<Item = Unit>>(units: I) -> UniqueDigits
+    {
+        UniqueDigits::from_indices(units.map(|unit| unit.indices()).collect())
+    }
+
+    pub fn rows() -> UniqueDigits
+    {
+        UniqueDigits::from_units((1..=9).map(Unit::Row))
+    }
+
+    pub fn columns() -> UniqueDigits
+    {
+        UniqueDigits::from_units((1..=9).map(Unit::Col))
+    }
+
+    pub fn boxes() -> UniqueDigits
+    {
+        UniqueDigits::from_units((1..=9).map(Unit::Box))
+    }
+
+    /// The two main diagonals of Sudoku-X.
+    pub fn diagonals() -> UniqueDigits
+    {
+        UniqueDigits::from_indices(vec![
+            std::array::from_fn(|k| 10*k),
+            std::array::from_fn(|k| 8*(k + 1))
+        ])
+    }
+}
+
+
+impl Constraint for UniqueDigits {
+
+    fn eliminate(&self, grid: &mut SudokuGrid) -> Eliminations
+    {
+        let mut eliminations = Vec::new();
+        for region in self.regions.iter() {
+            let placed = region.iter()
+                .filter_map(|&i| grid.0[i].get().ok())
+                .fold(0u16, |mask, d| mask | 0x0001 << (d - 1));
+            for &i in region.iter() {
+                let sq = &mut grid.0[i];
+                let removed = sq.digit_bits() & placed;
+                if sq.is_set() || removed == 0 {
+                    continue;
+                }
+                for digit in (1..=9).filter(|&d| removed & (0x0001 << (d - 1)) != 0) {
+                    sq.remove_possibility_unchecked(digit);
+                    eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit });
+                }
+            }
+
+            // A digit with one home left in the region rules out the
+            // other candidates of that square.
+            for digit in (1..=9).filter(|&d| placed & (0x0001 << (d - 1)) == 0) {
+                let mut homes = region.iter().filter(|&&i| !grid.0[i].is_set() && grid.0[i].is_possible(digit));
+                if let (Some(&i), None) = (homes.next(), homes.next()) {
+                    let sq = &mut grid.0[i];
+                    let others = sq.digit_bits() & !(0x0001 << (digit - 1));
+                    for other in (1..=9).filter(|&d| others & (0x0001 << (d - 1)) != 0) {
+                        sq.remove_possibility_unchecked(other);
+                        eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit: other });
+                    }
+                }
+            }
+        }
+        eliminations
+    }
+
+    fn is_satisfied(&self, grid: &SudokuGrid) -> bool
+    {
+        self.regions.iter().all(|region| {
+            let mut seen = 0u16;
+            region.iter().filter_map(|&i| grid.0[i].get().ok()).all(|digit| {
+                let bit = 0x0001 << (digit - 1);
+                let fresh = seen & bit == 0;
+                seen |= bit;
+                fresh
+            })
+        })
+    }
+}
+
+
+/// The constraints a solution must satisfy together.
+#[derive(Debug, Clone)]
+pub struct ConstraintSet(Vec<Arc<dyn Constraint>>);
+
+
+impl Default for ConstraintSet {
+
+    fn default() -> ConstraintSet
+    {
+        ConstraintSet::standard()
+    }
+}
+
+
+impl ConstraintSet {
+
+    /// No constraints at all, so any filling of the grid is a solution.
+    pub fn empty() -> ConstraintSet
+    {
+        ConstraintSet(Vec::new())
+    }
+
+    /// The rows, columns and boxes of classic sudoku.
+    pub fn standard() -> ConstraintSet
+    {
+        ConstraintSet::empty()
+            .with(UniqueDigits::rows())
+            .with(UniqueDigits::columns())
+            .with(UniqueDigits::boxes())
+    }
+
+    /// Classic sudoku with no digit repeated on either main diagonal.
+    pub fn sudoku_x() -> ConstraintSet
+    {
+        ConstraintSet::standard().with(UniqueDigits::diagonals())
+    }
+
+    /// Jigsaw sudoku, with the boxes replaced by the regions of `map`.
+    pub fn jigsaw(map: &RegionMap) -> ConstraintSet
+    {
+        ConstraintSet::empty()
+            .with(UniqueDigits::rows())
+            .with(UniqueDigits::columns())
+            .with(UniqueDigits::from(map))
+    }
+
+    /// Add a constraint to the set.
+    pub fn with<C: Constraint + 'static>(mut self, constraint: C) -> ConstraintSet
+    {
+        self.push(constraint);
+        self
+    }
+
+    pub fn push<C: Constraint + 'static>(&mut self, constraint: C)
+    {
+        self.0.push(Arc::new(constraint));
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Constraint>
+    {
+        self.0.iter().map(|c| c.as_ref())
+    }
+
+    /// Run one elimination pass of every constraint.
+    pub fn eliminate(&self, grid: &mut SudokuGrid) -> Eliminations
+    {
+        self.0.iter().flat_map(|c| c.eliminate(grid)).collect()
+    }
+
+    /// Whether the set squares keep to every constraint.
+    pub fn is_satisfied(&self, grid: &SudokuGrid) -> bool
+    {
+        self.0.iter().all(|c| c.is_satisfied(grid))
+    }
+
+    /// Whether the grid is complete and keeps to every constraint.
+    pub fn check(&self, grid: &SudokuGrid) -> bool
+    {
+        grid.is_complete() && self.is_satisfied(grid)
+    }
+
+    /// Eliminate candidates and fill squares left with one, repeating
+    /// until nothing changes, like `SudokuGrid::propagate`. Returns the
+    /// number of squares filled, or an error once a constraint is broken
+    /// or a square runs out of candidates.
+    pub fn propagate(&self, grid: &mut SudokuGrid) -> Result<usize, SudokuError>
+    {
+        let mut placed = 0;
+        loop {
+            self.eliminate(grid);
+            if !self.is_satisfied(grid) {
+                return Err(SudokuError::NoSolution);
+            }
+            if let Some(sq) = grid.0.iter().find(|sq| !sq.is_set() && sq.possibilities_number() == 0) {
+                return Err(SudokuError::NoCandidates { row: sq.row(), col: sq.col() });
+            }
+
+            let singles: Vec<(u8, u8, u8)> = grid.0.iter()
+                .filter(|sq| !sq.is_set() && sq.possibilities_number() == 1)
+                .map(|sq| (sq.row(), sq.col(), sq.digit_bits().trailing_zeros() as u8 + 1))
+                .collect();
+            if singles.is_empty() {
+                return Ok(placed);
+            }
+            for (row, col, digit) in singles {
+                grid.set_unchecked(row, col, digit);
+                placed += 1;
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solved() -> SudokuGrid
+    {
+        crate::sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ]
+    }
+
+    #[test]
+    fn test_standard_matches_check()
+    {
+        let standard = ConstraintSet::standard();
+        let grid = solved();
+        assert!(standard.check(&grid));
+        assert_eq!(standard.check(&grid), grid.check().unwrap());
+
+        let mut broken = grid.clone();
+        broken.set((1, 1), 7).unwrap();
+        assert!(!standard.is_satisfied(&broken));
+        assert!(!standard.check(&SudokuGrid::new()));
+        assert!(standard.is_satisfied(&SudokuGrid::new()));
+    }
+
+    #[test]
+    fn test_eliminate()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 5).unwrap();
+        let eliminations = ConstraintSet::empty().with(UniqueDigits::rows()).eliminate(&mut grid);
+        assert_eq!(eliminations.len(), 8);
+        assert!(eliminations.contains(&Elimination { row: 1, col: 9, digit: 5 }));
+        assert!(!grid.0[8].is_possible(5));
+        assert!(grid.0[9].is_possible(5));
+
+        let custom = UniqueDigits::new(&[[(1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (6, 6), (7, 7), (8, 8), (9, 9)]])
+            .unwrap();
+        assert_eq!(custom.eliminate(&mut grid), vec![
+            Elimination { row: 2, col: 2, digit: 5 }, Elimination { row: 3, col: 3, digit: 5 },
+            Elimination { row: 4, col: 4, digit: 5 }, Elimination { row: 5, col: 5, digit: 5 },
+            Elimination { row: 6, col: 6, digit: 5 }, Elimination { row: 7, col: 7, digit: 5 },
+            Elimination { row: 8, col: 8, digit: 5 }, Elimination { row: 9, col: 9, digit: 5 }
+        ]);
+    }
+
+    #[test]
+    fn test_propagate()
+    {
+        let mut grid = solved();
+        grid.clear((5, 5)).unwrap();
+        grid.clear((9, 9)).unwrap();
+        assert_eq!(ConstraintSet::standard().propagate(&mut grid), Ok(2));
+        assert_eq!(grid, solved());
+
+        let mut stuck = SudokuGrid::new();
+        for (col, digit) in (1..=8).zip(1..=8) {
+            stuck.set((1, col), digit).unwrap();
+        }
+        stuck.set((2, 9), 9).unwrap();
+        assert_eq!(
+            ConstraintSet::standard().propagate(&mut stuck),
+            Err(SudokuError::NoCandidates { row: 1, col: 9 })
+        );
+    }
+
+    #[test]
+    fn test_diagonals()
+    {
+        let UniqueDigits { regions } = UniqueDigits::diagonals();
+        assert_eq!(regions[0], [0, 10, 20, 30, 40, 50, 60, 70, 80]);
+        assert_eq!(regions[1], [8, 16, 24, 32, 40, 48, 56, 64, 72]);
+
+        // The main diagonal of the solved grid holds 9 twice.
+        let grid = solved();
+        assert!(ConstraintSet::standard().check(&grid));
+        assert!(!ConstraintSet::sudoku_x().check(&grid));
+        assert_eq!(grid.check_with(&ConstraintSet::sudoku_x()), Ok(false));
+    }
+
+    #[test]
+    fn test_hidden_single_in_region()
+    {
+        // Only the centre square of the main diagonal can still hold a 1.
+        let mut grid = SudokuGrid::new();
+        for k in (1..=9).filter(|&k| k != 5) {
+            grid.remove_candidate((k, k), 1).unwrap();
+        }
+        let eliminations = UniqueDigits::diagonals().eliminate(&mut grid);
+        assert_eq!(eliminations.len(), 8);
+        assert!(eliminations.iter().all(|e| (e.row, e.col) == (5, 5) && e.digit != 1));
+        assert_eq!(grid.square((5, 5)).candidates().collect::<Vec<_>>(), vec![1]);
+
+        // Nothing is left to find on a second pass.
+        assert!(UniqueDigits::diagonals().eliminate(&mut grid).is_empty());
+    }
+
+    #[test]
+    fn test_new_rejects_positions_off_the_grid()
+    {
+        let mut region: [Position; 9] = std::array::from_fn(|k| (1, k as u8 + 1));
+        assert!(UniqueDigits::new(&[region]).is_ok());
+        region[4] = (0, 5);
+        assert_eq!(UniqueDigits::new(&[region]), Err(SudokuError::InvalidPosition { row: 0, col: 5 }));
+        region[4] = (1, 10);
+        assert_eq!(UniqueDigits::new(&[region]), Err(SudokuError::InvalidPosition { row: 1, col: 10 }));
+    }
+}
@@ -0,0 +1,204 @@
+use std::convert::TryInto;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Pos, SudokuError, SudokuGrid};
+use crate::techniques::Elimination;
+
+use super::{Constraint, Eliminations};
+
+
+/// Whether a marked square takes an even or an odd digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parity {
+    Even,
+    Odd
+}
+
+
+impl Parity {
+
+    /// The candidate bits of the digits with this parity.
+    pub fn mask(self) -> u16
+    {
+        match self {
+            Parity::Even => 0x00AA,
+            Parity::Odd => 0x0155
+        }
+    }
+}
+
+
+/// Squares marked even-only or odd-only.
+///
+/// Markings are written as 81 characters in row-major order: `E` for even,
+/// `O` for odd and `.` for an unmarked square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EvenOdd([Option<Parity>; 81]);
+
+
+impl Default for EvenOdd {
+
+    fn default() -> EvenOdd
+    {
+        EvenOdd::new()
+    }
+}
+
+
+impl EvenOdd {
+
+    /// No marked squares.
+    pub fn new() -> EvenOdd
+    {
+        EvenOdd([None; 81])
+    }
+
+    pub fn mark<P>(&mut self, pos: P, parity: Parity) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        self.0[pos.index()] = Some(parity);
+        Ok(())
+    }
+
+    /// The marking of the square at `pos`, `None` if unmarked.
+    pub fn parity<P>(&self, pos: P) -> Result<Option<Parity>, SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        Ok(self.0[pos.index()])
+    }
+}
+
+
+impl Constraint for EvenOdd {
+
+    fn eliminate(&self, grid: &mut SudokuGrid) -> Eliminations
+    {
+        let mut eliminations = Vec::new();
+        for (sq, parity) in grid.0.iter_mut().zip(self.0.iter()) {
+            let removed = match parity {
+                Some(parity) if !sq.is_set() => sq.digit_bits() & !parity.mask(),
+                _ => 0
+            };
+            for digit in (1..=9).filter(|&d| removed & (0x0001 << (d - 1)) != 0) {
+                sq.remove_possibility_unchecked(digit);
+                eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit });
+            }
+        }
+        eliminations
+    }
+
+    fn is_satisfied(&self, grid: &SudokuGrid) -> bool
+    {
+        grid.0.iter().zip(self.0.iter()).all(|(sq, parity)| match (sq.get(), parity) {
+            (Ok(digit), Some(parity)) => parity.mask() & (0x0001 << (digit - 1)) != 0,
+            _ => true
+        })
+    }
+}
+
+
+impl fmt::Display for EvenOdd {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        for parity in self.0.iter() {
+            f.write_str(match parity {
+                Some(Parity::Even) => "E",
+                Some(Parity::Odd) => "O",
+                None => "."
+            })?;
+        }
+        Ok(())
+    }
+}
+
+
+impl FromStr for EvenOdd {
+    type Err = SudokuError;
+
+    /// Parse 81 markings, accepting either case and `0` for unmarked.
+    fn from_str(s: &str) -> Result<EvenOdd, SudokuError>
+    {
+        let s = s.trim();
+        let length = s.chars().count();
+        if length != 81 {
+            return Err(SudokuError::InvalidLength { length });
+        }
+
+        let mut markings = EvenOdd::new();
+        for (position, character) in s.chars().enumerate() {
+            markings.0[position] = match character {
+                'E' | 'e' => Some(Parity::Even),
+                'O' | 'o' => Some(Parity::Odd),
+                '.' | '0' => None,
+                _ => return Err(SudokuError::InvalidCharacter { position, character })
+            };
+        }
+        Ok(markings)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConstraintSet;
+    use crate::solver::SolverConfig;
+
+    #[test]
+    fn test_parse_and_display()
+    {
+        let line = format!("{:.<81}", "EO.e");
+        let markings: EvenOdd = line.parse().unwrap();
+        assert_eq!(markings.parity((1, 1)), Ok(Some(Parity::Even)));
+        assert_eq!(markings.parity((1, 2)), Ok(Some(Parity::Odd)));
+        assert_eq!(markings.parity((1, 3)), Ok(None));
+        assert_eq!(markings.parity((0, 3)), Err(SudokuError::InvalidPosition { row: 0, col: 3 }));
+        assert_eq!(markings.parity((3, 10)), Err(SudokuError::InvalidPosition { row: 3, col: 10 }));
+        assert_eq!(markings.to_string(), format!("{:.<81}", "EO.E"));
+        assert_eq!(markings.to_string().parse(), Ok(markings));
+        assert_eq!(
+            format!("{:.<81}", "X").parse::<EvenOdd>(),
+            Err(SudokuError::InvalidCharacter { position: 0, character: 'X' })
+        );
+    }
+
+    #[test]
+    fn test_masks()
+    {
+        let mut markings = EvenOdd::new();
+        markings.mark((1, 1), Parity::Even).unwrap();
+        markings.mark((1, 2), Parity::Odd).unwrap();
+        let mut grid = SudokuGrid::new();
+        assert_eq!(markings.eliminate(&mut grid).len(), 9);
+        assert_eq!(grid.0[0].digit_bits(), Parity::Even.mask());
+        assert_eq!(grid.0[1].digit_bits(), Parity::Odd.mask());
+
+        grid.set((1, 1), 3).unwrap();
+        assert!(!markings.is_satisfied(&grid));
+    }
+
+    #[test]
+    fn test_solve()
+    {
+        // Mark the parities of the solution's first row on a bare grid.
+        let mut markings = EvenOdd::new();
+        for (col, &digit) in (1..=9).zip([9, 8, 5, 4, 2, 3, 7, 1, 6].iter()) {
+            let parity = if digit % 2 == 0 { Parity::Even } else { Parity::Odd };
+            markings.mark((1, col), parity).unwrap();
+        }
+        let constraints = ConstraintSet::standard().with(markings);
+        let solution = SudokuGrid::new()
+            .solve_with(&SolverConfig::new().constraints(constraints.clone()))
+            .unwrap();
+        assert!(constraints.check(&solution));
+        assert!(markings.is_satisfied(&solution));
+    }
+}
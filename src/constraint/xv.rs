@@ -0,0 +1,111 @@
+use crate::{SudokuError, SudokuGrid};
+
+use super::{Constraint, Eliminations};
+use super::adjacency::{Edge, PairRule, all_edges, edge_indices};
+
+
+/// XV markers on the edges between squares: the digits either side of an
+/// X sum to 10 and those either side of a V sum to 5. With the negative
+/// constraint no unmarked edge may sum to 5 or 10.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XV {
+    x: PairRule,
+    v: PairRule,
+    unmarked: Option<PairRule>
+}
+
+
+/// Edge indices with the earlier square first, so the same edge always
+/// compares equal.
+fn ordered(edges: &[Edge]) -> Result<Vec<(usize, usize)>, SudokuError>
+{
+    edges.iter().map(|&edge| {
+        let (a, b) = edge_indices(edge)?;
+        Ok((a.min(b), a.max(b)))
+    }).collect()
+}
+
+
+impl XV {
+
+    pub fn new(x: &[Edge], v: &[Edge]) -> Result<XV, SudokuError>
+    {
+        Ok(XV {
+            x: PairRule::new(ordered(x)?, |a, b| a + b == 10),
+            v: PairRule::new(ordered(v)?, |a, b| a + b == 5),
+            unmarked: None
+        })
+    }
+
+    /// Also forbid sums of 5 and 10 across every edge without a marker.
+    pub fn negative(mut self) -> XV
+    {
+        let marked: Vec<(usize, usize)> = self.x.pairs().iter()
+            .chain(self.v.pairs().iter())
+            .copied()
+            .collect();
+        let unmarked = all_edges().filter(|edge| !marked.contains(edge)).collect();
+        self.unmarked = Some(PairRule::new(unmarked, |a, b| a + b != 5 && a + b != 10));
+        self
+    }
+}
+
+
+impl Constraint for XV {
+
+    fn eliminate(&self, grid: &mut SudokuGrid) -> Eliminations
+    {
+        let mut eliminations = Vec::new();
+        self.x.eliminate(grid, &mut eliminations);
+        self.v.eliminate(grid, &mut eliminations);
+        if let Some(unmarked) = &self.unmarked {
+            unmarked.eliminate(grid, &mut eliminations);
+        }
+        eliminations
+    }
+
+    fn is_satisfied(&self, grid: &SudokuGrid) -> bool
+    {
+        self.x.is_satisfied(grid) && self.v.is_satisfied(grid)
+            && self.unmarked.as_ref().is_none_or(|rule| rule.is_satisfied(grid))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markers()
+    {
+        let xv = XV::new(&[((1, 2), (1, 1))], &[((2, 1), (3, 1))]).unwrap();
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 3).unwrap();
+        xv.eliminate(&mut grid);
+        assert_eq!(grid.0[1].digit_bits(), 0x0040);
+        assert_eq!(grid.0[9].digit_bits(), 0x000F);
+
+        grid.set((2, 1), 4).unwrap();
+        grid.set((3, 1), 2).unwrap();
+        assert!(!xv.is_satisfied(&grid));
+        assert!(XV::new(&[((1, 1), (2, 2))], &[]).is_err());
+    }
+
+    #[test]
+    fn test_negative()
+    {
+        let xv = XV::new(&[((1, 1), (1, 2))], &[]).unwrap().negative();
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 3).unwrap();
+        grid.set((1, 2), 7).unwrap();
+        assert!(xv.is_satisfied(&grid));
+
+        grid.set((2, 1), 2).unwrap();
+        assert!(!xv.is_satisfied(&grid));
+        grid.clear((2, 1)).unwrap();
+        xv.eliminate(&mut grid);
+        assert!(!grid.0[9].is_possible(2) && !grid.0[9].is_possible(7));
+        assert!(grid.0[10].is_possible(2) && !grid.0[10].is_possible(3));
+    }
+}
@@ -0,0 +1,109 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::NonZeroU8;
+
+use crate::SudokuError;
+
+
+/// A digit from 1 to 9. Holding one means the value has been checked. The
+/// grid and square setters take a `Digit` or a `u8`, and only the latter
+/// can fail with `InvalidDigit`. Converts to and from `u8` for convenience:
+///
+/// ```
+/// use std::convert::TryFrom;
+/// use bitsudoku::Digit;
+///
+/// let digit = Digit::try_from(7).unwrap();
+/// assert_eq!(u8::from(digit), 7);
+/// assert!(Digit::try_from(0).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Digit(NonZeroU8);
+
+
+impl Digit {
+
+    /// The digits 1 to 9 in order.
+    pub const ALL: [Digit; 9] = [
+        Digit::unwrap(1), Digit::unwrap(2), Digit::unwrap(3),
+        Digit::unwrap(4), Digit::unwrap(5), Digit::unwrap(6),
+        Digit::unwrap(7), Digit::unwrap(8), Digit::unwrap(9)
+    ];
+
+    const fn unwrap(value: u8) -> Digit
+    {
+        match Digit::new(value) {
+            Some(digit) => digit,
+            None => panic!("Not a digit.")
+        }
+    }
+
+    /// The digit `value`, or `None` unless it is from 1 to 9.
+    pub const fn new(value: u8) -> Option<Digit>
+    {
+        match NonZeroU8::new(value) {
+            Some(value) if value.get() <= 9 => Some(Digit(value)),
+            _ => None
+        }
+    }
+
+    pub const fn get(self) -> u8
+    {
+        self.0.get()
+    }
+
+    /// The digit's candidate bit, bit `d - 1` for digit `d`.
+    pub const fn bit(self) -> u16
+    {
+        0x0001 << (self.0.get() - 1)
+    }
+}
+
+
+impl TryFrom<u8> for Digit {
+    type Error = SudokuError;
+
+    fn try_from(value: u8) -> Result<Digit, SudokuError>
+    {
+        Digit::new(value).ok_or(SudokuError::InvalidDigit { digit: u16::from(value) })
+    }
+}
+
+
+impl From<Digit> for u8 {
+
+    fn from(digit: Digit) -> u8
+    {
+        digit.get()
+    }
+}
+
+
+impl fmt::Display for Digit {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{}", self.0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions()
+    {
+        for value in 1..=9u8 {
+            let digit = Digit::try_from(value).unwrap();
+            assert_eq!(digit.get(), value);
+            assert_eq!(digit.bit(), 0x0001 << (value - 1));
+            assert_eq!(digit.to_string(), value.to_string());
+            assert_eq!(Digit::ALL[usize::from(value - 1)], digit);
+        }
+        assert_eq!(Digit::try_from(0), Err(SudokuError::InvalidDigit { digit: 0 }));
+        assert_eq!(Digit::new(10), None);
+        assert!(Digit::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
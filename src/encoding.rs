@@ -0,0 +1,152 @@
+//! Dense binary encoding of grids.
+//!
+//! The first byte holds flags, followed by 41 bytes packing one digit per
+//! nibble (high nibble first, 0 for empty). When candidates are included
+//! the 9 bit mask of every empty square follows as a little-endian bit
+//! stream.
+
+use crate::{SudokuGrid, SudokuError};
+
+
+const CANDIDATES_FLAG: u8 = 0x01;
+const DIGIT_BYTES: usize = 41;
+
+
+impl SudokuGrid {
+
+    /// Encode the set squares in 42 bytes, dropping the candidates.
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        self.encode(false)
+    }
+
+    /// Encode the set squares together with the candidate masks of the
+    /// empty squares.
+    pub fn to_bytes_with_candidates(&self) -> Vec<u8>
+    {
+        self.encode(true)
+    }
+
+    fn encode(&self, candidates: bool) -> Vec<u8>
+    {
+        let mut bytes = vec![0u8; 1 + DIGIT_BYTES];
+        if candidates {
+            bytes[0] |= CANDIDATES_FLAG;
+        }
+
+        for (i, sq) in self.0.iter().enumerate() {
+            let digit = sq.get().unwrap_or(0);
+            bytes[1 + i / 2] |= if i % 2 == 0 { digit << 4 } else { digit };
+        }
+
+        if candidates {
+            let mut bits = 0usize;
+            for sq in self.0.iter().filter(|sq| !sq.is_set()) {
+                let mask = sq.digit_bits();
+                for b in 0..9 {
+                    if bits.is_multiple_of(8) {
+                        bytes.push(0);
+                    }
+                    if mask & (0x0001 << b) != 0 {
+                        *bytes.last_mut().unwrap() |= 0x01 << (bits % 8);
+                    }
+                    bits += 1;
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Decode a grid written by `to_bytes` or `to_bytes_with_candidates`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SudokuGrid, SudokuError>
+    {
+        if bytes.len() < 1 + DIGIT_BYTES {
+            return Err(SudokuError::InvalidLength { length: bytes.len() });
+        }
+
+        let mut grid = SudokuGrid::new();
+        for i in 0..81 {
+            let byte = bytes[1 + i / 2];
+            let digit = if i % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+            match digit {
+                0 => {},
+                1..=9 => grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, digit),
+                d => return Err(SudokuError::InvalidDigit { digit: u16::from(d) })
+            }
+        }
+
+        let rest = &bytes[1 + DIGIT_BYTES..];
+        if bytes[0] & CANDIDATES_FLAG == 0 {
+            return match rest.len() {
+                0 => Ok(grid),
+                _ => Err(SudokuError::InvalidLength { length: bytes.len() })
+            };
+        }
+
+        let empty = grid.0.iter().filter(|sq| !sq.is_set()).count();
+        if rest.len() != (9*empty).div_ceil(8) {
+            return Err(SudokuError::InvalidLength { length: bytes.len() });
+        }
+
+        let mut bits = 0usize;
+        for sq in grid.0.iter_mut().filter(|sq| !sq.is_set()) {
+            for digit in 1..=9 {
+                if rest[bits / 8] & (0x01 << (bits % 8)) == 0 {
+                    sq.remove_possibility_unchecked(digit);
+                }
+                bits += 1;
+            }
+        }
+        Ok(grid)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_round_trip()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 9).unwrap();
+        grid.set((1, 2), 3).unwrap();
+        grid.set((9, 9), 1).unwrap();
+
+        let bytes = grid.to_bytes();
+        assert_eq!(bytes.len(), 42);
+        assert_eq!(bytes[1], 0x93);
+        assert_eq!(bytes[41], 0x10);
+
+        let back = SudokuGrid::from_bytes(&bytes).unwrap();
+        assert_eq!(back.0[0].get().unwrap(), 9);
+        assert_eq!(back.0[80].get().unwrap(), 1);
+        assert!(!back.0[2].is_set());
+    }
+
+    #[test]
+    fn test_bytes_with_candidates()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 9).unwrap();
+        grid.propagate().unwrap();
+
+        let bytes = grid.to_bytes_with_candidates();
+        assert_eq!(bytes.len(), 42 + 90);
+
+        let back = SudokuGrid::from_bytes(&bytes).unwrap();
+        assert!(!back.0[1].is_possible(9));
+        assert!(back.0[1].is_possible(8));
+        assert!(back.0[40].is_possible(9));
+    }
+
+    #[test]
+    fn test_bytes_errors()
+    {
+        assert!(SudokuGrid::from_bytes(&[0; 10]).is_err());
+        let mut bytes = SudokuGrid::new().to_bytes();
+        bytes[1] = 0xA0;
+        assert!(SudokuGrid::from_bytes(&bytes).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt;
+
+
+/// The error type for every fallible operation in the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SudokuError {
+    NonUniqueSet,
+    IsAlreadySet,
+    NotSet,
+    InvalidDigit { digit: u16 },
+    InvalidPosition { row: u8, col: u8 },
+    NoCandidates { row: u8, col: u8 },
+    NoUniqueSolution,
+    GenerationFailed,
+    InvalidLength { length: usize },
+    InvalidCharacter { position: usize, character: char },
+    Io { kind: std::io::ErrorKind },
+    InvalidSwap { first: u8, second: u8 },
+    InvalidRelabeling,
+    IsGiven { row: u8, col: u8 },
+    NoSolution,
+    LimitExceeded,
+    Cancelled,
+    InvalidRegion { region: u8 },
+    InvalidEdge { first: (u8, u8), second: (u8, u8) },
+    RenderFailed,
+    InvalidEntry { row: u8, col: u8, digit: u8 },
+    DuplicateSquare { row: u8, col: u8 },
+    DuplicateDigit { row: u8, col: u8, digit: u8 },
+    InvalidRecord { line: u64 }
+}
+
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self {
+            SudokuError::NonUniqueSet =>
+                f.write_str("square has more than one candidate left"),
+            SudokuError::IsAlreadySet =>
+                f.write_str("square is already set"),
+            SudokuError::NotSet =>
+                f.write_str("square is not set"),
+            SudokuError::InvalidDigit { digit } =>
+                write!(f, "invalid digit {}", digit),
+            SudokuError::InvalidPosition { row, col } =>
+                write!(f, "invalid position ({}, {})", row, col),
+            SudokuError::NoCandidates { row, col } =>
+                write!(f, "square ({}, {}) has no candidates left", row, col),
+            SudokuError::NoUniqueSolution =>
+                f.write_str("puzzle does not have a unique solution"),
+            SudokuError::GenerationFailed =>
+                f.write_str("could not generate a puzzle meeting the requirements"),
+            SudokuError::InvalidLength { length } =>
+                write!(f, "invalid puzzle length {}", length),
+            SudokuError::InvalidCharacter { position, character } =>
+                write!(f, "invalid character {:?} at position {}", character, position),
+            SudokuError::Io { kind } =>
+                write!(f, "i/o error: {}", std::io::Error::from(*kind)),
+            SudokuError::InvalidSwap { first, second } =>
+                write!(f, "cannot swap {} and {}", first, second),
+            SudokuError::InvalidRelabeling =>
+                f.write_str("relabeling is not a permutation of the digits"),
+            SudokuError::IsGiven { row, col } =>
+                write!(f, "square ({}, {}) is a given clue", row, col),
+            SudokuError::NoSolution =>
+                f.write_str("puzzle has no solution"),
+            SudokuError::LimitExceeded =>
+                f.write_str("search gave up after reaching its node or time limit"),
+            SudokuError::Cancelled =>
+                f.write_str("search was cancelled"),
+            SudokuError::InvalidRegion { region } =>
+                write!(f, "region {} does not have nine squares", region),
+            SudokuError::InvalidEdge { first, second } =>
+                write!(f, "squares {:?} and {:?} do not share an edge", first, second),
+            SudokuError::RenderFailed =>
+                f.write_str("could not render the grid as an image"),
+            SudokuError::InvalidEntry { row, col, digit } =>
+                write!(f, "invalid digit {} for square ({}, {})", digit, row, col),
+            SudokuError::DuplicateSquare { row, col } =>
+                write!(f, "square ({}, {}) is given more than once", row, col),
+            SudokuError::DuplicateDigit { row, col, digit } =>
+                write!(f, "digit {} at ({}, {}) repeats in a row, column or box", digit, row, col),
+            SudokuError::InvalidRecord { line } =>
+                write!(f, "invalid record on line {}", line)
+        }
+    }
+}
+
+impl Error for SudokuError {}
+
+/// For `Digit`'s own, infallible, conversion to `Digit`, so that methods
+/// taking `TryInto<Digit>` accept both it and `u8`.
+impl From<std::convert::Infallible> for SudokuError {
+    fn from(never: std::convert::Infallible) -> SudokuError
+    {
+        match never {}
+    }
+}
+
+impl From<std::io::Error> for SudokuError {
+    fn from(err: std::io::Error) -> SudokuError
+    {
+        SudokuError::Io { kind: err.kind() }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display()
+    {
+        let err = SudokuError::InvalidPosition { row: 0, col: 3 };
+        assert_eq!(err.to_string(), "invalid position (0, 3)");
+
+        let boxed: Box<dyn Error> = Box::new(SudokuError::NotSet);
+        assert_eq!(boxed.to_string(), "square is not set");
+    }
+}
@@ -0,0 +1,336 @@
+use std::convert::{TryFrom, TryInto};
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+use crate::{Hint, Pos, SudokuGrid, SudokuError};
+use crate::solver::{LogicalSolver, SolveStep};
+use crate::tables::PEERS;
+use crate::techniques::Elimination;
+
+
+/// A change to one square of an `ObservedGrid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GridEvent {
+    CellSet { row: u8, col: u8, digit: u8 },
+    CellCleared { row: u8, col: u8 },
+    CandidateRemoved { row: u8, col: u8, digit: u8 }
+}
+
+
+/// Somewhere to send events: a callback or the sending end of a channel.
+enum Listener {
+    Callback(Box<dyn FnMut(&GridEvent) + Send>),
+    Channel(Sender<GridEvent>)
+}
+
+
+/// A grid that reports each change made to it, so a GUI can redraw just
+/// the squares that changed, whether a user or a solver made them. Each
+/// method fires its `GridEvent`s as it changes a square, and a call that
+/// changes nothing fires none.
+pub struct ObservedGrid {
+    grid: SudokuGrid,
+    listeners: Vec<Listener>
+}
+
+
+impl ObservedGrid {
+
+    pub fn new(grid: SudokuGrid) -> ObservedGrid
+    {
+        ObservedGrid { grid, listeners: Vec::new() }
+    }
+
+    pub fn grid(&self) -> &SudokuGrid
+    {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> SudokuGrid
+    {
+        self.grid
+    }
+
+    /// Call `callback` with every event from now on.
+    pub fn subscribe<F>(&mut self, callback: F)
+    where
+        F: FnMut(&GridEvent) + Send + 'static
+    {
+        self.listeners.push(Listener::Callback(Box::new(callback)));
+    }
+
+    /// A channel that receives every event from now on. Dropping the
+    /// receiver unsubscribes it.
+    pub fn channel(&mut self) -> Receiver<GridEvent>
+    {
+        let (sender, receiver) = channel();
+        self.listeners.push(Listener::Channel(sender));
+        receiver
+    }
+
+    fn notify(&mut self, event: GridEvent)
+    {
+        self.listeners.retain_mut(|listener| match listener {
+            Listener::Callback(callback) => {
+                callback(&event);
+                true
+            },
+            Listener::Channel(sender) => sender.send(event).is_ok()
+        });
+    }
+
+    pub fn set<P>(&mut self, pos: P, digit: u8) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let before = self.grid.get::<Pos>(pos)?;
+        self.grid.set::<Pos, u8>(pos, digit)?;
+        if before != Some(digit) {
+            self.notify(GridEvent::CellSet { row: pos.row(), col: pos.col(), digit });
+        }
+        Ok(())
+    }
+
+    pub fn clear<P>(&mut self, pos: P) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let before = self.grid.get::<Pos>(pos)?;
+        self.grid.clear::<Pos>(pos)?;
+        if before.is_some() {
+            self.notify(GridEvent::CellCleared { row: pos.row(), col: pos.col() });
+        }
+        Ok(())
+    }
+
+    pub fn remove_candidate<P>(&mut self, pos: P, digit: u8) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        if self.grid.remove_candidate::<Pos, u8>(pos, digit)? {
+            self.notify(GridEvent::CandidateRemoved { row: pos.row(), col: pos.col(), digit });
+        }
+        Ok(())
+    }
+
+    /// Remove `digit` from the candidates of the peers of square `index`.
+    fn eliminate_from_peers(&mut self, index: usize, digit: u8) -> Result<(), SudokuError>
+    {
+        for &j in PEERS[index].iter() {
+            let pos = Pos::from_index(usize::from(j));
+            self.remove_candidate(pos, digit)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the digit of every set square from its peers' candidates.
+    fn eliminate_all(&mut self) -> Result<(), SudokuError>
+    {
+        for i in 0..81 {
+            if let Ok(digit) = self.grid.0[i].get() {
+                self.eliminate_from_peers(i, digit)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill each square of `placements`, removing its digit from its
+    /// peers, then remove the candidates of `eliminations`.
+    fn apply_changes(
+        &mut self,
+        placements: &[(u8, u8, u8)],
+        eliminations: &[Elimination]
+    ) -> Result<(), SudokuError>
+    {
+        for &(row, col, digit) in placements {
+            self.set((row, col), digit)?;
+            self.eliminate_from_peers(Pos::try_from((row, col))?.index(), digit)?;
+        }
+        for elimination in eliminations {
+            self.remove_candidate((elimination.row, elimination.col), elimination.digit)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the move of a hint, such as one from `SudokuGrid::hint`,
+    /// reporting each change.
+    pub fn apply(&mut self, hint: &Hint) -> Result<(), SudokuError>
+    {
+        let placements: Vec<_> = hint.placement.into_iter().collect();
+        self.apply_changes(&placements, &hint.eliminations)
+    }
+
+    /// Take the next step of `solver`, as `LogicalSolver::next_step` would
+    /// after bringing the candidates up to date, reporting each change.
+    /// Returns `None` once no technique makes progress.
+    pub fn step(&mut self, solver: &LogicalSolver) -> Result<Option<SolveStep>, SudokuError>
+    {
+        self.eliminate_all()?;
+        let mut work = self.grid.clone();
+        let step = match solver.next_step(&mut work)? {
+            Some(step) => step,
+            None => return Ok(None)
+        };
+        self.apply_changes(&step.placements, &step.eliminations)?;
+        Ok(Some(step))
+    }
+
+    /// Take steps of `solver` until none makes progress, as
+    /// `LogicalSolver::solve` does, reporting each change.
+    pub fn solve(&mut self, solver: &LogicalSolver) -> Result<Vec<SolveStep>, SudokuError>
+    {
+        let mut steps = Vec::new();
+        while let Some(step) = self.step(solver)? {
+            steps.push(step);
+        }
+        Ok(steps)
+    }
+
+    /// Run `SudokuGrid::propagate`, reporting every elimination and
+    /// placement as it is made. As there, changes made before a square
+    /// runs out of candidates are kept, and they are reported.
+    pub fn propagate(&mut self) -> Result<usize, SudokuError>
+    {
+        self.eliminate_all()?;
+
+        let mut placed = 0;
+        while let Some(i) = self.grid.0.iter().position(
+            |sq| !sq.is_set() && sq.possibilities_number() == 1
+        ) {
+            let pos = Pos::from_index(i);
+            let digit = self.grid.0[i].candidates().next().unwrap_or_default();
+            self.set(pos, digit)?;
+            self.eliminate_from_peers(i, digit)?;
+            placed += 1;
+        }
+
+        match self.grid.0.iter().find(|sq| !sq.is_set() && sq.possibilities_number() == 0) {
+            Some(sq) => Err(SudokuError::NoCandidates { row: sq.row(), col: sq.col() }),
+            None => Ok(placed)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    #[test]
+    fn test_callback_events()
+    {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut observed = ObservedGrid::new(SudokuGrid::new());
+        let log = Arc::clone(&seen);
+        observed.subscribe(move |event| log.lock().unwrap().push(*event));
+
+        observed.set((1, 1), 5).unwrap();
+        observed.remove_candidate((1, 2), 3).unwrap();
+        observed.remove_candidate((1, 2), 3).unwrap();
+        observed.clear((1, 1)).unwrap();
+        assert!(observed.set((1, 1), 10).is_err());
+
+        assert_eq!(*seen.lock().unwrap(), vec![
+            GridEvent::CellSet { row: 1, col: 1, digit: 5 },
+            GridEvent::CandidateRemoved { row: 1, col: 2, digit: 3 },
+            GridEvent::CellCleared { row: 1, col: 1 }
+        ]);
+    }
+
+    #[test]
+    fn test_channel_events()
+    {
+        // Singles don't finish this one, so some squares keep candidates.
+        let puzzle: SudokuGrid = concat!(
+            "..............3.85..1.2.......5.7.....4...1...9.......5......",
+            "73..2.1........4...9"
+        ).parse().unwrap();
+        let mut observed = ObservedGrid::new(puzzle.clone());
+        let events = observed.channel();
+        let placed = observed.propagate().unwrap();
+
+        let received: Vec<GridEvent> = events.try_iter().collect();
+        let set = received.iter().filter(|event| matches!(event, GridEvent::CellSet { .. })).count();
+        assert_eq!(set, placed);
+        assert!(received.iter().any(|event| matches!(event, GridEvent::CandidateRemoved { .. })));
+
+        // Replaying the events gives the same grid as propagating directly.
+        let mut replayed = puzzle.clone();
+        replay(&mut replayed, received.into_iter());
+        let mut direct = puzzle;
+        direct.propagate().unwrap();
+        assert_eq!(replayed, direct);
+        assert_eq!(&replayed, observed.grid());
+    }
+
+    /// Apply `events` to `grid`, as a GUI would to its own copy.
+    fn replay(grid: &mut SudokuGrid, events: impl Iterator<Item = GridEvent>)
+    {
+        for event in events {
+            match event {
+                GridEvent::CellSet { row, col, digit } => grid.set((row, col), digit).unwrap(),
+                GridEvent::CellCleared { row, col } => grid.clear((row, col)).unwrap(),
+                GridEvent::CandidateRemoved { row, col, digit } => {
+                    assert!(grid.remove_candidate((row, col), digit).unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solver_steps_fire_events()
+    {
+        let puzzle: SudokuGrid = concat!(
+            "..............3.85..1.2.......5.7.....4...1...9.......5......",
+            "73..2.1........4...9"
+        ).parse().unwrap();
+        let mut observed = ObservedGrid::new(puzzle.clone());
+        let events = observed.channel();
+        let solver = LogicalSolver::new();
+
+        let steps = observed.solve(&solver).unwrap();
+        let mut direct = puzzle.clone();
+        assert_eq!(steps, solver.solve(&mut direct).unwrap());
+        assert_eq!(observed.grid(), &direct);
+        assert!(observed.step(&solver).unwrap().is_none());
+
+        let mut replayed = puzzle;
+        replay(&mut replayed, events.try_iter());
+        assert_eq!(&replayed, observed.grid());
+    }
+
+    #[test]
+    fn test_apply_hint()
+    {
+        let mut grid = SudokuGrid::new();
+        for col in 1..=8 {
+            grid.set((1, col), col).unwrap();
+        }
+        let hint = grid.hint().unwrap();
+        let mut observed = ObservedGrid::new(grid.clone());
+        let events = observed.channel();
+        observed.apply(&hint).unwrap();
+
+        let received: Vec<GridEvent> = events.try_iter().collect();
+        assert_eq!(received[0], GridEvent::CellSet { row: 1, col: 9, digit: 9 });
+        assert_eq!(observed.grid().get((1, 9)), Ok(Some(9)));
+        replay(&mut grid, received.into_iter());
+        assert_eq!(&grid, observed.grid());
+    }
+
+    #[test]
+    fn test_dropped_channel_unsubscribes()
+    {
+        let mut observed = ObservedGrid::new(SudokuGrid::new());
+        drop(observed.channel());
+        observed.set((1, 1), 5).unwrap();
+        assert!(observed.listeners.is_empty());
+    }
+}
@@ -0,0 +1,503 @@
+//! Random puzzle generation.
+
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{ConstraintSet, SudokuGrid, SudokuError};
+use crate::rating::{Difficulty, rate};
+use crate::solver::{LogicalSolver, SolverConfig};
+use crate::techniques::Technique;
+use crate::solver::backtrack::{is_unique, random_solution};
+use crate::unavoidable::unavoidable_masks;
+
+
+/// Number of fresh solutions tried before giving up on a target.
+const MAX_ATTEMPTS: usize = 100;
+
+/// Number of fresh solutions tried against a clue pattern. Each costs
+/// only a uniqueness check, so many more can be afforded.
+const PATTERN_ATTEMPTS: usize = 2000;
+
+
+/// The symmetry kept by the clues of a generated puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Clues are unchanged by a half turn about the centre.
+    Rotational180,
+    /// Clues are mirrored in the main diagonal.
+    Diagonal,
+    /// Clues are unchanged by every rotation and reflection of the square.
+    FullDihedral
+}
+
+impl Symmetry {
+
+    /// The squares that must be cleared together with `index`.
+    pub(crate) fn orbit(self, index: usize) -> Vec<usize>
+    {
+        let (r, c) = (index / 9, index % 9);
+        let images = match self {
+            Symmetry::None => vec![(r, c)],
+            Symmetry::Rotational180 => vec![(r, c), (8 - r, 8 - c)],
+            Symmetry::Diagonal => vec![(r, c), (c, r)],
+            Symmetry::FullDihedral => vec![
+                (r, c), (c, 8 - r), (8 - r, 8 - c), (8 - c, r),
+                (c, r), (r, 8 - c), (8 - r, c), (8 - c, 8 - r)
+            ]
+        };
+        let mut orbit: Vec<usize> = images.into_iter().map(|(r, c)| 9*r + c).collect();
+        orbit.sort_unstable();
+        orbit.dedup();
+        orbit
+    }
+}
+
+
+fn grid_from_values(values: &[u8; 81]) -> SudokuGrid
+{
+    let mut grid = SudokuGrid::new();
+    for (i, &v) in values.iter().enumerate() {
+        if v != 0 {
+            grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, v);
+        }
+    }
+    grid
+}
+
+/// Remove clues from a random solution, keeping a removal only when the
+/// puzzle stays unique and `accept` agrees. Removals that would leave an
+/// unavoidable set without a clue are turned down without a search.
+fn generate_from<R, A>(rng: &mut R, symmetry: Symmetry, accept: &mut A) -> [u8; 81]
+where
+    R: Rng + ?Sized,
+    A: FnMut(&[u8; 81]) -> bool
+{
+    let mut values = random_solution(rng);
+    let sets = unavoidable_masks(&values, 2);
+    let mut clues: u128 = (1 << 81) - 1;
+
+    let mut order: Vec<usize> = (0..81).collect();
+    order.shuffle(rng);
+    for i in order {
+        let orbit = symmetry.orbit(i);
+        if orbit.iter().any(|&j| values[j] == 0) {
+            continue;
+        }
+
+        let removed = orbit.iter().fold(0u128, |m, &j| m | (1 << j));
+        if sets.iter().any(|&set| set & clues & !removed == 0) {
+            continue;
+        }
+
+        let saved = values;
+        for &j in orbit.iter() {
+            values[j] = 0;
+        }
+        if is_unique(&values) && accept(&values) {
+            clues &= !removed;
+        } else {
+            values = saved;
+        }
+    }
+
+    values
+}
+
+/// Generate a puzzle with a unique solution by filling a random grid and
+/// removing clues for as long as the solution stays unique.
+pub fn generate() -> SudokuGrid
+{
+    generate_with_symmetry(Symmetry::None)
+}
+
+/// Generate a puzzle whose clues keep the given symmetry. Clues are
+/// removed a whole orbit at a time.
+pub fn generate_with_symmetry(symmetry: Symmetry) -> SudokuGrid
+{
+    generate_with_rng(&mut rand::thread_rng(), symmetry)
+}
+
+/// Generate a puzzle drawing all randomness from `rng`, so the same
+/// generator state always gives the same puzzle.
+pub fn generate_with_rng<R: Rng + ?Sized>(rng: &mut R, symmetry: Symmetry) -> SudokuGrid
+{
+    grid_from_values(&generate_from(rng, symmetry, &mut |_| true))
+}
+
+/// Generate the puzzle belonging to `seed`. The same seed gives the same
+/// puzzle on every platform for a given version of the crate. The seed
+/// drives ChaCha8, whose output is fixed, rather than `StdRng`, which
+/// `rand` may change in any release.
+pub fn generate_seeded(seed: u64) -> SudokuGrid
+{
+    generate_with_rng(&mut ChaCha8Rng::seed_from_u64(seed), Symmetry::None)
+}
+
+/// The puzzle of the day and its grade, see `daily`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyPuzzle {
+    pub puzzle: SudokuGrid,
+    pub difficulty: Difficulty
+}
+
+/// Spread the bits of a date so that neighbouring days give unrelated
+/// seeds. This is the SplitMix64 finaliser, which unlike the standard
+/// library's hasher is fixed across platforms and compiler versions.
+fn date_seed(date: u64) -> u64
+{
+    let mut z = date.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// The puzzle for a day, however the caller numbers days: `20261015` and
+/// days since an epoch both work. Every client asking for the same date
+/// gets the same puzzle without talking to a server, as the date seeds
+/// ChaCha8 as in `generate_seeded`.
+pub fn daily<D: Into<u64>>(date: D) -> DailyPuzzle
+{
+    let puzzle = generate_with_rng(
+        &mut ChaCha8Rng::seed_from_u64(date_seed(date.into())),
+        Symmetry::Rotational180
+    );
+    let difficulty = rate(&puzzle).expect("Generated puzzles are consistent.");
+    DailyPuzzle { puzzle, difficulty }
+}
+
+/// Generate a puzzle that the rating engine grades at `difficulty`. Clues
+/// are only removed while the grade stays at or below the target, and a
+/// new solution is tried if the target isn't reached.
+pub fn generate_with_difficulty(difficulty: Difficulty) -> Result<SudokuGrid, SudokuError>
+{
+    generate_with_difficulty_rng(&mut rand::thread_rng(), difficulty)
+}
+
+/// Like `generate_with_difficulty`, drawing all randomness from `rng`.
+pub fn generate_with_difficulty_rng<R>(rng: &mut R, difficulty: Difficulty)
+    -> Result<SudokuGrid, SudokuError>
+where
+    R: Rng + ?Sized
+{
+    generate_rated(rng, difficulty, Symmetry::None)
+}
+
+/// Generate a puzzle graded at `difficulty` whose clues keep `symmetry`,
+/// drawing all randomness from `rng`. Symmetric puzzles have fewer ways to
+/// remove clues, so hard grades may take more attempts or fail.
+pub fn generate_rated<R>(rng: &mut R, difficulty: Difficulty, symmetry: Symmetry)
+    -> Result<SudokuGrid, SudokuError>
+where
+    R: Rng + ?Sized
+{
+    let mut accept = |values: &[u8; 81]| {
+        rate(&grid_from_values(values)).is_ok_and(|d| d <= difficulty)
+    };
+
+    for _ in 0..MAX_ATTEMPTS {
+        let grid = grid_from_values(&generate_from(rng, symmetry, &mut accept));
+        if rate(&grid)? == difficulty {
+            return Ok(grid);
+        }
+    }
+    Err(SudokuError::GenerationFailed)
+}
+
+/// Generate a puzzle that needs `technique`: the logical solver finishes
+/// it with `technique` and the easier techniques, but not with the easier
+/// ones alone, so the technique appears in every logical solve. Useful for
+/// practice sets. Rare techniques such as `Jellyfish` may take more than
+/// the available attempts and fail with `GenerationFailed`.
+pub fn generate_requiring(technique: Technique) -> Result<SudokuGrid, SudokuError>
+{
+    generate_requiring_rng(&mut rand::thread_rng(), technique)
+}
+
+/// Like `generate_requiring`, drawing all randomness from `rng`.
+pub fn generate_requiring_rng<R>(rng: &mut R, technique: Technique)
+    -> Result<SudokuGrid, SudokuError>
+where
+    R: Rng + ?Sized
+{
+    let easier: Vec<Technique> = Technique::ALL.iter().copied().filter(|&t| t < technique).collect();
+    let allowed = LogicalSolver::with_techniques(&[easier.as_slice(), &[technique]].concat());
+    let easier = LogicalSolver::with_techniques(&easier);
+    let solves = |solver: &LogicalSolver, grid: &SudokuGrid| {
+        let mut work = grid.clone();
+        solver.solve(&mut work).is_ok() && work.is_complete()
+    };
+
+    // Remove clues while the allowed techniques still finish the puzzle,
+    // then keep the result if the easier ones alone don't.
+    let mut accept = |values: &[u8; 81]| solves(&allowed, &grid_from_values(values));
+    for _ in 0..MAX_ATTEMPTS {
+        let grid = grid_from_values(&generate_from(rng, Symmetry::None, &mut accept));
+        if !solves(&easier, &grid) {
+            return Ok(grid);
+        }
+    }
+    Err(SudokuError::GenerationFailed)
+}
+
+/// A generated puzzle and how many clues it has, see
+/// `generate_with_clue_count`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountedPuzzle {
+    pub puzzle: SudokuGrid,
+    pub clues: usize
+}
+
+/// Generate a puzzle with `clues` givens, or as close above that as the
+/// attempts get. Clues are removed only while more than `clues` remain,
+/// and a new solution is tried until the count is hit. Counts from 23 to
+/// 81 are nearly always reached. Unique puzzles exist down to 17 clues
+/// but random removal seldom finds them below about 22, so check the
+/// reported count. Fails with `GenerationFailed` for counts outside 17 to
+/// 81.
+pub fn generate_with_clue_count(clues: usize) -> Result<CountedPuzzle, SudokuError>
+{
+    generate_with_clue_count_rng(&mut rand::thread_rng(), clues)
+}
+
+/// Like `generate_with_clue_count`, drawing all randomness from `rng`.
+pub fn generate_with_clue_count_rng<R>(rng: &mut R, clues: usize)
+    -> Result<CountedPuzzle, SudokuError>
+where
+    R: Rng + ?Sized
+{
+    if !(17..=81).contains(&clues) {
+        return Err(SudokuError::GenerationFailed);
+    }
+
+    let count = |values: &[u8; 81]| values.iter().filter(|&&v| v != 0).count();
+    let mut best: Option<[u8; 81]> = None;
+    for _ in 0..MAX_ATTEMPTS {
+        let values = generate_from(rng, Symmetry::None, &mut |values| count(values) >= clues);
+        if best.is_none_or(|best| count(&values) < count(&best)) {
+            best = Some(values);
+        }
+        if count(&values) == clues {
+            break;
+        }
+    }
+    let values = best.expect("At least one attempt is made.");
+    Ok(CountedPuzzle { puzzle: grid_from_values(&values), clues: count(&values) })
+}
+
+/// Generate a puzzle whose clues sit in exactly the squares set in
+/// `pattern`, in row-major order. Fails with `GenerationFailed` if no
+/// random solution gives a unique puzzle within the retry budget, and at
+/// once for patterns of fewer than 17 squares, which never do.
+pub fn generate_with_pattern(pattern: &[bool; 81]) -> Result<SudokuGrid, SudokuError>
+{
+    generate_with_pattern_rng(&mut rand::thread_rng(), pattern)
+}
+
+/// Like `generate_with_pattern`, drawing all randomness from `rng`.
+pub fn generate_with_pattern_rng<R>(rng: &mut R, pattern: &[bool; 81])
+    -> Result<SudokuGrid, SudokuError>
+where
+    R: Rng + ?Sized
+{
+    if pattern.iter().filter(|&&clue| clue).count() < 17 {
+        return Err(SudokuError::GenerationFailed);
+    }
+
+    // Most solutions leave some unavoidable set outside the pattern, and
+    // finding the small ones costs far less than a uniqueness check.
+    let clues = (0..81).filter(|&i| pattern[i]).fold(0u128, |m, i| m | (1 << i));
+    for _ in 0..PATTERN_ATTEMPTS {
+        let mut values = random_solution(rng);
+        if unavoidable_masks(&values, 2).iter().any(|&set| set & clues == 0) {
+            continue;
+        }
+        for (value, _) in values.iter_mut().zip(pattern.iter()).filter(|(_, &clue)| !clue) {
+            *value = 0;
+        }
+        if is_unique(&values) {
+            return Ok(grid_from_values(&values));
+        }
+    }
+    Err(SudokuError::GenerationFailed)
+}
+
+/// Generate a puzzle that has a unique solution under `constraints`, such
+/// as `ConstraintSet::sudoku_x()`, drawing all randomness from `rng`.
+/// Fails with `NoSolution` when no grid keeps to the constraints.
+pub fn generate_constrained<R>(rng: &mut R, constraints: &ConstraintSet, symmetry: Symmetry)
+    -> Result<SudokuGrid, SudokuError>
+where
+    R: Rng + ?Sized
+{
+    let config = SolverConfig::new().constraints(constraints.clone());
+    let mut grid = SudokuGrid::new().solve_with(&config.clone().seed(rng.gen()))?;
+
+    let mut order: Vec<usize> = (0..81).collect();
+    order.shuffle(rng);
+    for i in order {
+        let orbit = symmetry.orbit(i);
+        if orbit.iter().any(|&j| !grid.0[j].is_set()) {
+            continue;
+        }
+
+        let saved = grid.clone();
+        for &j in orbit.iter() {
+            grid.0[j].unset();
+        }
+        grid.sync_boards();
+        if grid.count_solutions_with(&config, 2)? != 1 {
+            grid = saved;
+        }
+    }
+    Ok(grid)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use crate::solver::backtrack::grid_values;
+
+    #[test]
+    fn test_generated_puzzle_is_unique()
+    {
+        let grid = generate();
+        let values = grid_values(&grid);
+        assert!(grid.has_unique_solution());
+        assert!(values.iter().filter(|&&v| v != 0).count() < 40);
+    }
+
+    #[test]
+    fn test_generate_easy()
+    {
+        let grid = generate_with_difficulty(Difficulty::Easy).unwrap();
+        assert_eq!(rate(&grid).unwrap(), Difficulty::Easy);
+    }
+
+    #[test]
+    fn test_seeded_generation_repeats()
+    {
+        let grid = generate_seeded(2024);
+        assert_eq!(grid, generate_seeded(2024));
+        assert_ne!(grid, generate_seeded(2025));
+        // Seeds must keep their puzzles across rand releases.
+        assert_eq!(
+            grid.to_line_string(),
+            "4...1..8.1......7...5.47.1..9....3.....5.6.9...23.4...52...3..7......5....8...9.."
+        );
+
+        let mut a = StdRng::seed_from_u64(5);
+        let mut b = StdRng::seed_from_u64(5);
+        assert_eq!(
+            generate_with_difficulty_rng(&mut a, Difficulty::Easy),
+            generate_with_difficulty_rng(&mut b, Difficulty::Easy)
+        );
+    }
+
+    #[test]
+    fn test_generate_with_pattern()
+    {
+        // The clue squares of a well known puzzle.
+        let pattern: [bool; 81] = std::array::from_fn(|i| {
+            concat!(
+                "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+                ".6....28....419..5....8..79"
+            ).as_bytes()[i] != b'.'
+        });
+        let grid = generate_with_pattern_rng(&mut StdRng::seed_from_u64(3), &pattern).unwrap();
+        assert!(grid.has_unique_solution());
+        assert!(grid.iter().zip(pattern.iter()).all(|(sq, &clue)| sq.is_set() == clue));
+
+        let sparse: [bool; 81] = std::array::from_fn(|i| i < 16);
+        assert_eq!(generate_with_pattern(&sparse), Err(SudokuError::GenerationFailed));
+    }
+
+    #[test]
+    fn test_generate_with_clue_count()
+    {
+        let mut rng = StdRng::seed_from_u64(11);
+        for &clues in [26, 40, 81].iter() {
+            let generated = generate_with_clue_count_rng(&mut rng, clues).unwrap();
+            assert_eq!(generated.clues, clues);
+            assert_eq!(generated.puzzle.filled_count(), clues);
+            assert!(generated.puzzle.has_unique_solution());
+        }
+        assert_eq!(generate_with_clue_count(16), Err(SudokuError::GenerationFailed));
+    }
+
+    #[test]
+    fn test_generate_requiring()
+    {
+        let mut rng = StdRng::seed_from_u64(8);
+        for &technique in [Technique::HiddenSingle, Technique::NakedPair, Technique::XWing].iter() {
+            let grid = generate_requiring_rng(&mut rng, technique).unwrap();
+            assert!(grid.has_unique_solution());
+
+            let mut work = grid.clone();
+            let steps = LogicalSolver::new().solve(&mut work).unwrap();
+            assert!(work.is_complete());
+            assert!(steps.iter().any(|step| step.technique == technique));
+            assert!(steps.iter().all(|step| step.technique <= technique));
+        }
+    }
+
+    #[test]
+    fn test_daily()
+    {
+        let today = daily(20261015u32);
+        assert_eq!(today, daily(20261015u64));
+        assert_ne!(today.puzzle, daily(20261016u32).puzzle);
+        assert!(today.puzzle.has_unique_solution());
+        assert_eq!(rate(&today.puzzle), Ok(today.difficulty));
+        assert_eq!(
+            today.puzzle.to_line_string(),
+            "....5.8.3.1...95.........7.6.749...1..3...9..4...786.2.4.........86...1.3.6.1...."
+        );
+    }
+
+    #[test]
+    fn test_generate_rated_keeps_symmetry()
+    {
+        let mut rng = StdRng::seed_from_u64(9);
+        let grid = generate_rated(&mut rng, Difficulty::Easy, Symmetry::Diagonal).unwrap();
+        assert_eq!(rate(&grid), Ok(Difficulty::Easy));
+        for i in 0..81 {
+            assert_eq!(grid.0[i].is_set(), grid.0[9*(i % 9) + i / 9].is_set());
+        }
+    }
+
+    #[test]
+    fn test_orbit_sizes()
+    {
+        assert_eq!(Symmetry::Rotational180.orbit(40), vec![40]);
+        assert_eq!(Symmetry::Rotational180.orbit(0), vec![0, 80]);
+        assert_eq!(Symmetry::Diagonal.orbit(1), vec![1, 9]);
+        assert_eq!(Symmetry::FullDihedral.orbit(1).len(), 8);
+        assert_eq!(Symmetry::FullDihedral.orbit(0), vec![0, 8, 72, 80]);
+    }
+
+    #[test]
+    fn test_generated_puzzle_is_symmetric()
+    {
+        let grid = generate_with_symmetry(Symmetry::Rotational180);
+        for i in 0..81 {
+            assert_eq!(grid.0[i].is_set(), grid.0[80 - i].is_set());
+        }
+    }
+
+    #[test]
+    fn test_generate_sudoku_x()
+    {
+        let constraints = ConstraintSet::sudoku_x();
+        let config = SolverConfig::new().constraints(constraints.clone());
+        let mut rng = StdRng::seed_from_u64(3);
+        let puzzle = generate_constrained(&mut rng, &constraints, Symmetry::Rotational180).unwrap();
+        assert_eq!(puzzle.count_solutions_with(&config, 2), Ok(1));
+        assert!(constraints.is_satisfied(&puzzle));
+        assert!(puzzle.solve_with(&config).unwrap().check_with(&constraints).unwrap());
+    }
+}
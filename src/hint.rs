@@ -0,0 +1,65 @@
+use crate::SudokuGrid;
+use crate::solver::LogicalSolver;
+use crate::techniques::{Elimination, Technique};
+
+
+/// The next logical move on a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub technique: Technique,
+    /// The square to fill and its digit as `(row, col, digit)`, for
+    /// techniques that place a digit.
+    pub placement: Option<(u8, u8, u8)>,
+    /// Candidates that the move removes.
+    pub eliminations: Vec<Elimination>,
+    /// The squares involved in the move.
+    pub cells: Vec<(u8, u8)>
+}
+
+
+impl SudokuGrid {
+
+    /// Find the easiest logical move from the grid's current state, or
+    /// `None` if the grid is full, inconsistent or no known technique
+    /// applies. The grid itself is not changed.
+    pub fn hint(&self) -> Option<Hint>
+    {
+        let mut work = self.clone();
+        work.eliminate_all().ok()?;
+        let step = LogicalSolver::new().next_step(&mut work).ok()??;
+
+        Some(Hint {
+            technique: step.technique,
+            placement: step.placements.first().copied(),
+            eliminations: step.eliminations,
+            cells: step.cells
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_finds_single()
+    {
+        let mut grid = SudokuGrid::new();
+        for col in 1..=8 {
+            grid.set((1, col), col).unwrap();
+        }
+
+        let hint = grid.hint().unwrap();
+        assert_eq!(hint.technique, Technique::NakedSingle);
+        assert_eq!(hint.placement, Some((1, 9, 9)));
+        assert_eq!(hint.cells, vec![(1, 9)]);
+        assert!(!grid.0[8].is_set());
+    }
+
+    #[test]
+    fn test_no_hint_for_empty_grid()
+    {
+        assert!(SudokuGrid::new().hint().is_none());
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+use std::convert::TryInto;
+
+use crate::{Pos, SudokuGrid, SudokuError, SudokuSquare};
+
+
+const DEFAULT_DEPTH: usize = 100;
+
+
+/// A grid together with an undo/redo journal of the edits made to it.
+/// Each entry keeps the squares from before and after the edit, and the
+/// oldest entries are dropped once `max_depth` is reached.
+pub struct GridHistory {
+    grid: SudokuGrid,
+    undo: VecDeque<([SudokuSquare; 81], [SudokuSquare; 81])>,
+    redo: Vec<([SudokuSquare; 81], [SudokuSquare; 81])>,
+    max_depth: usize
+}
+
+
+impl GridHistory {
+
+    pub fn new(grid: SudokuGrid) -> GridHistory
+    {
+        GridHistory::with_depth(grid, DEFAULT_DEPTH)
+    }
+
+    pub fn with_depth(grid: SudokuGrid, max_depth: usize) -> GridHistory
+    {
+        GridHistory { grid, undo: VecDeque::new(), redo: Vec::new(), max_depth }
+    }
+
+    pub fn grid(&self) -> &SudokuGrid
+    {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> SudokuGrid
+    {
+        self.grid
+    }
+
+    /// Apply an edit to the grid and record it. A failed edit leaves the
+    /// grid as it was and is not recorded. Recording clears the redo list.
+    pub fn edit<F>(&mut self, f: F) -> Result<(), SudokuError>
+    where
+        F: FnOnce(&mut SudokuGrid) -> Result<(), SudokuError>
+    {
+        let before = self.grid.0;
+        if let Err(err) = f(&mut self.grid) {
+            self.grid.0 = before;
+            self.grid.sync_boards();
+            return Err(err);
+        }
+
+        if self.max_depth > 0 {
+            if self.undo.len() == self.max_depth {
+                self.undo.pop_front();
+            }
+            self.undo.push_back((before, self.grid.0));
+        }
+        self.redo.clear();
+        Ok(())
+    }
+
+    pub fn set<P>(&mut self, pos: P, digit: u8) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        self.edit(|grid| grid.set::<Pos, u8>(pos, digit))
+    }
+
+    pub fn clear<P>(&mut self, pos: P) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        self.edit(|grid| grid.clear::<Pos>(pos))
+    }
+
+    pub fn remove_candidate<P>(&mut self, pos: P, digit: u8) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        self.edit(|grid| grid.remove_candidate::<Pos, u8>(pos, digit).map(|_| ()))
+    }
+
+    pub fn can_undo(&self) -> bool
+    {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool
+    {
+        !self.redo.is_empty()
+    }
+
+    /// Revert the last edit, returning false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool
+    {
+        match self.undo.pop_back() {
+            Some(entry) => {
+                self.grid.0 = entry.0;
+                self.grid.sync_boards();
+                self.redo.push(entry);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Reapply the last undone edit, returning false if there was none.
+    pub fn redo(&mut self) -> bool
+    {
+        match self.redo.pop() {
+            Some(entry) => {
+                self.grid.0 = entry.1;
+                self.grid.sync_boards();
+                self.undo.push_back(entry);
+                true
+            },
+            None => false
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo()
+    {
+        let mut history = GridHistory::new(SudokuGrid::new());
+        history.set((1, 1), 5).unwrap();
+        history.remove_candidate((1, 2), 3).unwrap();
+        assert!(!history.grid().0[1].is_possible(3));
+
+        assert!(history.undo());
+        assert!(history.grid().0[1].is_possible(3));
+        assert!(history.undo());
+        assert!(!history.grid().0[0].is_set());
+        assert!(!history.undo());
+
+        assert!(history.redo());
+        assert_eq!(history.grid().0[0].get().unwrap(), 5);
+        history.clear((1, 1)).unwrap();
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_failed_edit_is_not_recorded()
+    {
+        let mut history = GridHistory::new(SudokuGrid::new());
+        assert!(history.set((1, 1), 10).is_err());
+        assert!(history.remove_candidate((0, 1), 1).is_err());
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_depth_is_bounded()
+    {
+        let mut history = GridHistory::with_depth(SudokuGrid::new(), 2);
+        for col in 1..=4 {
+            history.set((1, col), col).unwrap();
+        }
+        assert!(history.undo());
+        assert!(history.undo());
+        assert!(!history.undo());
+        assert!(history.grid().0[1].is_set());
+        assert!(!history.grid().0[2].is_set());
+    }
+}
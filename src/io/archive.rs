@@ -0,0 +1,266 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use crate::{SudokuGrid, SudokuError};
+use crate::rating::{Difficulty, rate};
+
+
+/// A file holding one puzzle per line, possibly millions of them. Puzzles
+/// are read lazily and appended in place, so the file is never held in
+/// memory. Each line starts with the puzzle's 81 characters; anything
+/// after the first whitespace is ignored, and blank lines and lines
+/// starting with `#` are skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleArchive {
+    path: PathBuf
+}
+
+
+impl PuzzleArchive {
+
+    /// An archive at `path`. The file is only opened when it is read or
+    /// written, and appending creates it.
+    pub fn new<P: AsRef<Path>>(path: P) -> PuzzleArchive
+    {
+        PuzzleArchive { path: path.as_ref().to_path_buf() }
+    }
+
+    pub fn path(&self) -> &Path
+    {
+        &self.path
+    }
+
+    /// Stream the puzzles from the start of the file.
+    pub fn puzzles(&self) -> Result<ArchiveReader<BufReader<File>>, SudokuError>
+    {
+        Ok(ArchiveReader::new(File::open(&self.path)?))
+    }
+
+    /// Stream the puzzles that pass `filter`. Lines that fail to parse
+    /// are still reported.
+    pub fn filtered(&self, filter: PuzzleFilter) -> Result<ArchiveReader<BufReader<File>>, SudokuError>
+    {
+        Ok(self.puzzles()?.with_filter(filter))
+    }
+
+    /// Open the file for appending puzzles to its end, creating it if
+    /// needed.
+    pub fn append(&self) -> Result<ArchiveWriter<BufWriter<File>>, SudokuError>
+    {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        Ok(ArchiveWriter::new(BufWriter::new(file)))
+    }
+
+    /// Append every puzzle in `grids`, returning how many were written.
+    pub fn append_all<'a, I>(&self, grids: I) -> Result<usize, SudokuError>
+    where
+        I: IntoIterator<Item = &'a SudokuGrid>
+    {
+        let mut writer = self.append()?;
+        for grid in grids {
+            writer.write(grid)?;
+        }
+        writer.flush()?;
+        Ok(writer.written())
+    }
+}
+
+
+/// Which puzzles an `ArchiveReader` passes on. Clue counts are checked
+/// first, since rating a puzzle takes a logical solve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleFilter {
+    clues: RangeInclusive<usize>,
+    difficulty: Option<RangeInclusive<Difficulty>>
+}
+
+
+impl Default for PuzzleFilter {
+
+    fn default() -> PuzzleFilter
+    {
+        PuzzleFilter::new()
+    }
+}
+
+
+impl PuzzleFilter {
+
+    /// A filter that passes every puzzle.
+    pub fn new() -> PuzzleFilter
+    {
+        PuzzleFilter { clues: 0..=81, difficulty: None }
+    }
+
+    /// Pass only puzzles with a clue count in `clues`.
+    pub fn clues(mut self, clues: RangeInclusive<usize>) -> PuzzleFilter
+    {
+        self.clues = clues;
+        self
+    }
+
+    /// Pass only puzzles rated within `difficulty`. Puzzles the rater
+    /// can't grade don't pass.
+    pub fn difficulty(mut self, difficulty: RangeInclusive<Difficulty>) -> PuzzleFilter
+    {
+        self.difficulty = Some(difficulty);
+        self
+    }
+
+    pub fn matches(&self, grid: &SudokuGrid) -> bool
+    {
+        if !self.clues.contains(&grid.filled_count()) {
+            return false;
+        }
+        match &self.difficulty {
+            Some(range) => rate(grid).is_ok_and(|d| range.contains(&d)),
+            None => true
+        }
+    }
+}
+
+
+/// Lazily reads the puzzles of an archive, one line at a time.
+pub struct ArchiveReader<R> {
+    lines: Lines<R>,
+    filter: PuzzleFilter
+}
+
+
+impl<R: Read> ArchiveReader<BufReader<R>> {
+
+    /// Read archive lines from any source, such as standard input.
+    pub fn new(reader: R) -> ArchiveReader<BufReader<R>>
+    {
+        ArchiveReader { lines: BufReader::new(reader).lines(), filter: PuzzleFilter::new() }
+    }
+}
+
+
+impl<R: BufRead> ArchiveReader<R> {
+
+    pub fn with_filter(mut self, filter: PuzzleFilter) -> ArchiveReader<R>
+    {
+        self.filter = filter;
+        self
+    }
+}
+
+
+impl<R: BufRead> Iterator for ArchiveReader<R> {
+    type Item = Result<SudokuGrid, SudokuError>;
+
+    fn next(&mut self) -> Option<Result<SudokuGrid, SudokuError>>
+    {
+        for line in self.lines.by_ref() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into()))
+            };
+            let puzzle = match line.split_whitespace().next() {
+                Some(puzzle) if !puzzle.starts_with('#') => puzzle,
+                _ => continue
+            };
+            match puzzle.parse::<SudokuGrid>() {
+                Ok(grid) if !self.filter.matches(&grid) => continue,
+                result => return Some(result)
+            }
+        }
+        None
+    }
+}
+
+
+/// Writes puzzles to an archive, one line each with `.` for empty
+/// squares.
+pub struct ArchiveWriter<W: Write> {
+    writer: W,
+    written: usize
+}
+
+
+impl<W: Write> ArchiveWriter<W> {
+
+    pub fn new(writer: W) -> ArchiveWriter<W>
+    {
+        ArchiveWriter { writer, written: 0 }
+    }
+
+    pub fn write(&mut self, grid: &SudokuGrid) -> Result<(), SudokuError>
+    {
+        writeln!(self.writer, "{}", grid.to_line_string())?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// The number of puzzles written through this writer.
+    pub fn written(&self) -> usize
+    {
+        self.written
+    }
+
+    pub fn flush(&mut self) -> Result<(), SudokuError>
+    {
+        Ok(self.writer.flush()?)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASY: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const HARD: &str = "..............3.85..1.2.......5.7.....4...1...9.......5......73..2.1........4...9";
+
+    #[test]
+    fn test_reader_skips_comments_and_trailing_fields()
+    {
+        let text = format!("# collection\n{} easy\n\n{}\nbad line\n", EASY, HARD);
+        let results: Vec<_> = ArchiveReader::new(text.as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], EASY.parse());
+        assert_eq!(results[1], HARD.parse());
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_filter()
+    {
+        let text = format!("{}\n{}\n", EASY, HARD);
+        let sparse: Vec<_> = ArchiveReader::new(text.as_bytes())
+            .with_filter(PuzzleFilter::new().clues(17..=25))
+            .collect();
+        assert_eq!(sparse, vec![HARD.parse()]);
+
+        // Both need nothing beyond singles.
+        let easy = ArchiveReader::new(text.as_bytes())
+            .with_filter(PuzzleFilter::new().difficulty(Difficulty::Easy..=Difficulty::Easy));
+        assert_eq!(easy.count(), 2);
+        let hard = ArchiveReader::new(text.as_bytes())
+            .with_filter(PuzzleFilter::new().clues(17..=25).difficulty(Difficulty::Hard..=Difficulty::Diabolical));
+        assert_eq!(hard.count(), 0);
+    }
+
+    #[test]
+    fn test_append_and_read_back()
+    {
+        let path = std::env::temp_dir().join(format!("bitsudoku-archive-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let archive = PuzzleArchive::new(&path);
+        let easy: SudokuGrid = EASY.parse().unwrap();
+        let hard: SudokuGrid = HARD.parse().unwrap();
+
+        assert_eq!(archive.append_all(std::slice::from_ref(&easy)).unwrap(), 1);
+        assert_eq!(archive.append_all(&[hard.clone(), easy.clone()]).unwrap(), 2);
+        let read: Vec<SudokuGrid> = archive.puzzles().unwrap().map(Result::unwrap).collect();
+        assert_eq!(read, vec![easy.clone(), hard, easy]);
+
+        let filtered = archive.filtered(PuzzleFilter::new().clues(30..=81)).unwrap().count();
+        assert_eq!(filtered, 2);
+        std::fs::remove_file(&path).unwrap();
+        assert!(archive.puzzles().is_err());
+    }
+}
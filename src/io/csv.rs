@@ -0,0 +1,212 @@
+use std::io::{Read, Write};
+
+use ::csv::{ReaderBuilder, StringRecord, Trim, Writer};
+
+use crate::{SudokuGrid, SudokuError};
+use crate::rating::{Difficulty, rate};
+
+
+/// The columns that go with a puzzle in a CSV file. Empty cells and
+/// missing columns are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PuzzleMeta {
+    pub solution: Option<SudokuGrid>,
+    pub clues: Option<usize>,
+    pub difficulty: Option<Difficulty>,
+    /// Any other columns, as `(header, value)` in file order.
+    pub extra: Vec<(String, String)>
+}
+
+
+impl PuzzleMeta {
+
+    /// Solve, count and rate `puzzle`, leaving out what can't be found,
+    /// such as the solution of a puzzle that has none.
+    pub fn for_puzzle(puzzle: &SudokuGrid) -> PuzzleMeta
+    {
+        PuzzleMeta {
+            solution: puzzle.solve().ok(),
+            clues: Some(puzzle.filled_count()),
+            difficulty: rate(puzzle).ok(),
+            extra: Vec::new()
+        }
+    }
+}
+
+
+impl From<::csv::Error> for SudokuError {
+    fn from(err: ::csv::Error) -> SudokuError
+    {
+        let line = err.position().map_or(0, |pos| pos.line());
+        match err.into_kind() {
+            ::csv::ErrorKind::Io(err) => err.into(),
+            _ => SudokuError::InvalidRecord { line }
+        }
+    }
+}
+
+
+const HEADERS: [&str; 4] = ["puzzle", "solution", "clues", "difficulty"];
+
+
+/// Where each known column sits in a record.
+struct Columns {
+    puzzle: usize,
+    solution: Option<usize>,
+    clues: Option<usize>,
+    difficulty: Option<usize>,
+    extra: Vec<(usize, String)>
+}
+
+
+impl Columns {
+
+    fn from_headers(headers: &StringRecord) -> Option<Columns>
+    {
+        let find = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+        let columns = Columns {
+            puzzle: find("puzzle")?,
+            solution: find("solution"),
+            clues: find("clues"),
+            difficulty: find("difficulty"),
+            extra: Vec::new()
+        };
+        let known = [Some(columns.puzzle), columns.solution, columns.clues, columns.difficulty];
+        let extra = headers.iter().enumerate()
+            .filter(|(i, _)| !known.contains(&Some(*i)))
+            .map(|(i, h)| (i, h.to_string()))
+            .collect();
+        Some(Columns { extra, ..columns })
+    }
+
+    fn read(&self, record: &StringRecord) -> Result<(SudokuGrid, PuzzleMeta), SudokuError>
+    {
+        let line = record.position().map_or(0, |pos| pos.line());
+        let invalid = SudokuError::InvalidRecord { line };
+        let cell = |column: Option<usize>| column.and_then(|i| record.get(i)).filter(|v| !v.is_empty());
+
+        let puzzle = cell(Some(self.puzzle)).ok_or(invalid)?.parse()?;
+        let meta = PuzzleMeta {
+            solution: cell(self.solution).map(str::parse).transpose()?,
+            clues: cell(self.clues).map(|v| v.parse().map_err(|_| invalid)).transpose()?,
+            difficulty: cell(self.difficulty).map(|v| Difficulty::from_name(v).ok_or(invalid)).transpose()?,
+            extra: self.extra.iter()
+                .map(|(i, h)| (h.clone(), record.get(*i).unwrap_or("").to_string()))
+                .collect()
+        };
+        Ok((puzzle, meta))
+    }
+}
+
+
+/// Stream `(puzzle, meta)` pairs from a CSV file with a header row. A
+/// `puzzle` column is required; `solution`, `clues` and `difficulty` are
+/// read when present, matched without regard to case, and every other
+/// column is kept in `extra`. Fails at once with `InvalidRecord` if the
+/// header has no `puzzle` column.
+pub fn read_csv<R: Read>(reader: R)
+    -> Result<impl Iterator<Item = Result<(SudokuGrid, PuzzleMeta), SudokuError>>, SudokuError>
+{
+    let mut reader = ReaderBuilder::new().trim(Trim::All).from_reader(reader);
+    let columns = Columns::from_headers(reader.headers()?)
+        .ok_or(SudokuError::InvalidRecord { line: 1 })?;
+    Ok(reader.into_records().map(move |record| columns.read(&record?)))
+}
+
+/// Write `(puzzle, meta)` pairs as CSV under a `puzzle,solution,clues,
+/// difficulty` header, using `.` for empty squares. The extra columns are
+/// those of the first pair; later pairs fill them by header name.
+pub fn write_csv<'a, W, I>(writer: W, rows: I) -> Result<(), SudokuError>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a (SudokuGrid, PuzzleMeta)>
+{
+    let mut writer = Writer::from_writer(writer);
+    let mut extra: Option<Vec<String>> = None;
+    for (puzzle, meta) in rows {
+        if extra.is_none() {
+            let names: Vec<String> = meta.extra.iter().map(|(h, _)| h.clone()).collect();
+            writer.write_record(HEADERS.iter().copied().chain(names.iter().map(String::as_str)))?;
+            extra = Some(names);
+        }
+
+        let known = [
+            puzzle.to_line_string(),
+            meta.solution.as_ref().map_or(String::new(), SudokuGrid::to_line_string),
+            meta.clues.map_or(String::new(), |n| n.to_string()),
+            meta.difficulty.map_or(String::new(), |d| d.to_string())
+        ];
+        let others = extra.iter().flatten().map(|name| {
+            meta.extra.iter().find(|(h, _)| h == name).map_or("", |(_, v)| v.as_str())
+        });
+        writer.write_record(known.iter().map(String::as_str).chain(others))?;
+    }
+    if extra.is_none() {
+        writer.write_record(HEADERS.iter())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASY: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    #[test]
+    fn test_read_columns()
+    {
+        let text = format!(
+            "Source,Puzzle,Difficulty,Clues\n\"Times, 3 May\",{},easy,30\nbook,{},,\n",
+            EASY, "0".repeat(81)
+        );
+        let rows: Vec<_> = read_csv(text.as_bytes()).unwrap().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, EASY.parse().unwrap());
+        assert_eq!(rows[0].1.difficulty, Some(Difficulty::Easy));
+        assert_eq!(rows[0].1.clues, Some(30));
+        assert_eq!(rows[0].1.solution, None);
+        assert_eq!(rows[0].1.extra, vec![("Source".to_string(), "Times, 3 May".to_string())]);
+        assert_eq!(rows[1].0.filled_count(), 0);
+        assert_eq!(rows[1].1.difficulty, None);
+    }
+
+    #[test]
+    fn test_read_errors()
+    {
+        assert_eq!(read_csv("grid\n".as_bytes()).err(), Some(SudokuError::InvalidRecord { line: 1 }));
+
+        let text = format!("puzzle,difficulty\n{},tricky\n{},\n12345,\n", EASY, EASY);
+        let rows: Vec<_> = read_csv(text.as_bytes()).unwrap().collect();
+        assert_eq!(rows[0], Err(SudokuError::InvalidRecord { line: 2 }));
+        assert!(rows[1].is_ok());
+        assert_eq!(rows[2], Err(SudokuError::InvalidLength { length: 5 }));
+    }
+
+    #[test]
+    fn test_round_trip()
+    {
+        let puzzle: SudokuGrid = EASY.parse().unwrap();
+        let mut meta = PuzzleMeta::for_puzzle(&puzzle);
+        assert!(meta.solution.as_ref().is_some_and(SudokuGrid::is_solved));
+        assert_eq!(meta.clues, Some(30));
+        meta.extra.push(("source".to_string(), "wikipedia".to_string()));
+        let rows = vec![(puzzle.clone(), meta), (puzzle, PuzzleMeta::default())];
+
+        let mut out = Vec::new();
+        write_csv(&mut out, &rows).unwrap();
+        let text = String::from_utf8(out.clone()).unwrap();
+        assert!(text.starts_with("puzzle,solution,clues,difficulty,source\n"));
+        assert!(text.ends_with(&format!("{},,,,\n", EASY)));
+
+        let back: Vec<_> = read_csv(out.as_slice()).unwrap().map(Result::unwrap).collect();
+        assert_eq!(back[0], rows[0]);
+        assert_eq!(back[1].1.extra, vec![("source".to_string(), String::new())]);
+
+        let mut empty = Vec::new();
+        write_csv(&mut empty, &[]).unwrap();
+        assert_eq!(empty, b"puzzle,solution,clues,difficulty\n");
+    }
+}
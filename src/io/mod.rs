@@ -0,0 +1,15 @@
+//! Reading and writing puzzle files.
+
+mod archive;
+#[cfg(feature = "csv")]
+mod csv;
+mod pencilmark;
+mod sdm;
+mod ss;
+
+pub use archive::{ArchiveReader, ArchiveWriter, PuzzleArchive, PuzzleFilter};
+#[cfg(feature = "csv")]
+pub use self::csv::{PuzzleMeta, read_csv, write_csv};
+pub use pencilmark::{read_pencilmarks, write_pencilmarks};
+pub use sdm::{read_sdm, write_sdm};
+pub use ss::{read_ss, write_ss};
@@ -0,0 +1,152 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::{SudokuGrid, SudokuError};
+
+
+/// Read a grid in the pencilmark format used by HoDoKu and SudoCue, where
+/// every square lists its remaining candidates. A square with a single
+/// digit is taken as set, and `-` marks a square with no candidates left.
+/// Border lines and the `|` between boxes are ignored.
+pub fn read_pencilmarks<R: Read>(reader: R) -> Result<SudokuGrid, SudokuError>
+{
+    let mut tokens = Vec::with_capacity(81);
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(['.', ':', '\'', '+', '*']) {
+            continue;
+        }
+        tokens.extend(line.split(|ch: char| ch == '|' || ch.is_whitespace())
+            .filter(|token| !token.is_empty())
+            .map(str::to_string));
+    }
+    if tokens.len() != 81 {
+        return Err(SudokuError::InvalidLength { length: tokens.len() });
+    }
+
+    let mut grid = SudokuGrid::new();
+    for (position, token) in tokens.iter().enumerate() {
+        let mut mask = 0u16;
+        for character in token.chars().filter(|&ch| ch != '-') {
+            match character {
+                '1'..='9' => mask |= 0x0001 << (character as u8 - b'1'),
+                _ => return Err(SudokuError::InvalidCharacter { position, character })
+            }
+        }
+
+        let (row, col) = ((position / 9 + 1) as u8, (position % 9 + 1) as u8);
+        if token.len() == 1 && mask != 0 {
+            grid.set_unchecked(row, col, mask.trailing_zeros() as u8 + 1);
+            continue;
+        }
+        let sq = &mut grid.0[position];
+        for digit in (1..=9).filter(|&d| mask & (0x0001 << (d - 1)) == 0) {
+            sq.remove_possibility_unchecked(digit);
+        }
+    }
+    Ok(grid)
+}
+
+/// Write a grid in the pencilmark format, with set squares as their digit
+/// and empty squares as their candidates. Candidates are written as the
+/// grid holds them, so call `propagate` first on a freshly loaded puzzle.
+pub fn write_pencilmarks<W: Write>(mut writer: W, grid: &SudokuGrid) -> Result<(), SudokuError>
+{
+    let cells: Vec<String> = grid.iter().map(|sq| match sq.get() {
+        Ok(digit) => digit.to_string(),
+        Err(_) if sq.possibilities_number() == 0 => "-".to_string(),
+        Err(_) => (1..=9).filter(|&d| sq.is_possible(d)).map(|d| (b'0' + d) as char).collect()
+    }).collect();
+    let widths: Vec<usize> = (0..9)
+        .map(|c| (0..9).map(|r| cells[9*r + c].len()).max().unwrap_or(1))
+        .collect();
+
+    // Each box is a space, three padded cells two spaces apart and a space.
+    let stacks: Vec<usize> = widths.chunks(3).map(|w| w.iter().sum::<usize>() + 6).collect();
+    let border = |ends: char, joint: char| {
+        let dashes: Vec<String> = stacks.iter().map(|&n| "-".repeat(n)).collect();
+        format!("{}{}{}", ends, dashes.join(&joint.to_string()), ends)
+    };
+
+    writeln!(writer, "{}", border('.', '.'))?;
+    for r in 0..9 {
+        if r == 3 || r == 6 {
+            writeln!(writer, "{}", border(':', '+'))?;
+        }
+        let mut line = String::from("|");
+        for stack in 0..3 {
+            let padded: Vec<String> = (3*stack..3*stack + 3)
+                .map(|c| format!("{:<width$}", cells[9*r + c], width = widths[c]))
+                .collect();
+            line.push_str(&format!(" {} |", padded.join("  ")));
+        }
+        writeln!(writer, "{}", line)?;
+    }
+    let mut bottom = border('\'', '\'');
+    bottom.push('\n');
+    writer.write_all(bottom.as_bytes())?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle() -> SudokuGrid
+    {
+        concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap()
+    }
+
+    #[test]
+    fn test_write_layout()
+    {
+        let mut grid = puzzle();
+        grid.eliminate_all().unwrap();
+        let mut out = Vec::new();
+        write_pencilmarks(&mut out, &grid).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(lines.len(), 13);
+        assert!(lines[0].starts_with(".-") && lines[4].starts_with(":-") && lines[12].starts_with("'-"));
+        assert!(lines[1].starts_with("| 5 "));
+        assert!(lines[1].contains(" 124 "));
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+    }
+
+    #[test]
+    fn test_round_trip_keeps_eliminations()
+    {
+        let mut grid = puzzle();
+        grid.eliminate_all().unwrap();
+        grid.0[2].remove_possibility(4).unwrap();
+
+        let mut out = Vec::new();
+        write_pencilmarks(&mut out, &grid).unwrap();
+        let back = read_pencilmarks(out.as_slice()).unwrap();
+        assert!(back.iter().zip(grid.iter()).all(|(a, b)| a.digit_bits() == b.digit_bits()));
+
+        // Squares down to one candidate come back set.
+        let singles = grid.iter().filter(|sq| !sq.is_set() && sq.possibilities_number() == 1).count();
+        assert!(singles > 0);
+        assert_eq!(back.filled_count(), grid.filled_count() + singles);
+    }
+
+    #[test]
+    fn test_read_errors()
+    {
+        assert_eq!(
+            read_pencilmarks("| 12 3 |".as_bytes()),
+            Err(SudokuError::InvalidLength { length: 2 })
+        );
+        let text = format!("{} 1x", "1 ".repeat(80));
+        assert_eq!(
+            read_pencilmarks(text.as_bytes()),
+            Err(SudokuError::InvalidCharacter { position: 80, character: 'x' })
+        );
+    }
+}
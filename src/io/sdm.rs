@@ -0,0 +1,63 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::{SudokuGrid, SudokuError};
+
+
+/// Stream the puzzles of a SudoCue `.sdm` collection, one 81 character
+/// puzzle per line. Blank lines are skipped.
+pub fn read_sdm<R: Read>(reader: R) -> impl Iterator<Item = Result<SudokuGrid, SudokuError>>
+{
+    BufReader::new(reader).lines().filter_map(|line| match line {
+        Ok(line) if line.trim().is_empty() => None,
+        Ok(line) => Some(line.parse()),
+        Err(err) => Some(Err(err.into()))
+    })
+}
+
+/// Write puzzles as a `.sdm` collection, using `0` for empty squares.
+pub fn write_sdm<'a, W, I>(mut writer: W, grids: I) -> Result<(), SudokuError>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a SudokuGrid>
+{
+    for grid in grids {
+        writeln!(writer, "{}", grid.to_line_string_with('0'))?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sdm_round_trip()
+    {
+        let mut first = SudokuGrid::new();
+        first.set((1, 1), 4).unwrap();
+        let mut second = SudokuGrid::new();
+        second.set((9, 9), 7).unwrap();
+
+        let mut out = Vec::new();
+        write_sdm(&mut out, &[first, second]).unwrap();
+        let text = String::from_utf8(out.clone()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.starts_with("4000"));
+
+        let grids: Vec<SudokuGrid> = read_sdm(&out[..]).map(Result::unwrap).collect();
+        assert_eq!(grids.len(), 2);
+        assert_eq!(grids[0].0[0].get().unwrap(), 4);
+        assert_eq!(grids[1].0[80].get().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_sdm_reports_bad_lines()
+    {
+        let text = format!("{}\n\nnot a puzzle\n", "0".repeat(81));
+        let results: Vec<_> = read_sdm(text.as_bytes()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::{SudokuGrid, SudokuError};
+
+
+/// Read a puzzle in the Simple Sudoku `.ss` format: nine rows with `|`
+/// between the boxes, dashed lines between the bands and `.` or `X` for
+/// empty squares.
+pub fn read_ss<R: Read>(reader: R) -> Result<SudokuGrid, SudokuError>
+{
+    let mut cells = String::with_capacity(81);
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('-') {
+            continue;
+        }
+        for ch in line.chars().filter(|&ch| ch != '|' && !ch.is_whitespace()) {
+            cells.push(match ch {
+                'X' | 'x' => '.',
+                ch => ch
+            });
+        }
+    }
+    cells.parse()
+}
+
+/// Write a puzzle in the Simple Sudoku `.ss` format.
+pub fn write_ss<W: Write>(mut writer: W, grid: &SudokuGrid) -> Result<(), SudokuError>
+{
+    let line = grid.to_line_string();
+    for (r, row) in line.as_bytes().chunks(9).enumerate() {
+        if r == 3 || r == 6 {
+            writeln!(writer, "-----------")?;
+        }
+        let row = std::str::from_utf8(row).unwrap();
+        writeln!(writer, "{}|{}|{}", &row[0..3], &row[3..6], &row[6..9])?;
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "\
+53.|.7.|...
+6..|195|...
+.98|...|.6.
+-----------
+8..|.6.|..3
+4..|8.3|..1
+7..|.2.|..6
+-----------
+.6.|...|28.
+...|419|..5
+...|.8.|.79
+";
+
+    #[test]
+    fn test_ss_round_trip()
+    {
+        let grid = read_ss(PUZZLE.as_bytes()).unwrap();
+        assert_eq!(grid.0[0].get().unwrap(), 5);
+        assert!(!grid.0[2].is_set());
+
+        let mut out = Vec::new();
+        write_ss(&mut out, &grid).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), PUZZLE);
+    }
+
+    #[test]
+    fn test_ss_accepts_x_for_blanks()
+    {
+        let text = PUZZLE.replace('.', "X");
+        let grid = read_ss(text.as_bytes()).unwrap();
+        assert!(!grid.0[2].is_set());
+        assert!(read_ss("123|456|789\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,356 @@
+//! Counting and listing the grids isomorphic to a given one.
+//!
+//! The symmetries of sudoku are the 3,359,232 geometric maps, an optional
+//! transpose followed by orderings of the rows and columns that keep
+//! bands and stacks together, each combined with the 9! relabelings of
+//! the digits. Different symmetries give the same grid when the grid has
+//! automorphisms, or when some digits are missing and relabeling them
+//! changes nothing, so isomorphs are counted and listed without repeats.
+
+use rand::Rng;
+
+use crate::SudokuGrid;
+use crate::canonical::line_orders;
+use crate::solver::backtrack::grid_values;
+
+
+/// The number of line orderings that keep groups of three together.
+const ORDERS: usize = 1296;
+
+/// The number of geometric maps.
+const GEOMETRIES: usize = 2*ORDERS*ORDERS;
+
+
+/// A transpose and line orderings. Square `(r, c)` of the result comes
+/// from square `(rows[r], cols[c])` of the grid, transposed first if
+/// `transpose` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Geometry {
+    transpose: bool,
+    rows: [usize; 9],
+    cols: [usize; 9]
+}
+
+
+impl Geometry {
+
+    fn from_index(index: usize, orders: &[[usize; 9]]) -> Geometry
+    {
+        Geometry {
+            transpose: index >= ORDERS*ORDERS,
+            rows: orders[index / ORDERS % ORDERS],
+            cols: orders[index % ORDERS]
+        }
+    }
+
+    /// The position of the geometry in the order `from_index` uses.
+    fn index(&self) -> usize
+    {
+        usize::from(self.transpose)*ORDERS*ORDERS
+            + order_index(&self.rows)*ORDERS + order_index(&self.cols)
+    }
+
+    /// The square each square of the result comes from.
+    fn sources(&self) -> [usize; 81]
+    {
+        std::array::from_fn(|i| {
+            let (r, c) = (self.rows[i / 9], self.cols[i % 9]);
+            if self.transpose { 9*c + r } else { 9*r + c }
+        })
+    }
+
+    /// `other` followed by this geometry.
+    fn after(&self, other: &Geometry) -> Geometry
+    {
+        let (rows, cols) = if self.transpose {
+            (&other.cols, &other.rows)
+        } else {
+            (&other.rows, &other.cols)
+        };
+        Geometry {
+            transpose: self.transpose != other.transpose,
+            rows: std::array::from_fn(|r| rows[self.rows[r]]),
+            cols: std::array::from_fn(|c| cols[self.cols[c]])
+        }
+    }
+
+    fn apply(&self, values: &[u8; 81]) -> [u8; 81]
+    {
+        let sources = self.sources();
+        std::array::from_fn(|i| values[sources[i]])
+    }
+}
+
+
+/// The position of a line ordering in `line_orders`.
+fn order_index(order: &[usize; 9]) -> usize
+{
+    // The position of a permutation of three in `PERMS3`.
+    let perm = |a: usize, b: usize, c: usize| 2*a + usize::from(b > c);
+    let outer = perm(order[0] / 3, order[3] / 3, order[6] / 3);
+    let inner = |g: usize| perm(order[3*g] % 3, order[3*g + 1] % 3, order[3*g + 2] % 3);
+    ((outer*6 + inner(0))*6 + inner(1))*6 + inner(2)
+}
+
+
+/// The geometric maps taking the set squares of `values` onto themselves
+/// up to a relabeling of the digits, the identity among them.
+fn automorphisms(values: &[u8; 81], orders: &[[usize; 9]]) -> Vec<Geometry>
+{
+    let transposed: [u8; 81] = std::array::from_fn(|i| values[9*(i % 9) + i / 9]);
+    let mut found = Vec::new();
+    for (transpose, source) in [(false, values), (true, &transposed)].iter() {
+        for cols in orders.iter() {
+            let mut search = Search {
+                target: values,
+                source,
+                cols,
+                rows: [0; 9],
+                found: Vec::new()
+            };
+            search.run(0, [0; 10], [0; 10], 0, 0);
+            found.extend(search.found.into_iter().map(|rows| Geometry { transpose: *transpose, rows, cols: *cols }));
+        }
+    }
+    found
+}
+
+
+struct Search<'a> {
+    target: &'a [u8; 81],
+    source: &'a [u8; 81],
+    cols: &'a [usize; 9],
+    rows: [usize; 9],
+    found: Vec<[usize; 9]>
+}
+
+
+impl<'a> Search<'a> {
+
+    /// Choose the source rows for slots `slot..9`, keeping a relabeling
+    /// that takes the chosen rows onto the target's, and the bands whole.
+    fn run(&mut self, slot: usize, labels: [u8; 10], inverse: [u8; 10], used: u16, band: usize)
+    {
+        if slot == 9 {
+            self.found.push(self.rows);
+            return;
+        }
+
+        let candidates: Vec<usize> = if slot.is_multiple_of(3) {
+            (0..9).filter(|&r| used & (0x0007 << (3*(r / 3))) == 0).collect()
+        } else {
+            (3*band..3*band + 3).filter(|&r| used & (0x0001 << r) == 0).collect()
+        };
+
+        'rows: for row in candidates {
+            let (mut labels, mut inverse) = (labels, inverse);
+            for c in 0..9 {
+                let from = usize::from(self.source[9*row + self.cols[c]]);
+                let to = usize::from(self.target[9*slot + c]);
+                if (from == 0) != (to == 0) {
+                    continue 'rows;
+                }
+                if from == 0 {
+                    continue;
+                }
+                if labels[from] == 0 && inverse[to] == 0 {
+                    labels[from] = to as u8;
+                    inverse[to] = from as u8;
+                } else if usize::from(labels[from]) != to {
+                    continue 'rows;
+                }
+            }
+            self.rows[slot] = row;
+            self.run(slot + 1, labels, inverse, used | (0x0001 << row), row / 3);
+        }
+    }
+}
+
+
+/// The digits present in `values`, in increasing order.
+fn present_digits(values: &[u8; 81]) -> Vec<u8>
+{
+    (1..=9).filter(|d| values.contains(d)).collect()
+}
+
+
+/// Step `digits` to the next arrangement of its first `k` entries in
+/// lexicographic order, keeping the rest increasing. Returns false after
+/// the last one.
+fn next_arrangement(digits: &mut [u8; 9], k: usize) -> bool
+{
+    digits[k..].reverse();
+    let i = match (1..9).rev().find(|&i| digits[i - 1] < digits[i]) {
+        Some(i) => i - 1,
+        None => return false
+    };
+    let j = (i + 1..9).rev().find(|&j| digits[j] > digits[i]).unwrap();
+    digits.swap(i, j);
+    digits[i + 1..].reverse();
+    true
+}
+
+
+/// Every grid isomorphic to a given one, each once, from
+/// `SudokuGrid::isomorphs`.
+pub struct Isomorphs {
+    orders: Vec<[usize; 9]>,
+    automorphisms: Vec<Geometry>,
+    present: Vec<u8>,
+    values: [u8; 81],
+    /// The geometric maps already covered, one bit each.
+    seen: Vec<u64>,
+    next: usize,
+    /// The current geometric image and the relabeling to apply to it.
+    current: Option<([u8; 81], [u8; 9])>
+}
+
+
+impl Isomorphs {
+
+    fn new(values: [u8; 81]) -> Isomorphs
+    {
+        let orders = line_orders();
+        Isomorphs {
+            automorphisms: automorphisms(&values, &orders),
+            orders,
+            present: present_digits(&values),
+            values,
+            seen: vec![0; GEOMETRIES / 64 + 1],
+            next: 0,
+            current: None
+        }
+    }
+}
+
+
+impl Iterator for Isomorphs {
+    type Item = SudokuGrid;
+
+    fn next(&mut self) -> Option<SudokuGrid>
+    {
+        loop {
+            if let Some((image, digits)) = self.current.as_mut() {
+                let mut labels = [0u8; 10];
+                for (&from, &to) in self.present.iter().zip(digits.iter()) {
+                    labels[usize::from(from)] = to;
+                }
+                let mut grid = SudokuGrid::new();
+                for (i, &v) in image.iter().enumerate().filter(|(_, &v)| v != 0) {
+                    grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, labels[usize::from(v)]);
+                }
+                if !next_arrangement(digits, self.present.len()) {
+                    self.current = None;
+                }
+                return Some(grid);
+            }
+
+            // Take the next geometric map not reached from an earlier one
+            // through an automorphism, and mark the ones it reaches.
+            let index = (self.next..GEOMETRIES).find(|&g| self.seen[g / 64] & (1 << (g % 64)) == 0)?;
+            let geometry = Geometry::from_index(index, &self.orders);
+            for automorphism in self.automorphisms.iter() {
+                let g = geometry.after(automorphism).index();
+                self.seen[g / 64] |= 1 << (g % 64);
+            }
+            self.next = index + 1;
+            self.current = Some((geometry.apply(&self.values), [1, 2, 3, 4, 5, 6, 7, 8, 9]));
+        }
+    }
+}
+
+
+impl SudokuGrid {
+
+    /// How many different grids are isomorphic to this one, itself
+    /// included, taking only the set squares into account. A solution
+    /// with no automorphisms has 1,218,998,108,160.
+    pub fn isomorph_count(&self) -> u64
+    {
+        let values = grid_values(self);
+        let automorphisms = automorphisms(&values, &line_orders()).len() as u64;
+        let labelings: u64 = (10 - present_digits(&values).len() as u64..=9).product();
+        GEOMETRIES as u64 / automorphisms*labelings
+    }
+
+    /// Every grid isomorphic to this one, each exactly once and itself
+    /// first, with the set squares only. There are usually over a
+    /// trillion, so take what you need. Finding the grid's automorphisms
+    /// up front is slow for grids with few clues.
+    pub fn isomorphs(&self) -> Isomorphs
+    {
+        Isomorphs::new(grid_values(self))
+    }
+
+    /// A grid drawn uniformly from those isomorphic to this one, by
+    /// applying a symmetry drawn uniformly with `scramble`.
+    pub fn random_isomorph<R: Rng + ?Sized>(&self, rng: &mut R) -> SudokuGrid
+    {
+        self.scramble(rng)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn solution() -> SudokuGrid
+    {
+        crate::sudoku_grid!(concat!(
+            "985423716134679582627815394376942851519786243",
+            "842351967493567128258134679761298435"
+        ))
+    }
+
+    #[test]
+    fn test_geometry_indices()
+    {
+        let orders = line_orders();
+        for &index in [0, 1, 1295, 1296, 777_777, GEOMETRIES / 2, GEOMETRIES - 1].iter() {
+            let geometry = Geometry::from_index(index, &orders);
+            assert_eq!(geometry.index(), index);
+        }
+
+        let (a, b) = (Geometry::from_index(2_000_000, &orders), Geometry::from_index(31_337, &orders));
+        let values = grid_values(&solution());
+        assert_eq!(a.after(&b).apply(&values), a.apply(&b.apply(&values)));
+    }
+
+    #[test]
+    fn test_counts()
+    {
+        assert_eq!(solution().isomorph_count(), 1_218_998_108_160);
+
+        let mut grid = SudokuGrid::new();
+        grid.set((5, 5), 3).unwrap();
+        assert_eq!(grid.isomorph_count(), 81*9);
+    }
+
+    #[test]
+    fn test_isomorphs_are_distinct()
+    {
+        let grid = solution();
+        let isomorphs: Vec<SudokuGrid> = grid.isomorphs().take(2000).collect();
+        assert_eq!(isomorphs[0], grid);
+        assert!(isomorphs.iter().all(SudokuGrid::is_solved));
+        assert!(isomorphs.iter().step_by(400).all(|g| g.is_isomorphic_to(&grid)));
+        assert_eq!(isomorphs.iter().collect::<HashSet<_>>().len(), 2000);
+
+        let mut grid = SudokuGrid::new();
+        grid.set((5, 5), 3).unwrap();
+        let isomorphs: HashSet<SudokuGrid> = grid.isomorphs().collect();
+        assert_eq!(isomorphs.len(), 81*9);
+        assert!(isomorphs.iter().all(|g| g.filled_count() == 1));
+    }
+
+    #[test]
+    fn test_random_isomorph()
+    {
+        let grid = solution();
+        let other = grid.random_isomorph(&mut rand::thread_rng());
+        assert!(other.is_isomorphic_to(&grid));
+    }
+}
@@ -1,68 +1,377 @@
-use std::clone::Clone;
-use std::marker::Copy;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::str::FromStr;
 
 
+/// Build a grid from `(row, col), digit` pairs, from 81 values in
+/// row-major order where `0` or `_` leaves a square empty, or from an 81
+/// character puzzle string. A malformed string is a compile error.
+///
+/// ```
+/// use bitsudoku::sudoku_grid;
+///
+/// let grid = sudoku_grid!(concat!(
+///     "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+///     ".6....28....419..5....8..79"
+/// ));
+/// assert_eq!(grid.filled_count(), 30);
+/// ```
+///
+/// ```compile_fail
+/// let grid = bitsudoku::sudoku_grid!("53..7....6..195");
+/// ```
 #[macro_export]
 macro_rules! sudoku_grid {
-    ($(($x:expr, $y:expr), $v:expr),*) => {{
-        let mut grid = SudokuGrid::new();
-        $(
-            grid.set($x, $y, $v);
-        )*
+    (@values [$($acc:expr),*] _ $(, $($rest:tt)*)?) => {
+        $crate::sudoku_grid!(@values [$($acc,)* 0] $($($rest)*)?)
+    };
+    (@values [$($acc:expr),*] $v:expr $(, $($rest:tt)*)?) => {
+        $crate::sudoku_grid!(@values [$($acc,)* $v] $($($rest)*)?)
+    };
+    (@values [$($acc:expr),*]) => {{
+        let mut grid = $crate::SudokuGrid::new();
+        let values: &[u8] = &[$($acc),*];
+        if values.len() > 81 {
+            panic!("Too many values in grid.");
+        }
+        for (i, &value) in values.iter().enumerate().filter(|&(_, &v)| v != 0) {
+            grid.set(((i / 9 + 1) as u8, (i % 9 + 1) as u8), value).expect("Invalid value in grid.");
+        }
         grid
     }};
-    ($($v:expr),*) => {{
-        let mut grid = SudokuGrid::new();
-        let mut row = 1;
-        let mut col = 1;
+    (concat!($($s:literal),+ $(,)?)) => {
+        $crate::sudoku_grid!(@str concat!($($s),+))
+    };
+    ($s:literal) => {
+        $crate::sudoku_grid!(@str $s)
+    };
+    (@str $s:expr) => {{
+        const _: () = assert!(
+            $crate::is_puzzle_literal($s),
+            "A puzzle string needs 81 characters, each a digit or `.`."
+        );
+        $s.parse::<$crate::SudokuGrid>().expect("Checked puzzle string.")
+    }};
+    ($(($x:expr, $y:expr), $v:expr),*) => {{
+        let mut grid = $crate::SudokuGrid::new();
         $(
-            if row == 10 {
-                panic!("Too many values grid.");
-            }
-
-            grid.set(row, col, $v);
-            if col == 9 {
-                col = 0;
-                row += 1
-            } 
-            col += 1;
+            grid.set(($x, $y), $v).expect("Invalid square in grid.");
         )*
         grid
     }};
+    ($($v:tt)*) => {
+        $crate::sudoku_grid!(@values [] $($v)*)
+    };
+}
+
+#[cfg(feature = "macros")]
+extern crate self as bitsudoku;
+
+#[cfg(feature = "macros")]
+pub use bitsudoku_macros::sudoku_grid_checked;
+
+/// Whether `s` is 81 ASCII digits and `.`, for `sudoku_grid!` to check
+/// string literals at compile time.
+#[doc(hidden)]
+pub const fn is_puzzle_literal(s: &str) -> bool
+{
+    let bytes = s.as_bytes();
+    if bytes.len() != 81 {
+        return false;
+    }
+    let mut i = 0;
+    while i < 81 {
+        if !bytes[i].is_ascii_digit() && bytes[i] != b'.' {
+            return false;
+        }
+        i += 1;
+    }
+    true
 }
 
+mod bitboard;
+mod canonical;
+mod check;
+mod digit;
+mod encoding;
+mod error;
+#[cfg(feature = "events")]
+mod events;
+mod hint;
+mod history;
+mod isomorph;
+mod pos;
+mod snapshot;
 mod square;
+mod transform;
+mod unavoidable;
+mod unit;
+pub mod constraint;
+pub mod generator;
+pub mod io;
+#[cfg(feature = "python")]
+mod python;
+pub mod rating;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod sized;
+pub mod solver;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod tables;
+pub mod techniques;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+pub use check::CheckReport;
+pub use constraint::{Constraint, ConstraintSet};
+pub use digit::Digit;
+pub use error::SudokuError;
+#[cfg(feature = "events")]
+pub use events::{GridEvent, ObservedGrid};
+pub use hint::Hint;
+pub use history::GridHistory;
+pub use isomorph::Isomorphs;
+pub use pos::Pos;
+pub use snapshot::GridState;
 pub use square::{SudokuSquare};
+pub use unavoidable::UnavoidableSet;
+pub use unit::Unit;
+pub use rating::{Difficulty, rate};
 
-#[derive(Debug, Clone, Copy)]
-pub enum SudokuError {
-    NonUniqueSet,
-    IsAlreadySet,
-    NotSet,
-    InvalidDigit { digit: u16 },
-    InvalidPosition { row: u8, col: u8 }
-}
+
+/// The `(row, col)` of a square, both numbered from 1.
+pub type Position = (u8, u8);
 
 
-pub struct SudokuGrid([SudokuSquare; 81]);
+#[derive(Debug, Clone)]
+pub struct SudokuGrid([SudokuSquare; 81], bitboard::DigitBoards);
 
 
 impl Default for SudokuGrid {
 
     fn default() -> SudokuGrid
     {
-        let mut inner = [SudokuSquare::default(); 81];
-        for r in 0..=8 {
-            for c in 0..=8 {
-                inner[9*r + c].set_position((r+1) as u8, (c+1) as u8);
+        SudokuGrid::new()
+    }
+
+}
+
+
+impl PartialEq for SudokuGrid {
+
+    fn eq(&self, other: &SudokuGrid) -> bool
+    {
+        self.0 == other.0
+    }
+}
+
+
+impl Eq for SudokuGrid {}
+
+
+impl Hash for SudokuGrid {
+
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.0.hash(state);
+    }
+}
+
+
+impl<'a> IntoIterator for &'a SudokuGrid {
+    type Item = &'a SudokuSquare;
+    type IntoIter = std::slice::Iter<'a, SudokuSquare>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.iter()
+    }
+}
+
+
+impl<'a> IntoIterator for &'a mut SudokuGrid {
+    type Item = &'a mut SudokuSquare;
+    type IntoIter = std::slice::IterMut<'a, SudokuSquare>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.iter_mut()
+    }
+}
+
+
+impl fmt::Display for SudokuGrid {
+
+    /// Draw the grid with box separators, using `.` for empty squares.
+    /// The alternate form, `{:#}`, draws the candidates instead.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        const SEPARATOR: &str = "+-------+-------+-------+";
+
+        if f.alternate() {
+            return draw_candidates(self, f);
+        }
+
+        for (r, row) in self.0.chunks(9).enumerate() {
+            if r % 3 == 0 {
+                writeln!(f, "{}", SEPARATOR)?;
+            }
+            for (c, sq) in row.iter().enumerate() {
+                if c % 3 == 0 {
+                    f.write_str("| ")?;
+                }
+                match sq.get() {
+                    Ok(digit) => write!(f, "{} ", digit)?,
+                    Err(_) => f.write_str(". ")?
+                }
+            }
+            writeln!(f, "|")?;
+        }
+        write!(f, "{}", SEPARATOR)
+    }
+}
+
+
+/// Draw each square as a 3x3 block with its candidates in place and `.`
+/// for those eliminated. A set square shows only its digit, in the middle
+/// of an otherwise blank block.
+fn draw_candidates(grid: &SudokuGrid, f: &mut fmt::Formatter) -> fmt::Result
+{
+    const SEPARATOR: &str = "+-------------+-------------+-------------+";
+    const GAP: &str = "|             |             |             |";
+
+    for (r, row) in grid.0.chunks(9).enumerate() {
+        if r % 3 == 0 {
+            writeln!(f, "{}", SEPARATOR)?;
+        } else {
+            writeln!(f, "{}", GAP)?;
+        }
+        for line in 0..3u8 {
+            for (c, sq) in row.iter().enumerate() {
+                f.write_str(match c {
+                    0 => "| ",
+                    3 | 6 => " | ",
+                    _ => " "
+                })?;
+                for digit in 3*line + 1..=3*line + 3 {
+                    let mark = match sq.get() {
+                        Ok(value) if digit == 5 => (b'0' + value) as char,
+                        Ok(_) => ' ',
+                        Err(_) if sq.is_possible(digit) => (b'0' + digit) as char,
+                        Err(_) => '.'
+                    };
+                    write!(f, "{}", mark)?;
+                }
             }
+            writeln!(f, " |")?;
+        }
+    }
+    write!(f, "{}", SEPARATOR)
+}
+
+
+impl FromStr for SudokuGrid {
+    type Err = SudokuError;
+
+    /// Parse an 81 character puzzle string in row-major order, where `0`,
+    /// `.` or `-` mark an empty square. Surrounding whitespace is ignored.
+    fn from_str(s: &str) -> Result<SudokuGrid, SudokuError>
+    {
+        let s = s.trim();
+        let length = s.chars().count();
+        if length != 81 {
+            return Err(SudokuError::InvalidLength { length });
+        }
+
+        let mut grid = SudokuGrid::new();
+        for (position, character) in s.chars().enumerate() {
+            match character {
+                '0' | '.' | '-' => {},
+                '1'..='9' => {
+                    let row = (position / 9 + 1) as u8;
+                    let col = (position % 9 + 1) as u8;
+                    grid.set_unchecked(row, col, character as u8 - b'0');
+                },
+                _ => return Err(SudokuError::InvalidCharacter { position, character })
+            }
+        }
+        Ok(grid)
+    }
+}
+
+
+impl FromIterator<(u8, u8, u8)> for SudokuGrid {
+
+    /// Build a grid from `(row, col, digit)` entries. Panics on an entry
+    /// that `set_all` would refuse; call that to handle the error instead.
+    fn from_iter<I: IntoIterator<Item = (u8, u8, u8)>>(entries: I) -> SudokuGrid
+    {
+        let mut grid = SudokuGrid::new();
+        if let Err(err) = grid.set_all(entries) {
+            panic!("Invalid entry in grid: {}.", err);
         }
+        grid
+    }
+}
 
-        SudokuGrid(inner)
 
+/// Convert `pos` for the grid methods that panic on a bad position.
+fn expect_pos<P>(pos: P) -> Pos
+where
+    P: TryInto<Pos>,
+    SudokuError: From<P::Error>
+{
+    match pos.try_into().map_err(SudokuError::from) {
+        Ok(pos) => pos,
+        Err(SudokuError::InvalidPosition { row, col }) => panic!("Invalid position ({}, {}).", row, col),
+        Err(err) => panic!("Invalid position: {}.", err)
     }
+}
+
+
+impl std::ops::Index<Pos> for SudokuGrid {
+    type Output = SudokuSquare;
 
+    fn index(&self, pos: Pos) -> &SudokuSquare
+    {
+        &self.0[pos.index()]
+    }
+}
+
+
+impl TryFrom<[[u8; 9]; 9]> for SudokuGrid {
+    type Error = SudokuError;
+
+    /// Build a grid from rows of digits, with 0 for an empty square.
+    fn try_from(rows: [[u8; 9]; 9]) -> Result<SudokuGrid, SudokuError>
+    {
+        SudokuGrid::try_from(rows.map(|row| row.map(|digit| Some(digit).filter(|&d| d != 0))))
+    }
+}
+
+
+impl TryFrom<[[Option<u8>; 9]; 9]> for SudokuGrid {
+    type Error = SudokuError;
+
+    /// Build a grid from rows of digits, with `None` for an empty square.
+    /// `Some(0)` is an invalid digit rather than a blank.
+    fn try_from(rows: [[Option<u8>; 9]; 9]) -> Result<SudokuGrid, SudokuError>
+    {
+        let mut grid = SudokuGrid::new();
+        for (row, values) in (1..=9).zip(rows.iter()) {
+            for (col, value) in (1..=9).zip(values.iter()) {
+                if let Some(digit) = *value {
+                    grid.set((row, col), digit)?;
+                }
+            }
+        }
+        Ok(grid)
+    }
 }
 
 
@@ -76,58 +385,565 @@ impl SudokuGrid {
                 inner[9*r + c].set_position((r+1) as u8, (c+1) as u8);
             }
         }
-        SudokuGrid(inner)
+        SudokuGrid(inner, bitboard::DigitBoards::default())
+    }
+
+    /// Build a grid from 81 values in row-major order, with 0 for an empty
+    /// square, as `sudoku_grid!` does for literals. Fails as `set_all`
+    /// does on a value above 9 or a digit repeated in a unit.
+    pub fn from_values(values: &[u8; 81]) -> Result<SudokuGrid, SudokuError>
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set_all(values.iter().enumerate()
+            .filter(|&(_, &v)| v != 0)
+            .map(|(i, &v)| ((i / 9 + 1) as u8, (i % 9 + 1) as u8, v)))?;
+        Ok(grid)
+    }
+
+    /// Set the square at `pos` to `digit`. The position is a `Pos` or a
+    /// `(row, col)` pair numbered from 1, and the digit a `Digit` or a
+    /// `u8`; both are checked.
+    pub fn set<P, D>(&mut self, pos: P, digit: D) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>,
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let digit: Digit = digit.try_into()?;
+        if self.0[pos.index()].is_given() {
+            return Err(SudokuError::IsGiven { row: pos.row(), col: pos.col() });
+        }
+        self.set_unchecked(pos.row(), pos.col(), digit.get());
+        Ok(())
+    }
+
+    /// Set every `(row, col, digit)` in turn, for loading puzzles from any
+    /// source. Fails on the first entry that is out of range, names a
+    /// square already listed, repeats a digit in a unit or would change a
+    /// given clue, and then leaves the grid as it was.
+    pub fn set_all<I>(&mut self, entries: I) -> Result<(), SudokuError>
+    where
+        I: IntoIterator<Item = (u8, u8, u8)>
+    {
+        let mut work = self.clone();
+        let mut listed = [false; 81];
+        for (row, col, digit) in entries {
+            let index = Pos::try_from((row, col))?.index();
+            if digit == 0 || digit > 9 {
+                return Err(SudokuError::InvalidEntry { row, col, digit });
+            }
+            if listed[index] {
+                return Err(SudokuError::DuplicateSquare { row, col });
+            }
+            listed[index] = true;
+            if work.peers((row, col)).iter().any(|sq| sq.is(digit)) {
+                return Err(SudokuError::DuplicateDigit { row, col, digit });
+            }
+            work.set((row, col), digit)?;
+        }
+        *self = work;
+        Ok(())
+    }
+
+    /// The digit at `pos` as a `Digit`, or `None` if the square is empty.
+    pub fn get_digit<P>(&self, pos: P) -> Result<Option<Digit>, SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        Ok(self.get(pos)?.and_then(Digit::new))
+    }
+
+    /// Set a square and mark it as a given clue, which `set` and `clear`
+    /// will then refuse to change.
+    pub fn set_given<P, D>(&mut self, pos: P, digit: D) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>,
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let index = pos.index();
+        self.set(pos, digit)?;
+        self.0[index].set_given()
+    }
+
+    /// Mark every set square as a given clue, for a grid that has just
+    /// been loaded from a puzzle.
+    pub fn mark_givens(&mut self)
+    {
+        for sq in self.0.iter_mut().filter(|sq| sq.is_set()) {
+            let _ = sq.set_given();
+        }
+    }
+
+    /// Set a square and remove `digit` from the candidates of its 20
+    /// peers. Squares left with one candidate are not filled; use
+    /// `propagate` for that.
+    pub fn set_and_propagate<P, D>(&mut self, pos: P, digit: D) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>,
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let index = pos.index();
+        self.set(pos, digit)?;
+        let digit = self.0[index].get()?;
+        self.eliminate_from_peers(index, digit);
+        Ok(())
+    }
+
+    /// Set a square without validating the arguments. Out of range values
+    /// panic or leave the square in a meaningless state.
+    pub fn set_unchecked(&mut self, row: u8, col: u8, digit: u8)
+    {
+        let index = Pos::new_unchecked(row, col).index();
+        self.refresh_boards();
+        self.0[index].set_value_unchecked(digit);
+        self.1.remove(index);
+        self.1.insert(index, digit);
+    }
+
+    /// Whether the grid is complete and every unit holds each digit once.
+    /// Use `check_detailed` to find out which units fail, and `check_with`
+    /// with `ConstraintSet::sudoku_x()` to check the diagonals as well.
+    pub fn check(&self) -> Result<bool, SudokuError>
+    {
+        Ok(self.is_solved())
+    }
+
+    /// Like `check`, under the given rules rather than plain rows, columns
+    /// and boxes; pass `ConstraintSet::sudoku_x()` to include diagonals.
+    pub fn check_with(&self, constraints: &ConstraintSet) -> Result<bool, SudokuError>
+    {
+        Ok(constraints.check(self))
+    }
+
+    /// Eliminate set digits from the candidates of their peers and fill
+    /// any square that is left with a single candidate, repeating until
+    /// nothing changes. Returns the number of squares that were filled.
+    pub fn propagate(&mut self) -> Result<usize, SudokuError>
+    {
+        self.refresh_boards();
+        self.eliminate_all()?;
+
+        let mut placed = 0;
+        while let Some(i) = self.0.iter().position(
+            |sq| !sq.is_set() && sq.possibilities_number() == 1
+        ) {
+            let digit = self.0[i].set()?;
+            self.1.insert(i, digit);
+            self.eliminate_from_peers(i, digit);
+            placed += 1;
+        }
+
+        match self.0.iter().find(|sq| !sq.is_set() && sq.possibilities_number() == 0) {
+            Some(sq) => Err(SudokuError::NoCandidates { row: sq.row(), col: sq.col() }),
+            None => Ok(placed)
+        }
+    }
+
+    /// Print the candidates of every square to standard output, as the
+    /// alternate `Display` form draws them.
+    pub fn print_candidates(&self)
+    {
+        println!("{:#}", self);
+    }
+
+    /// The grid as 81 characters in row-major order with `.` for empty
+    /// squares, the form `from_str` reads.
+    pub fn to_line_string(&self) -> String
+    {
+        self.to_line_string_with('.')
+    }
+
+    /// Like `to_line_string`, writing `blank` for empty squares; `'0'`
+    /// suits tools that expect digits only.
+    pub fn to_line_string_with(&self, blank: char) -> String
+    {
+        self.0.iter().map(|sq| match sq.get() {
+            Ok(digit) => (b'0' + digit) as char,
+            Err(_) => blank
+        }).collect()
+    }
+
+    /// Whether the set squares admit exactly one solution. Squares that
+    /// are not set count as blank regardless of their candidates.
+    pub fn has_unique_solution(&self) -> bool
+    {
+        self.count_solutions(2) == 1
+    }
+
+    /// Count the solutions of the grid, stopping once `limit` have been
+    /// found. Grids whose set squares already clash have no solutions.
+    pub fn count_solutions(&self, limit: usize) -> usize
+    {
+        solver::backtrack::Search::from_grid(self).map_or(0, |mut s| s.count(limit))
+    }
+
+    /// Find a solution by backtracking. When there is more than one, the
+    /// first found is returned.
+    pub fn solve(&self) -> Result<SudokuGrid, SudokuError>
+    {
+        self.solutions().next().ok_or(SudokuError::NoSolution)
+    }
+
+    /// Lazily iterate over every solution of the grid. Take two to test
+    /// uniqueness; an under-constrained grid may have a great many.
+    pub fn solutions(&self) -> solver::Solutions
+    {
+        solver::Solutions::new(self)
+    }
+
+    /// Whether the puzzle is unique and every clue is needed, so that
+    /// removing any one of them leaves more than one solution.
+    pub fn is_minimal(&self) -> bool
+    {
+        let mut values = solver::backtrack::grid_values(self);
+        if !solver::backtrack::is_unique(&values) {
+            return false;
+        }
+        (0..81).all(|i| {
+            let digit = values[i];
+            if digit == 0 {
+                return true;
+            }
+            values[i] = 0;
+            let needed = !solver::backtrack::is_unique(&values);
+            values[i] = digit;
+            needed
+        })
+    }
+
+    /// The clues that can each be removed on its own with the solution
+    /// staying unique, in row-major order. When any squares are marked as
+    /// given, only those are clues and other set squares are treated as
+    /// blank; otherwise every set square is. Removing one may make others
+    /// necessary, so this is not a set to clear all at once; `minimize`
+    /// does that. A puzzle without a unique solution has none.
+    pub fn redundant_clues(&self) -> Vec<Pos>
+    {
+        let mut values = solver::backtrack::grid_values(self);
+        if self.0.iter().any(SudokuSquare::is_given) {
+            for (value, sq) in values.iter_mut().zip(self.0.iter()) {
+                if !sq.is_given() {
+                    *value = 0;
+                }
+            }
+        }
+        if !solver::backtrack::is_unique(&values) {
+            return Vec::new();
+        }
+        let mut redundant = Vec::new();
+        for i in 0..81 {
+            let digit = values[i];
+            if digit == 0 {
+                continue;
+            }
+            values[i] = 0;
+            if solver::backtrack::is_unique(&values) {
+                redundant.push(Pos::from_index(i));
+            }
+            values[i] = digit;
+        }
+        redundant
+    }
+
+    /// Clear clues that aren't needed for a unique solution, returning how
+    /// many were removed. Clues are tried in row-major order.
+    pub fn minimize(&mut self) -> Result<usize, SudokuError>
+    {
+        let mut values = solver::backtrack::grid_values(self);
+        if !solver::backtrack::is_unique(&values) {
+            return Err(SudokuError::NoUniqueSolution);
+        }
+
+        self.refresh_boards();
+        let mut removed = 0;
+        for i in 0..81 {
+            let digit = values[i];
+            if digit == 0 {
+                continue;
+            }
+            values[i] = 0;
+            if solver::backtrack::is_unique(&values) {
+                self.0[i].unset();
+                self.1.remove(i);
+                removed += 1;
+            } else {
+                values[i] = digit;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// The empty squares, fewest candidates first and in row-major order
+    /// among equals. This is the usual order in which to try guesses.
+    pub fn empty_cells_by_candidates(&self) -> std::vec::IntoIter<&SudokuSquare>
+    {
+        let mut squares: Vec<&SudokuSquare> = self.0.iter().filter(|sq| !sq.is_set()).collect();
+        squares.sort_by_key(|sq| sq.possibilities_number());
+        squares.into_iter()
+    }
+
+    /// Find digits that can only go in one square of some row, column or
+    /// box, returned as `(row, col, digit)` placements. Candidates are read
+    /// as they are, so call `propagate` first to bring them up to date.
+    pub fn find_hidden_singles(&self) -> Vec<(u8, u8, u8)>
+    {
+        let mut singles = Vec::new();
+        for unit in unit_indices() {
+            for digit in 1..=9 {
+                if unit.iter().any(|&i| self.0[i].is(digit)) {
+                    continue;
+                }
+
+                let mut places = unit.iter().filter(
+                    |&&i| !self.0[i].is_set() && self.0[i].is_possible(digit)
+                );
+                if let (Some(&i), None) = (places.next(), places.next()) {
+                    let single = (self.0[i].row(), self.0[i].col(), digit);
+                    if !singles.contains(&single) {
+                        singles.push(single);
+                    }
+                }
+            }
+        }
+        singles
+    }
+
+    /// Erase the square at `pos`, restoring all of its candidates. The
+    /// candidates of its peers are left alone.
+    pub fn clear<P>(&mut self, pos: P) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let index = pos.index();
+        if self.0[index].is_given() {
+            return Err(SudokuError::IsGiven { row: pos.row(), col: pos.col() });
+        }
+        self.refresh_boards();
+        self.0[index].unset();
+        self.1.remove(index);
+        Ok(())
+    }
+
+    /// Remove `digit` from the candidates of the square at `pos`. Returns
+    /// whether it was a candidate; set squares are left alone.
+    pub fn remove_candidate<P, D>(&mut self, pos: P, digit: D) -> Result<bool, SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>,
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let index = pos.index();
+        let digit: Digit = digit.try_into()?;
+        self.refresh_boards();
+        let sq = &mut self.0[index];
+        if sq.is_set() || !sq.is_possible(digit) {
+            return Ok(false);
+        }
+        sq.remove_possibility_unchecked(digit.get());
+        Ok(true)
+    }
+
+    /// Erase a square and recompute the candidates of it and its empty
+    /// peers from the set squares. Eliminations made by techniques in
+    /// those squares are lost.
+    pub fn clear_and_recompute<P>(&mut self, pos: P) -> Result<(), SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        let index = pos.index();
+        self.clear::<Pos>(pos)?;
+        let boards = self.boards();
+        for &i in tables::PEERS[index].iter().chain(std::iter::once(&(index as u8))) {
+            let i = usize::from(i);
+            if self.0[i].is_set() {
+                continue;
+            }
+            let taken = boards.seen_digits(i);
+            self.0[i].unset();
+            for digit in 1..=9 {
+                if taken & (0x0001 << (digit - 1)) != 0 {
+                    self.0[i].remove_possibility_unchecked(digit);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn eliminate_all(&mut self) -> Result<(), SudokuError>
+    {
+        for i in 0..81 {
+            if self.0[i].is_set() {
+                let digit = self.0[i].get()?;
+                self.eliminate_from_peers(i, digit);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn place(&mut self, row: u8, col: u8, digit: u8)
+    {
+        self.set_unchecked(row, col, digit);
+        self.eliminate_from_peers(Pos::new_unchecked(row, col).index(), digit);
+    }
+
+    fn eliminate_from_peers(&mut self, index: usize, digit: u8)
+    {
+        for &j in tables::PEERS[index].iter() {
+            let sq = &mut self.0[usize::from(j)];
+            if !sq.is_set() {
+                sq.remove_possibility_unchecked(digit);
+            }
+        }
+    }
+
+    /// The digit at `pos`, or `None` if the square is empty.
+    pub fn get<P>(&self, pos: P) -> Result<Option<u8>, SudokuError>
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos: Pos = pos.try_into()?;
+        Ok(self[pos].get().ok())
+    }
+
+    /// The square at `pos`, with its candidates. Panics if the position is
+    /// off the grid.
+    pub fn square<P>(&self, pos: P) -> &SudokuSquare
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        &self[expect_pos(pos)]
+    }
+
+    /// The 20 squares that share a row, column or box with the square at
+    /// `pos`. Panics if the position is off the grid.
+    pub fn peers<P>(&self, pos: P) -> [&SudokuSquare; 20]
+    where
+        P: TryInto<Pos>,
+        SudokuError: From<P::Error>
+    {
+        let pos = expect_pos(pos);
+        let peers = &tables::PEERS[pos.index()];
+        std::array::from_fn(|k| &self.0[usize::from(peers[k])])
+    }
+
+    /// Iterate over the squares in row-major order.
+    pub fn iter(&self) -> std::slice::Iter<'_, SudokuSquare>
+    {
+        self.0.iter()
+    }
+
+    /// Iterate over the squares to change them. This marks the digit
+    /// boards stale, so queries rebuild them until the next edit through
+    /// the grid, such as `set` or `clear`, brings them back up to date.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, SudokuSquare>
+    {
+        self.mark_boards_stale();
+        self.0.iter_mut()
+    }
+
+    pub fn get_row(&self, row: u8) -> &[SudokuSquare]
+    {
+        let offset = Unit::Row(row).indices()[0];
+        &self.0[offset..(offset+9)]
+    }
+
+    pub fn get_col(&self, col: u8) -> [&SudokuSquare; 9]
+    {
+        self.unit(Unit::Col(col))
+    }
+
+    /// The squares of a box, numbered 1 to 9 across and then down, in
+    /// row-major order within the box.
+    pub fn get_box(&self, box_id: u8) -> [&SudokuSquare; 9]
+    {
+        self.unit(Unit::Box(box_id))
     }
 
-    pub fn set(&mut self, row: u8, col: u8, digit: u8)
+    pub fn unit(&self, unit: Unit) -> [&SudokuSquare; 9]
     {
-        let index = (9*(row-1) + (col-1)) as usize;
-        self.0[index].set_value(digit);
+        let indices = unit.indices();
+        std::array::from_fn(|k| &self.0[indices[k]])
     }
 
-    pub fn check(&self) -> Result<bool, SudokuError>
+    /// The squares of a row, to change. Like `iter_mut`, this marks the
+    /// digit boards stale until the next edit through the grid.
+    pub fn get_row_mut(&mut self, row: u8) -> &mut [SudokuSquare]
     {
-        let mut result = true;
-        let mut col_results = [0x0000; 9];
-        let mut box_results = [0x0000; 9];
-
-        for i in 1..=9 {
-            result &= self.check_row(i)?;
-            self.get_row(i).iter().enumerate().for_each(
-                |(j, &sq)| { 
-                    col_results[j] += sq.digit_bits();
-                    let idx = usize::from(sq.get_box() - 1);
-                    box_results[idx] += sq.digit_bits();
-                }
-            );
-        }
-        col_results.iter().for_each(|v| result &= *v == 0x01FF);
-        box_results.iter().for_each(|v| result &= *v == 0x01FF);
+        let offset = Unit::Row(row).indices()[0];
+        self.mark_boards_stale();
+        &mut self.0[offset..(offset+9)]
+    }
 
-        Ok(result)
+    /// The squares of a column, to change, from top to bottom. Like
+    /// `iter_mut`, this marks the digit boards stale until the next edit
+    /// through the grid.
+    pub fn get_col_mut(&mut self, col: u8) -> [&mut SudokuSquare; 9]
+    {
+        self.unit_mut(Unit::Col(col))
     }
 
-    pub fn get_row(&self, row: u8) -> &[SudokuSquare]
+    /// The squares of a box, to change, in row-major order. Like
+    /// `iter_mut`, this marks the digit boards stale until the next edit
+    /// through the grid.
+    pub fn get_box_mut(&mut self, box_id: u8) -> [&mut SudokuSquare; 9]
     {
-        let offset = 9*(row-1) as usize;
-        &self.0[offset..(offset+9)]
+        self.unit_mut(Unit::Box(box_id))
     }
 
-    fn check_row(&self, row: u8) -> Result<bool, SudokuError>
+    /// The squares of a unit, to change, in the order `unit` gives them.
+    /// Like `iter_mut`, this marks the digit boards stale until the next
+    /// edit through the grid.
+    pub fn unit_mut(&mut self, unit: Unit) -> [&mut SudokuSquare; 9]
     {
-        let row = self.get_row(row);
-        let mut result: u16 = 0;
-        for sq in row.iter() {
-            result += sq.digit_bits()
-        }
-        //println!("{:016b}", result);
-        Ok(result == 0x01FF)
+        self.mark_boards_stale();
+        // Unit indices are increasing, so one pass picks them out in order.
+        let indices = unit.indices();
+        let mut squares = self.0.iter_mut().enumerate()
+            .filter(|(i, _)| indices.contains(i))
+            .map(|(_, sq)| sq);
+        std::array::from_fn(|_| squares.next().expect("A unit has nine squares."))
+    }
+
+    /// All 27 units together with their squares.
+    pub fn units(&self) -> impl Iterator<Item = (Unit, [&SudokuSquare; 9])>
+    {
+        Unit::all().map(move |unit| (unit, self.unit(unit)))
     }
 
 }
 
 
 
+fn is_peer(a: usize, b: usize) -> bool
+{
+    let (ra, ca) = (a / 9, a % 9);
+    let (rb, cb) = (b / 9, b % 9);
+    ra == rb || ca == cb || (ra / 3 == rb / 3 && ca / 3 == cb / 3)
+}
+
+fn unit_indices() -> impl Iterator<Item = [usize; 9]>
+{
+    Unit::all().map(|unit| unit.indices())
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,7 +1059,7 @@ mod tests {
 
         match grid.check() {
             Ok(b) => { println!("{:?}", b); assert!(b); },
-            Err(e) => { println!("{:?}", e); assert!(false); }
+            Err(e) => { panic!("{:?}", e); }
         }
 
     }
@@ -266,6 +1082,115 @@ mod tests {
         assert!(grid.check().unwrap());
     }
 
+    #[test]
+    fn test_macro_expressions()
+    {
+        let row = [5u8, 3, 0];
+        let x = 6;
+        let d = 7u32;
+        let grid = sudoku_grid![row[0], row[1], _, _, d as u8, _, x + 2, -(-1i8) as u8, 0,];
+        assert_eq!(grid.get((1, 1)), Ok(Some(5)));
+        assert_eq!(grid.get((1, 2)), Ok(Some(3)));
+        assert_eq!(grid.get((1, 3)), Ok(None));
+        assert_eq!(grid.get((1, 5)), Ok(Some(7)));
+        assert_eq!(grid.get((1, 7)), Ok(Some(8)));
+        assert_eq!(grid.get((1, 8)), Ok(Some(1)));
+        assert_eq!(grid.filled_count(), 5);
+    }
+
+    #[test]
+    fn test_macro_string()
+    {
+        let grid = sudoku_grid!("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        let zeros = sudoku_grid!(concat!(
+            "530070000600195000098000060800060003400803001700020006",
+            "060000280000419005000080079"
+        ));
+        assert_eq!(grid, zeros);
+        assert_eq!(grid.filled_count(), 30);
+        assert!(!is_puzzle_literal("53..7"));
+        assert!(!is_puzzle_literal(&"x".repeat(81)));
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn test_checked_macro()
+    {
+        let grid = sudoku_grid_checked![
+            5, 3, 0, 0, 7, 0, 0, 0, 0,
+            6, _, _, 1, 9, 5, _, _, _,
+            0, 9, 8, 0, 0, 0, 0, 6, 0,
+            8, 0, 0, 0, 6, 0, 0, 0, 3,
+            4, 0, 0, 8, 0, 3, 0, 0, 1,
+            7, 0, 0, 0, 2, 0, 0, 0, 6,
+            0, 6, 0, 0, 0, 0, 2, 8, 0,
+            0, 0, 0, 4, 1, 9, 0, 0, 5,
+            0, 0, 0, 0, 8, 0, 0, 7, 9
+        ];
+        let from_str = sudoku_grid_checked!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79"
+        );
+        assert_eq!(grid, from_str);
+        assert_eq!(grid.filled_count(), 30);
+    }
+
+    #[test]
+    fn test_try_from_arrays()
+    {
+        let mut rows = [[0u8; 9]; 9];
+        rows[0] = [5, 3, 0, 0, 7, 0, 0, 0, 0];
+        rows[8][8] = 9;
+        let grid = SudokuGrid::try_from(rows).unwrap();
+        assert_eq!(grid.filled_count(), 4);
+        assert_eq!(grid.0[1].get(), Ok(3));
+        assert_eq!(grid.0[80].get(), Ok(9));
+
+        let options = rows.map(|row| row.map(|d| if d == 0 { None } else { Some(d) }));
+        assert_eq!(SudokuGrid::try_from(options), Ok(grid));
+
+        rows[4][2] = 10;
+        assert_eq!(SudokuGrid::try_from(rows), Err(SudokuError::InvalidDigit { digit: 10 }));
+        let mut zero = [[None; 9]; 9];
+        zero[0][0] = Some(0);
+        assert_eq!(SudokuGrid::try_from(zero), Err(SudokuError::InvalidDigit { digit: 0 }));
+    }
+
+    #[test]
+    fn test_to_line_string()
+    {
+        let line = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        );
+        let grid: SudokuGrid = line.parse().unwrap();
+        assert_eq!(grid.to_line_string(), line);
+        assert_eq!(grid.to_line_string_with('0'), line.replace('.', "0"));
+        assert_eq!(grid.to_line_string_with('0').parse(), Ok(grid));
+    }
+
+    #[test]
+    fn test_macro_blank_squares()
+    {
+        let grid = sudoku_grid![
+            5, 3, 0, 0, 7, 0, 0, 0, 0,
+            6, _, _, 1, 9, 5, _, _, _,
+            0, 9, 8, 0, 0, 0, 0, 6, 0,
+            8, 0, 0, 0, 6, 0, 0, 0, 3,
+            4, 0, 0, 8, 0, 3, 0, 0, 1,
+            7, 0, 0, 0, 2, 0, 0, 0, 6,
+            0, 6, 0, 0, 0, 0, 2, 8, 0,
+            0, 0, 0, 4, 1, 9, 0, 0, 5,
+            0, 0, 0, 0, 8, 0, 0, 7, 9,
+        ];
+        let expected: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        assert_eq!(grid, expected);
+        assert_eq!(grid.0[2].digit_bits(), 0x01FF);
+        assert!(grid.has_unique_solution());
+    }
+
 
     #[test]
     fn test_check_false_solution() 
@@ -285,4 +1210,501 @@ mod tests {
         assert!(!grid.check().unwrap());
     }
 
-}
\ No newline at end of file
+
+    #[test]
+    fn test_propagate_fills_naked_singles()
+    {
+        let solution = sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ];
+
+        let mut grid = SudokuGrid::new();
+        for (i, sq) in solution.0.iter().enumerate() {
+            if i % 4 != 0 {
+                grid.set((sq.row(), sq.col()), sq.get().unwrap()).unwrap();
+            }
+        }
+
+        assert_eq!(grid.propagate().unwrap(), 21);
+        assert!(grid.check().unwrap());
+    }
+
+
+    #[test]
+    fn test_empty_cells_by_candidates()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 1).unwrap();
+        for digit in 3..=9 {
+            grid.0[40].remove_possibility(digit).unwrap();
+        }
+        for digit in 4..=9 {
+            grid.0[80].remove_possibility(digit).unwrap();
+        }
+        grid.0[2].remove_possibility(4).unwrap();
+
+        let order: Vec<(u8, u8)> = grid.empty_cells_by_candidates()
+            .map(|sq| (sq.row(), sq.col()))
+            .collect();
+        assert_eq!(order.len(), 80);
+        assert_eq!(&order[..4], &[(5, 5), (9, 9), (1, 3), (1, 2)]);
+    }
+
+    #[test]
+    fn test_find_hidden_singles()
+    {
+        // 5 is placed in rows 2 and 3 and columns 2 and 3, which leaves
+        // the first square as the only home for 5 in the top-left box.
+        let mut grid = sudoku_grid![
+            (2, 4), 5,
+            (3, 7), 5,
+            (4, 2), 5,
+            (7, 3), 5
+        ];
+        grid.propagate().unwrap();
+
+        assert!(grid.find_hidden_singles().contains(&(1, 1, 5)));
+    }
+
+    #[test]
+    fn test_has_unique_solution()
+    {
+        let mut grid = sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ];
+        assert!(grid.has_unique_solution());
+
+        // Only the first two rows on their own leave many solutions.
+        for row in 3..=9 {
+            for col in 1..=9 {
+                grid.clear((row, col)).unwrap();
+            }
+        }
+        assert!(!grid.has_unique_solution());
+        assert!(!SudokuGrid::new().has_unique_solution());
+    }
+
+    #[test]
+    fn test_minimize()
+    {
+        let mut grid = sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ];
+        assert!(!grid.is_minimal());
+
+        let removed = grid.minimize().unwrap();
+        assert!(removed > 50);
+        assert!(grid.is_minimal());
+        assert!(SudokuGrid::new().minimize().is_err());
+    }
+
+    #[test]
+    fn test_redundant_clues()
+    {
+        let mut grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        let redundant = grid.redundant_clues();
+        assert!(!redundant.is_empty());
+        for &pos in redundant.iter() {
+            let mut fewer = grid.clone();
+            fewer.clear(pos).unwrap();
+            assert_eq!(fewer.count_solutions(2), 1);
+        }
+
+        // With givens marked, squares the player filled aren't clues.
+        let mut played = grid.clone();
+        played.mark_givens();
+        let solution = played.solve().unwrap();
+        for i in (0..81).filter(|&i| !grid.0[i].is_set()).take(20) {
+            let sq = &solution.0[i];
+            played.set((sq.row(), sq.col()), sq.get().unwrap()).unwrap();
+        }
+        assert_eq!(played.redundant_clues(), redundant);
+
+        grid.minimize().unwrap();
+        assert!(grid.redundant_clues().is_empty());
+        assert!(SudokuGrid::new().redundant_clues().is_empty());
+    }
+
+    #[test]
+    fn test_from_str()
+    {
+        let grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        assert_eq!(grid.0[0].get().unwrap(), 5);
+        assert_eq!(grid.0[4].get().unwrap(), 7);
+        assert!(!grid.0[2].is_set());
+        assert_eq!(grid.0[80].get().unwrap(), 9);
+        assert!(grid.has_unique_solution());
+    }
+
+    #[test]
+    fn test_iter_row_major()
+    {
+        let mut grid = SudokuGrid::new();
+        let positions: Vec<(u8, u8)> = grid.iter().map(|sq| (sq.row(), sq.col())).collect();
+        assert_eq!(positions.len(), 81);
+        assert_eq!(positions[0], (1, 1));
+        assert_eq!(positions[10], (2, 2));
+        assert_eq!(positions[80], (9, 9));
+
+        for sq in &mut grid {
+            sq.remove_possibility(1).unwrap();
+        }
+        assert!((&grid).into_iter().all(|sq| !sq.is_possible(1)));
+    }
+
+    #[test]
+    fn test_get_col_and_box()
+    {
+        let grid = SudokuGrid::new();
+
+        let col = grid.get_col(4);
+        assert!(col.iter().enumerate().all(|(r, sq)| sq.row() == (r + 1) as u8 && sq.col() == 4));
+
+        for b in 1..=9 {
+            assert!(grid.get_box(b).iter().all(|sq| sq.get_box() == b));
+        }
+        let bx = grid.get_box(6);
+        assert_eq!((bx[0].row(), bx[0].col()), (4, 7));
+        assert_eq!((bx[8].row(), bx[8].col()), (6, 9));
+    }
+
+    #[test]
+    fn test_units()
+    {
+        let grid = SudokuGrid::new();
+        let units: Vec<_> = grid.units().collect();
+        assert_eq!(units.len(), 27);
+        assert_eq!(units[0].0, Unit::Row(1));
+        assert_eq!(units[26].0, Unit::Box(9));
+        assert!(units.iter().all(
+            |(unit, squares)| squares.iter().all(|sq| unit.contains(sq.row(), sq.col()))
+        ));
+    }
+
+    #[test]
+    fn test_clone_eq_hash()
+    {
+        use std::collections::HashSet;
+
+        let mut grid = SudokuGrid::new();
+        grid.set((3, 3), 3).unwrap();
+        let copy = grid.clone();
+        assert_eq!(grid, copy);
+
+        grid.set((4, 4), 4).unwrap();
+        assert_ne!(grid, copy);
+
+        let set: HashSet<SudokuGrid> = vec![grid.clone(), copy, grid].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_display()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 5).unwrap();
+        grid.set((5, 9), 3).unwrap();
+
+        let drawn = grid.to_string();
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(lines.len(), 13);
+        assert_eq!(lines[0], "+-------+-------+-------+");
+        assert_eq!(lines[1], "| 5 . . | . . . | . . . |");
+        assert_eq!(lines[6], "| . . . | . . . | . . 3 |");
+        assert_eq!(lines[12], "+-------+-------+-------+");
+    }
+
+    #[test]
+    fn test_set_all()
+    {
+        let grid: SudokuGrid = vec![(1, 1, 5), (2, 4, 3), (9, 9, 1)].into_iter().collect();
+        assert_eq!(grid.filled_count(), 3);
+        assert!(grid.0[12].is(3));
+
+        let mut grid = SudokuGrid::new();
+        grid.set_given((5, 5), 7).unwrap();
+        assert_eq!(grid.set_all(vec![(1, 1, 5), (1, 0, 2)]), Err(SudokuError::InvalidPosition { row: 1, col: 0 }));
+        assert_eq!(grid.set_all(vec![(1, 1, 5), (3, 3, 0)]), Err(SudokuError::InvalidEntry { row: 3, col: 3, digit: 0 }));
+        assert_eq!(grid.set_all(vec![(1, 1, 5), (1, 1, 6)]), Err(SudokuError::DuplicateSquare { row: 1, col: 1 }));
+        assert_eq!(grid.set_all(vec![(1, 1, 5), (3, 2, 5)]), Err(SudokuError::DuplicateDigit { row: 3, col: 2, digit: 5 }));
+        assert_eq!(grid.set_all(vec![(5, 5, 1)]), Err(SudokuError::IsGiven { row: 5, col: 5 }));
+        assert_eq!(grid.filled_count(), 1);
+
+        grid.set_all(vec![(1, 1, 5), (2, 2, 6)]).unwrap();
+        assert_eq!(grid.filled_count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid entry in grid")]
+    fn test_from_iter_panics()
+    {
+        let _: SudokuGrid = vec![(1, 1, 5), (1, 2, 5)].into_iter().collect();
+    }
+
+    #[test]
+    fn test_from_values()
+    {
+        let mut values = [0u8; 81];
+        values[0] = 5;
+        values[80] = 9;
+        let grid = SudokuGrid::from_values(&values).unwrap();
+        assert_eq!(grid.filled_count(), 2);
+        assert!(grid.0[80].is(9));
+
+        values[40] = 10;
+        assert_eq!(SudokuGrid::from_values(&values), Err(SudokuError::InvalidEntry { row: 5, col: 5, digit: 10 }));
+        values[40] = 0;
+        values[8] = 5;
+        assert_eq!(SudokuGrid::from_values(&values), Err(SudokuError::DuplicateDigit { row: 1, col: 9, digit: 5 }));
+    }
+
+    #[test]
+    fn test_get_and_square()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((4, 7), 2).unwrap();
+        assert_eq!(grid.get((4, 7)), Ok(Some(2)));
+        assert_eq!(grid.get((7, 4)), Ok(None));
+        assert_eq!(grid.get((10, 1)), Err(SudokuError::InvalidPosition { row: 10, col: 1 }));
+
+        grid.set_and_propagate((4, 7), 2).unwrap();
+        assert_eq!(grid.square((4, 8)).candidate_mask(), 0x01FD);
+        assert_eq!((grid.square((9, 1)).row(), grid.square((9, 1)).col()), (9, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid position (0, 3)")]
+    fn test_square_panics()
+    {
+        SudokuGrid::new().square((0, 3));
+    }
+
+    #[test]
+    fn test_unit_mut()
+    {
+        let mut grid = SudokuGrid::new();
+        for sq in grid.get_box_mut(5).iter_mut() {
+            sq.remove_possibility(7).unwrap();
+        }
+        assert!(grid.get_box(5).iter().all(|sq| !sq.is_possible(7)));
+        assert!(grid.square((4, 3)).is_possible(7));
+
+        let col = grid.get_col_mut(9);
+        assert_eq!((col[8].row(), col[8].col()), (9, 9));
+        col[2].force_value(4).unwrap();
+        assert!(!grid.has_conflicts());
+        grid.get_row_mut(3)[0].force_value(4).unwrap();
+        assert_eq!(grid.conflicts(), vec![((3, 1), (3, 9), 4)]);
+
+        // The next edit through the grid brings the boards back up to date.
+        assert!(grid.1.is_stale());
+        grid.clear((3, 1)).unwrap();
+        assert!(!grid.1.is_stale());
+        assert!(!grid.has_conflicts());
+        grid.unit_mut(Unit::Row(1))[0].force_value(2).unwrap();
+        grid.set((2, 2), 2).unwrap();
+        assert!(!grid.1.is_stale());
+        assert_eq!(grid.filled_count(), 3);
+        assert!(grid.has_conflicts());
+    }
+
+    #[test]
+    fn test_index_by_pos()
+    {
+        let grid: SudokuGrid = "985423716134679582627815394376942851519786243842351967493567128258134679761298435"
+            .parse().unwrap();
+        let pos = Pos::try_from((4, 7)).unwrap();
+        assert_eq!(grid[pos].get(), Ok(8));
+        assert_eq!(grid[pos].pos(), pos);
+        assert!(pos.peers().iter().all(|&peer| grid[peer].get() != Ok(8)));
+    }
+
+    #[test]
+    fn test_methods_take_pos()
+    {
+        let mut grid = SudokuGrid::new();
+        let pos = Pos::new(4, 7).unwrap();
+        grid.set(pos, 8).unwrap();
+        assert_eq!(grid.get((4, 7)), Ok(Some(8)));
+        assert_eq!(grid.square(pos).get(), Ok(8));
+        let other = Pos::new(4, 1).unwrap();
+        assert!(grid.peers(other).iter().any(|sq| sq.pos() == pos));
+        assert_eq!(grid.remove_candidate(other, 8), Ok(true));
+        grid.clear(pos).unwrap();
+        assert_eq!(grid.get(pos), Ok(None));
+    }
+
+    #[test]
+    fn test_digits()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((2, 3), Digit::ALL[6]).unwrap();
+        assert_eq!(grid.get((2, 3)), Ok(Some(7)));
+        assert_eq!(grid.get_digit((2, 3)), Ok(Digit::new(7)));
+        assert_eq!(grid.get_digit((2, 4)), Ok(None));
+        assert_eq!(grid.square((2, 3)).digit(), Digit::new(7));
+        assert!(grid.square((2, 4)).is_possible(Digit::ALL[0]));
+        assert!(!grid.square((2, 4)).is_possible(0));
+        assert!(!grid.square((2, 4)).is_possible(10));
+        assert_eq!(grid.remove_candidate((2, 4), Digit::ALL[0]), Ok(true));
+        assert_eq!(grid.set_given((2, 4), 10), Err(SudokuError::InvalidDigit { digit: 10 }));
+    }
+
+    #[test]
+    fn test_display_candidates()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 5).unwrap();
+        grid.propagate().unwrap();
+
+        let drawn = format!("{:#}", grid);
+        let lines: Vec<&str> = drawn.lines().collect();
+        assert_eq!(lines.len(), 37);
+        assert_eq!(lines[0], "+-------------+-------------+-------------+");
+        assert_eq!(lines[1], "|     123 123 | 123 123 123 | 123 123 123 |");
+        assert_eq!(lines[2], "|  5  4.6 4.6 | 4.6 4.6 4.6 | 4.6 4.6 4.6 |");
+        assert_eq!(lines[3], "|     789 789 | 789 789 789 | 789 789 789 |");
+        assert_eq!(lines[4], "|             |             |             |");
+        assert_eq!(lines[12], "+-------------+-------------+-------------+");
+        assert_eq!(lines[14], "| 4.6 456 456 | 456 456 456 | 456 456 456 |");
+    }
+
+    #[test]
+    fn test_from_str_errors()
+    {
+        match "123".parse::<SudokuGrid>() {
+            Err(SudokuError::InvalidLength { length }) => assert_eq!(length, 3),
+            _ => panic!("expected a length error")
+        }
+
+        let mut s = "0".repeat(81);
+        s.replace_range(10..11, "x");
+        match s.parse::<SudokuGrid>() {
+            Err(SudokuError::InvalidCharacter { position, character }) => {
+                assert_eq!(position, 10);
+                assert_eq!(character, 'x');
+            },
+            _ => panic!("expected a character error")
+        }
+    }
+
+    #[test]
+    fn test_set_validates_arguments()
+    {
+        let mut grid = SudokuGrid::new();
+        assert_eq!(grid.set((0, 1), 1), Err(SudokuError::InvalidPosition { row: 0, col: 1 }));
+        assert_eq!(grid.set((1, 10), 1), Err(SudokuError::InvalidPosition { row: 1, col: 10 }));
+        assert_eq!(grid.set((1, 1), 0), Err(SudokuError::InvalidDigit { digit: 0 }));
+        assert_eq!(grid.set((1, 1), 10), Err(SudokuError::InvalidDigit { digit: 10 }));
+        assert!(grid.iter().all(|sq| !sq.is_set()));
+
+        grid.set((9, 9), 9).unwrap();
+        assert_eq!(grid.0[80].get().unwrap(), 9);
+    }
+
+    #[test]
+    fn test_givens_are_protected()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set_given((1, 1), 5).unwrap();
+        grid.set((1, 2), 6).unwrap();
+
+        assert!(grid.0[0].is_given());
+        assert!(!grid.0[1].is_given());
+        assert_eq!(grid.set((1, 1), 4), Err(SudokuError::IsGiven { row: 1, col: 1 }));
+        assert_eq!(grid.clear((1, 1)), Err(SudokuError::IsGiven { row: 1, col: 1 }));
+        grid.set((1, 2), 7).unwrap();
+
+        grid.mark_givens();
+        assert!(grid.clear((1, 2)).is_err());
+    }
+
+    #[test]
+    fn test_set_and_propagate()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set_and_propagate((5, 5), 8).unwrap();
+
+        let updated = grid.iter().filter(|sq| !sq.is_set() && !sq.is_possible(8)).count();
+        assert_eq!(updated, 20);
+        assert!(!grid.0[9*4].is_possible(8));
+        assert!(!grid.0[9*3 + 3].is_possible(8));
+        assert!(grid.0[0].is_possible(8));
+        assert!(grid.set_and_propagate((5, 5), 0).is_err());
+    }
+
+    #[test]
+    fn test_peers()
+    {
+        let grid = SudokuGrid::new();
+        let peers = grid.peers((5, 5));
+        assert!(peers.iter().all(|sq| sq.row() == 5 || sq.col() == 5 || sq.get_box() == 5));
+        assert!(peers.iter().all(|sq| sq.row() != 5 || sq.col() != 5));
+    }
+
+    #[test]
+    fn test_clear()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 4).unwrap();
+        grid.set((1, 9), 7).unwrap();
+        grid.propagate().unwrap();
+        assert!(!grid.0[1].is_possible(4));
+
+        grid.clear((1, 1)).unwrap();
+        assert!(!grid.0[0].is_set());
+        assert_eq!(grid.0[0].possibilities_number(), 9);
+        assert!(!grid.0[1].is_possible(4));
+        assert!(grid.clear((0, 1)).is_err());
+
+        grid.set((1, 1), 4).unwrap();
+        grid.clear_and_recompute((1, 1)).unwrap();
+        assert!(grid.0[1].is_possible(4));
+        assert!(!grid.0[1].is_possible(7));
+        assert!(!grid.0[0].is_possible(7));
+        assert_eq!(grid.0[0].get_box(), 1);
+    }
+
+    #[test]
+    fn test_count_solutions_with_cap()
+    {
+        let mut grid = SudokuGrid::new();
+        assert_eq!(grid.count_solutions(0), 0);
+        assert_eq!(grid.count_solutions(10), 10);
+
+        grid.set((1, 1), 1).unwrap();
+        grid.set((1, 2), 1).unwrap();
+        assert_eq!(grid.count_solutions(10), 0);
+    }
+
+}
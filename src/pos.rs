@@ -0,0 +1,148 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{Position, SudokuError};
+use crate::tables;
+
+
+/// The position of a square, with row and column numbered from 1. Every
+/// `Pos` is on the grid: the fields are private and the constructors check
+/// them. The grid methods take a `Pos` or a `(row, col)` pair, which they
+/// check through `TryFrom`. A `Pos` also indexes a grid directly and
+/// converts to a `Position`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pos {
+    row: u8,
+    col: u8
+}
+
+
+impl Pos {
+
+    /// The position at `row`, `col`, or `None` unless both are from 1 to 9.
+    pub const fn new(row: u8, col: u8) -> Option<Pos>
+    {
+        if row == 0 || col == 0 || row > 9 || col > 9 {
+            return None;
+        }
+        Some(Pos { row, col })
+    }
+
+    /// The position at `row`, `col`, for callers that have already
+    /// checked both are from 1 to 9.
+    pub(crate) const fn new_unchecked(row: u8, col: u8) -> Pos
+    {
+        Pos { row, col }
+    }
+
+    pub const fn row(self) -> u8
+    {
+        self.row
+    }
+
+    pub const fn col(self) -> u8
+    {
+        self.col
+    }
+
+    /// The position of square `index` in row-major order. Panics unless
+    /// `index` is below 81.
+    pub const fn from_index(index: usize) -> Pos
+    {
+        assert!(index < 81, "Square index out of range.");
+        Pos { row: (index / 9 + 1) as u8, col: (index % 9 + 1) as u8 }
+    }
+
+    /// All 81 positions in row-major order.
+    pub fn all() -> impl Iterator<Item = Pos>
+    {
+        (0..81).map(Pos::from_index)
+    }
+
+    /// The index of the square in row-major order, from 0 to 80.
+    pub const fn index(self) -> usize
+    {
+        9*(self.row as usize - 1) + (self.col as usize - 1)
+    }
+
+    /// The box holding the square, numbered from 1 across then down.
+    pub const fn box_id(self) -> u8
+    {
+        3*((self.row - 1) / 3) + (self.col - 1) / 3 + 1
+    }
+
+    /// The 20 positions sharing a row, column or box with this one, in
+    /// row-major order.
+    pub fn peers(self) -> [Pos; 20]
+    {
+        let peers = &tables::PEERS[self.index()];
+        std::array::from_fn(|k| Pos::from_index(usize::from(peers[k])))
+    }
+
+    /// Whether `other` shares a row, column or box with this position.
+    pub fn sees(self, other: Pos) -> bool
+    {
+        self != other
+            && (self.row == other.row || self.col == other.col || self.box_id() == other.box_id())
+    }
+}
+
+
+impl TryFrom<(u8, u8)> for Pos {
+    type Error = SudokuError;
+
+    fn try_from((row, col): (u8, u8)) -> Result<Pos, SudokuError>
+    {
+        Pos::new(row, col).ok_or(SudokuError::InvalidPosition { row, col })
+    }
+}
+
+
+impl From<Pos> for Position {
+
+    fn from(pos: Pos) -> Position
+    {
+        (pos.row, pos.col)
+    }
+}
+
+
+impl fmt::Display for Pos {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "r{}c{}", self.row, self.col)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_round_trip()
+    {
+        for (index, pos) in Pos::all().enumerate() {
+            assert_eq!(pos.index(), index);
+            assert_eq!(Pos::try_from((pos.row(), pos.col())), Ok(pos));
+        }
+        assert_eq!(Pos::try_from((0, 4)), Err(SudokuError::InvalidPosition { row: 0, col: 4 }));
+        assert_eq!(Pos::new(5, 10), None);
+        assert_eq!(Pos::from_index(80).to_string(), "r9c9");
+    }
+
+    #[test]
+    fn test_box_and_peers()
+    {
+        let pos = Pos::new(5, 7).unwrap();
+        assert_eq!(pos.box_id(), 6);
+        assert_eq!(Pos::new(1, 1).unwrap().box_id(), 1);
+        assert_eq!(Pos::new(9, 9).unwrap().box_id(), 9);
+
+        let peers = pos.peers();
+        assert!(peers.iter().all(|&peer| pos.sees(peer)));
+        assert_eq!(Pos::all().filter(|&other| pos.sees(other)).count(), 20);
+        assert!(!pos.sees(pos));
+    }
+}
@@ -0,0 +1,187 @@
+//! Python bindings through PyO3.
+//!
+//! Build an extension module with maturin, e.g. `maturin develop
+//! --features python,pyo3/extension-module`, then `import bitsudoku`.
+//! Errors surface as `ValueError` with the crate's error message.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{SudokuError, SudokuGrid};
+use crate::generator;
+use crate::rating::{Difficulty, rate};
+use crate::solver;
+
+
+impl From<SudokuError> for PyErr {
+    fn from(err: SudokuError) -> PyErr
+    {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+
+/// A sudoku grid, created empty or from an 81 character puzzle string.
+#[pyclass(name = "SudokuGrid", module = "bitsudoku")]
+#[derive(Clone)]
+pub struct PyGrid {
+    grid: SudokuGrid
+}
+
+
+#[pymethods]
+impl PyGrid {
+
+    #[new]
+    #[pyo3(signature = (puzzle=None))]
+    fn new(puzzle: Option<&str>) -> PyResult<PyGrid>
+    {
+        let grid = match puzzle {
+            Some(puzzle) => puzzle.parse()?,
+            None => SudokuGrid::new()
+        };
+        Ok(PyGrid { grid })
+    }
+
+    /// The digit at `row`, `col`, or `None` if the square is empty.
+    fn get(&self, row: u8, col: u8) -> PyResult<Option<u8>>
+    {
+        Ok(self.grid.get((row, col))?)
+    }
+
+    fn set(&mut self, row: u8, col: u8, digit: u8) -> PyResult<()>
+    {
+        Ok(self.grid.set((row, col), digit)?)
+    }
+
+    fn clear(&mut self, row: u8, col: u8) -> PyResult<()>
+    {
+        Ok(self.grid.clear((row, col))?)
+    }
+
+    fn solve(&self) -> PyResult<PyGrid>
+    {
+        Ok(PyGrid { grid: self.grid.solve()? })
+    }
+
+    #[pyo3(signature = (limit=2))]
+    fn count_solutions(&self, limit: usize) -> usize
+    {
+        self.grid.count_solutions(limit)
+    }
+
+    fn has_unique_solution(&self) -> bool
+    {
+        self.grid.has_unique_solution()
+    }
+
+    fn is_solved(&self) -> bool
+    {
+        self.grid.is_solved()
+    }
+
+    fn filled_count(&self) -> usize
+    {
+        self.grid.filled_count()
+    }
+
+    /// The grade of the puzzle, such as `"Hard"`.
+    fn rate(&self) -> PyResult<String>
+    {
+        Ok(rate(&self.grid)?.to_string())
+    }
+
+    /// The puzzle as 81 characters with `.` for empty squares.
+    fn line(&self) -> String
+    {
+        self.grid.to_line_string()
+    }
+
+    fn __str__(&self) -> String
+    {
+        self.grid.to_string()
+    }
+
+    fn __repr__(&self) -> String
+    {
+        format!("SudokuGrid('{}')", self.grid.to_line_string())
+    }
+
+    fn __eq__(&self, other: &PyGrid) -> bool
+    {
+        self.grid == other.grid
+    }
+}
+
+
+/// Generate a puzzle, optionally at a named difficulty, or reproducibly
+/// from a seed. A seed can't be combined with a difficulty.
+#[pyfunction]
+#[pyo3(signature = (difficulty=None, seed=None))]
+fn generate(difficulty: Option<&str>, seed: Option<u64>) -> PyResult<PyGrid>
+{
+    let grid = match (difficulty, seed) {
+        (None, None) => generator::generate(),
+        (None, Some(seed)) => generator::generate_seeded(seed),
+        (Some(name), None) => {
+            let difficulty = Difficulty::from_name(name).ok_or_else(|| {
+                PyValueError::new_err(format!("unknown difficulty {:?}", name))
+            })?;
+            generator::generate_with_difficulty(difficulty)?
+        },
+        (Some(_), Some(_)) => {
+            return Err(PyValueError::new_err("difficulty and seed can't be combined"));
+        }
+    };
+    Ok(PyGrid { grid })
+}
+
+/// Solve a list of puzzle strings, giving `None` for those that fail to
+/// parse or have no solution. Uses every core with the `parallel` feature.
+#[pyfunction]
+fn solve_many(py: Python<'_>, puzzles: Vec<String>) -> Vec<Option<String>>
+{
+    py.allow_threads(|| {
+        let grids: Vec<Option<SudokuGrid>> = puzzles.iter().map(|p| p.parse().ok()).collect();
+        let parsed: Vec<SudokuGrid> = grids.iter().flatten().cloned().collect();
+        let mut solved = solver::solve_many(&parsed).into_iter();
+        grids.iter().map(|grid| {
+            grid.as_ref()?;
+            solved.next()?.ok().map(|g| g.to_line_string())
+        }).collect()
+    })
+}
+
+
+#[pymodule]
+fn bitsudoku(m: &Bound<'_, PyModule>) -> PyResult<()>
+{
+    m.add_class::<PyGrid>()?;
+    m.add_function(wrap_pyfunction!(generate, m)?)?;
+    m.add_function(wrap_pyfunction!(solve_many, m)?)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = concat!(
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+        ".6....28....419..5....8..79"
+    );
+
+    #[test]
+    fn test_grid_methods()
+    {
+        let grid = PyGrid::new(Some(PUZZLE)).unwrap();
+        assert_eq!(grid.get(1, 1).unwrap(), Some(5));
+        assert_eq!(grid.get(1, 3).unwrap(), None);
+        assert_eq!(grid.rate().unwrap(), "Easy");
+
+        let solved = grid.solve().unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(grid.__repr__(), format!("SudokuGrid('{}')", PUZZLE));
+    }
+}
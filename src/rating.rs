@@ -0,0 +1,161 @@
+//! Difficulty rating based on the logical solver.
+
+use std::fmt;
+
+use crate::{SudokuGrid, SudokuError};
+use crate::solver::LogicalSolver;
+use crate::techniques::Technique;
+
+
+/// Number of steps beyond singles after which a puzzle is bumped up a grade.
+const STEP_THRESHOLD: usize = 4;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    Diabolical
+}
+
+impl Difficulty {
+
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Diabolical
+    ];
+
+    /// Look a grade up by its display name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Difficulty>
+    {
+        Difficulty::ALL.iter().copied().find(|d| d.to_string().eq_ignore_ascii_case(name))
+    }
+
+    fn bump(self) -> Difficulty
+    {
+        match self {
+            Difficulty::Easy => Difficulty::Medium,
+            Difficulty::Medium => Difficulty::Hard,
+            _ => Difficulty::Expert
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+            Difficulty::Diabolical => "Diabolical"
+        };
+        f.write_str(name)
+    }
+}
+
+
+/// The outcome of rating a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    /// The hardest technique used, `None` if the grid was already full.
+    pub hardest: Option<Technique>,
+    pub steps: usize,
+    /// Whether the logical solver finished the puzzle.
+    pub solved: bool
+}
+
+
+fn technique_grade(technique: Technique) -> Difficulty
+{
+    match technique {
+        Technique::NakedSingle | Technique::HiddenSingle => Difficulty::Easy,
+        Technique::NakedPair | Technique::NakedTriple | Technique::NakedQuad => Difficulty::Medium,
+        Technique::XWing | Technique::Swordfish | Technique::Jellyfish => Difficulty::Hard,
+        Technique::XYWing | Technique::XYZWing | Technique::SimpleColouring => Difficulty::Expert
+    }
+}
+
+/// Solve the puzzle with the logical solver and grade it on the hardest
+/// technique needed and the number of steps beyond singles. Puzzles the
+/// solver can't finish without guessing are `Diabolical`.
+pub fn assess(grid: &SudokuGrid) -> Result<Rating, SudokuError>
+{
+    let mut work = grid.clone();
+    let steps = LogicalSolver::new().solve(&mut work)?;
+    let solved = work.0.iter().all(|sq| sq.is_set());
+    let hardest = steps.iter().map(|s| s.technique).max();
+
+    let difficulty = if !solved {
+        Difficulty::Diabolical
+    } else {
+        let grade = hardest.map_or(Difficulty::Easy, technique_grade);
+        let advanced = steps.iter().filter(|s| s.technique > Technique::HiddenSingle).count();
+        if advanced > STEP_THRESHOLD { grade.bump() } else { grade }
+    };
+
+    Ok(Rating { difficulty, hardest, steps: steps.len(), solved })
+}
+
+pub fn rate(grid: &SudokuGrid) -> Result<Difficulty, SudokuError>
+{
+    Ok(assess(grid)?.difficulty)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_from(values: &[u8]) -> SudokuGrid
+    {
+        let mut grid = SudokuGrid::new();
+        for (i, &v) in values.iter().enumerate() {
+            if v != 0 {
+                grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, v);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_rate_easy()
+    {
+        let grid = grid_from(&[
+            5, 3, 0, 0, 7, 0, 0, 0, 0,
+            6, 0, 0, 1, 9, 5, 0, 0, 0,
+            0, 9, 8, 0, 0, 0, 0, 6, 0,
+            8, 0, 0, 0, 6, 0, 0, 0, 3,
+            4, 0, 0, 8, 0, 3, 0, 0, 1,
+            7, 0, 0, 0, 2, 0, 0, 0, 6,
+            0, 6, 0, 0, 0, 0, 2, 8, 0,
+            0, 0, 0, 4, 1, 9, 0, 0, 5,
+            0, 0, 0, 0, 8, 0, 0, 7, 9
+        ]);
+        assert_eq!(rate(&grid).unwrap(), Difficulty::Easy);
+    }
+
+    #[test]
+    fn test_rate_empty_is_diabolical()
+    {
+        let rating = assess(&SudokuGrid::new()).unwrap();
+        assert_eq!(rating.difficulty, Difficulty::Diabolical);
+        assert!(!rating.solved);
+    }
+
+    #[test]
+    fn test_difficulty_order()
+    {
+        assert!(Difficulty::Easy < Difficulty::Hard);
+        assert_eq!(Difficulty::Expert.bump(), Difficulty::Expert);
+        assert_eq!(Difficulty::from_name("hard"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::from_name("tricky"), None);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{Position, SudokuGrid};
+
+
+/// Draw a grid like its `Display` form, coloured with ANSI escapes for a
+/// terminal: givens in bold, other set squares in cyan, digits that repeat
+/// in a unit in red and `cells`, for instance those of the last `Hint`, on
+/// a yellow background.
+pub fn ansi(grid: &SudokuGrid, cells: &[Position]) -> String
+{
+    const SEPARATOR: &str = "+-------+-------+-------+";
+
+    let conflicts = grid.conflicts();
+    let mut out = String::new();
+    for (r, row) in grid.0.chunks(9).enumerate() {
+        if r % 3 == 0 {
+            out.push_str(SEPARATOR);
+            out.push('\n');
+        }
+        for (c, sq) in row.iter().enumerate() {
+            if c % 3 == 0 {
+                out.push_str("| ");
+            }
+            let position = (sq.row(), sq.col());
+            let mut codes = Vec::new();
+            match sq.get() {
+                Ok(_) if conflicts.iter().any(|&(a, b, _)| a == position || b == position) =>
+                    codes.push("1;31"),
+                Ok(_) if sq.is_given() => codes.push("1"),
+                Ok(_) => codes.push("36"),
+                Err(_) => {}
+            }
+            if cells.contains(&position) {
+                codes.push("43");
+            }
+
+            let text = sq.get().map(|digit| (b'0' + digit) as char).unwrap_or('.');
+            if codes.is_empty() {
+                out.push(text);
+            } else {
+                out.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text));
+            }
+            out.push(' ');
+        }
+        out.push_str("|\n");
+    }
+    out.push_str(SEPARATOR);
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set_given((1, 1), 5).unwrap();
+        grid.set((1, 2), 3).unwrap();
+        grid.set_unchecked(9, 2, 3);
+
+        let out = ansi(&grid, &[(1, 2), (1, 3)]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[1], "| \x1b[1m5\x1b[0m \x1b[1;31;43m3\x1b[0m \x1b[43m.\x1b[0m | . . . | . . . |");
+        assert!(lines[11].contains("\x1b[1;31m3\x1b[0m"));
+
+        // Without colours it draws the same grid as `Display`.
+        let plain = ansi(&grid, &[]).replace("\x1b[0m", "")
+            .replace("\x1b[1;31m", "").replace("\x1b[1m", "");
+        assert_eq!(plain, grid.to_string());
+    }
+}
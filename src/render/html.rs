@@ -0,0 +1,78 @@
+use std::fmt::Write;
+
+use crate::{Position, SudokuGrid};
+
+
+/// Write a grid as an HTML table for server-side pages. See
+/// `html_highlighted` for the classes it carries.
+pub fn html(grid: &SudokuGrid) -> String
+{
+    html_highlighted(grid, &[])
+}
+
+/// Write a grid as an HTML table of class `sudoku`, with `cells` marked,
+/// for instance those of a `Hint`. Each `td` carries a class for what it
+/// holds, `given`, `solved` or `empty`, and may also carry `conflict` if
+/// its digit repeats in a unit, `highlight` if it is one of `cells`, and
+/// `box-right` or `box-bottom` on the last column or row of a box that
+/// isn't on the edge of the grid. Styling is left to the page.
+pub fn html_highlighted(grid: &SudokuGrid, cells: &[Position]) -> String
+{
+    let conflicts = grid.conflicts();
+    let mut out = String::from("<table class=\"sudoku\">\n");
+    for row in 1..=9u8 {
+        out.push_str("<tr>");
+        for sq in grid.get_row(row) {
+            let position = (sq.row(), sq.col());
+            let mut classes = vec![match sq.get() {
+                Ok(_) if sq.is_given() => "given",
+                Ok(_) => "solved",
+                Err(_) => "empty"
+            }];
+            if conflicts.iter().any(|&(a, b, _)| a == position || b == position) {
+                classes.push("conflict");
+            }
+            if cells.contains(&position) {
+                classes.push("highlight");
+            }
+            if sq.col() % 3 == 0 && sq.col() < 9 {
+                classes.push("box-right");
+            }
+            if row % 3 == 0 && row < 9 {
+                classes.push("box-bottom");
+            }
+
+            let text = sq.get().map(|digit| digit.to_string()).unwrap_or_default();
+            write!(out, "<td class=\"{}\">{}</td>", classes.join(" "), text).unwrap();
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set_given((1, 1), 5).unwrap();
+        grid.set_unchecked(1, 9, 5);
+        grid.set((2, 2), 4).unwrap();
+
+        let out = html_highlighted(&grid, &[(2, 2), (3, 3)]);
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows.len(), 11);
+        assert_eq!(rows[0], "<table class=\"sudoku\">");
+        assert!(rows[1].starts_with("<tr><td class=\"given conflict\">5</td><td class=\"empty\"></td>"));
+        assert!(rows[1].ends_with("<td class=\"solved conflict\">5</td></tr>"));
+        assert!(rows[2].contains("<td class=\"solved highlight\">4</td>"));
+        assert!(rows[3].contains("<td class=\"empty highlight box-right box-bottom\"></td>"));
+        assert_eq!(out.matches("<td").count(), 81);
+        assert!(!html(&grid).contains("highlight"));
+    }
+}
@@ -0,0 +1,13 @@
+//! Drawing grids for print, the web and the terminal.
+
+mod ansi;
+mod html;
+#[cfg(feature = "render-png")]
+mod png;
+mod svg;
+
+pub use ansi::ansi;
+pub use html::{html, html_highlighted};
+#[cfg(feature = "render-png")]
+pub use png::png;
+pub use svg::{SvgOptions, svg};
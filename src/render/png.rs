@@ -0,0 +1,126 @@
+use std::sync::{Arc, OnceLock};
+
+use resvg::{tiny_skia, usvg};
+use usvg::fontdb::Database;
+
+use crate::{SudokuError, SudokuGrid};
+
+use super::{SvgOptions, svg};
+
+
+/// Installed families to try for each generic one, first match wins.
+/// fontdb names only Windows and macOS fonts for these, which Linux hosts
+/// rarely have.
+const SANS_SERIF: [&str; 5] = ["Arial", "Helvetica", "DejaVu Sans", "Liberation Sans", "Noto Sans"];
+const SERIF: [&str; 5] = ["Times New Roman", "Times", "DejaVu Serif", "Liberation Serif", "Noto Serif"];
+const MONOSPACE: [&str; 5] = ["Courier New", "Courier", "DejaVu Sans Mono", "Liberation Mono", "Noto Sans Mono"];
+
+
+/// The first of `names` that is installed, or else the family of any
+/// installed face.
+fn installed(database: &Database, names: &[&str]) -> Option<String>
+{
+    let families = || database.faces().flat_map(|face| face.families.iter().map(|(name, _)| name));
+    names.iter()
+        .find(|&&name| families().any(|family| family == name))
+        .map(|name| name.to_string())
+        .or_else(|| families().next().cloned())
+}
+
+
+/// The system fonts, loaded on the first render, with the generic
+/// families pointed at fonts that are installed.
+fn fonts() -> Arc<Database>
+{
+    static FONTS: OnceLock<Arc<Database>> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut database = Database::new();
+        database.load_system_fonts();
+        if let Some(family) = installed(&database, &SANS_SERIF) {
+            database.set_sans_serif_family(family);
+        }
+        if let Some(family) = installed(&database, &SERIF) {
+            database.set_serif_family(family);
+        }
+        if let Some(family) = installed(&database, &MONOSPACE) {
+            database.set_monospace_family(family);
+        }
+        Arc::new(database)
+    }).clone()
+}
+
+
+/// Draw a grid as `render::svg` would and rasterize it to a PNG image,
+/// one pixel per unit of the drawing. Digits use the system fonts, so
+/// pick a `font_family` that the machine has; the generic families work
+/// wherever any font is installed. Fails with `RenderFailed` if there is
+/// text to draw and no fonts at all, rather than leave it out.
+pub fn png(grid: &SudokuGrid, options: &SvgOptions) -> Result<Vec<u8>, SudokuError>
+{
+    let drawing = svg(grid, options);
+    let fontdb = fonts();
+    if fontdb.is_empty() && drawing.contains("<text") {
+        return Err(SudokuError::RenderFailed);
+    }
+    let settings = usvg::Options { fontdb, ..usvg::Options::default() };
+    let tree = usvg::Tree::from_str(&drawing, &settings)
+        .map_err(|_| SudokuError::RenderFailed)?;
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or(SudokuError::RenderFailed)?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|_| SudokuError::RenderFailed)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png()
+    {
+        // An empty grid has no text, so it renders even without fonts.
+        let grid = SudokuGrid::new();
+        let image = png(&grid, &SvgOptions::new().cell_size(20.0)).unwrap();
+        assert!(image.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // The IHDR chunk holds the width and height, big-endian.
+        assert_eq!(&image[16..24], &[0, 0, 0, 200, 0, 0, 0, 200]);
+
+        assert_eq!(png(&grid, &SvgOptions::new().cell_size(0.0).margin(0.0)), Err(SudokuError::RenderFailed));
+    }
+
+    /// The dark pixels of a 20 by 20 cell whose top left corner is at
+    /// `x`, `y`, skipping the grid lines along its edges.
+    fn dark_pixels(image: &[u8], x: u32, y: u32) -> usize
+    {
+        let pixmap = tiny_skia::Pixmap::decode_png(image).unwrap();
+        (y + 3..y + 17)
+            .flat_map(|row| (x + 3..x + 17).map(move |col| (col, row)))
+            .filter(|&(col, row)| pixmap.pixel(col, row).is_some_and(|p| p.alpha() > 128 && p.red() < 128))
+            .count()
+    }
+
+    #[test]
+    fn test_png_draws_digits()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set_given((1, 1), 5).unwrap();
+        grid.set((5, 5), 8).unwrap();
+        let options = SvgOptions::new().cell_size(20.0).margin(10.0);
+        let empty = png(&SudokuGrid::new(), &options).unwrap();
+        assert_eq!(dark_pixels(&empty, 10, 10), 0);
+
+        // A host without fonts can't draw the digits and must say so.
+        if fonts().is_empty() {
+            assert_eq!(png(&grid, &options), Err(SudokuError::RenderFailed));
+            return;
+        }
+        let image = png(&grid, &options).unwrap();
+        assert!(dark_pixels(&image, 10, 10) > 10);
+        assert!(dark_pixels(&image, 90, 90) > 10);
+        assert_eq!(dark_pixels(&image, 30, 10), 0);
+    }
+}
+
@@ -0,0 +1,176 @@
+use std::fmt::Write;
+
+use crate::SudokuGrid;
+
+
+/// Settings for `render::svg`, built up from the defaults:
+///
+/// ```
+/// use bitsudoku::render::SvgOptions;
+///
+/// let options = SvgOptions::new().cell_size(60.0).candidates(true);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    cell_size: f64,
+    margin: f64,
+    candidates: bool,
+    font_family: String
+}
+
+
+impl Default for SvgOptions {
+
+    fn default() -> SvgOptions
+    {
+        SvgOptions {
+            cell_size: 50.0,
+            margin: 10.0,
+            candidates: false,
+            font_family: String::from("sans-serif")
+        }
+    }
+}
+
+
+impl SvgOptions {
+
+    /// 50 unit squares with a 10 unit margin, no candidates and a
+    /// sans-serif font.
+    pub fn new() -> SvgOptions
+    {
+        SvgOptions::default()
+    }
+
+    pub fn cell_size(mut self, size: f64) -> SvgOptions
+    {
+        self.cell_size = size;
+        self
+    }
+
+    /// The space around the grid, on every side.
+    pub fn margin(mut self, margin: f64) -> SvgOptions
+    {
+        self.margin = margin;
+        self
+    }
+
+    /// Draw the candidates of empty squares in small digits. They are
+    /// drawn as the grid holds them, so call `propagate` first on a
+    /// freshly loaded puzzle.
+    pub fn candidates(mut self, candidates: bool) -> SvgOptions
+    {
+        self.candidates = candidates;
+        self
+    }
+
+    pub fn font_family(mut self, family: &str) -> SvgOptions
+    {
+        self.font_family = family.to_string();
+        self
+    }
+
+    /// The width and height of the drawing.
+    pub fn size(&self) -> f64
+    {
+        9.0*self.cell_size + 2.0*self.margin
+    }
+}
+
+
+/// Draw a grid as a standalone SVG document, with givens in bold, other
+/// set squares in a normal weight and, if asked for, candidates in small
+/// digits.
+pub fn svg(grid: &SudokuGrid, options: &SvgOptions) -> String
+{
+    let (cell, margin, size) = (options.cell_size, options.margin, options.size());
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">"#,
+        size = size
+    ).unwrap();
+    writeln!(out, r#"<rect width="{size}" height="{size}" fill="white"/>"#, size = size).unwrap();
+
+    // Thin lines between squares, then thick ones around the boxes.
+    writeln!(out, r#"<g stroke="black" stroke-linecap="square">"#).unwrap();
+    for i in 0..=9 {
+        let width = if i % 3 == 0 { cell / 16.0 } else { cell / 50.0 };
+        let offset = margin + f64::from(i)*cell;
+        writeln!(
+            out,
+            r#"<line x1="{o}" y1="{m}" x2="{o}" y2="{e}" stroke-width="{w}"/><line x1="{m}" y1="{o}" x2="{e}" y2="{o}" stroke-width="{w}"/>"#,
+            o = offset, m = margin, e = size - margin, w = width
+        ).unwrap();
+    }
+    writeln!(out, "</g>").unwrap();
+
+    writeln!(
+        out,
+        r#"<g font-family="{}" text-anchor="middle" fill="black">"#,
+        escape(&options.font_family)
+    ).unwrap();
+    for sq in grid.iter() {
+        let left = margin + f64::from(sq.col() - 1)*cell;
+        let top = margin + f64::from(sq.row() - 1)*cell;
+        if let Ok(digit) = sq.get() {
+            let font = 0.7*cell;
+            let weight = if sq.is_given() { "bold" } else { "normal" };
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-size="{}" font-weight="{}">{}</text>"#,
+                left + 0.5*cell, top + 0.5*cell + 0.35*font, font, weight, digit
+            ).unwrap();
+        } else if options.candidates {
+            let font = 0.25*cell;
+            for digit in (1..=9u8).filter(|&d| sq.is_possible(d)) {
+                let (r, c) = (f64::from((digit - 1) / 3), f64::from((digit - 1) % 3));
+                writeln!(
+                    out,
+                    r#"<text x="{}" y="{}" font-size="{}" fill="dimgray">{}</text>"#,
+                    left + (c + 0.5)*cell/3.0, top + (r + 0.5)*cell/3.0 + 0.35*font, font, digit
+                ).unwrap();
+            }
+        }
+    }
+    writeln!(out, "</g>").unwrap();
+    out.push_str("</svg>\n");
+    out
+}
+
+
+fn escape(text: &str) -> String
+{
+    text.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set_given((1, 1), 5).unwrap();
+        grid.set((1, 2), 3).unwrap();
+
+        let out = svg(&grid, &SvgOptions::new());
+        assert!(out.starts_with("<svg ") && out.ends_with("</svg>\n"));
+        assert!(out.contains(r#"width="470""#));
+        assert!(out.contains(r#"font-weight="bold">5</text>"#));
+        assert!(out.contains(r#"font-weight="normal">3</text>"#));
+        assert_eq!(out.matches("<text").count(), 2);
+        assert_eq!(out.matches("<line").count(), 20);
+
+        grid.propagate().unwrap();
+        let out = svg(&grid, &SvgOptions::new().candidates(true).cell_size(90.0));
+        let candidates: usize = grid.iter()
+            .filter(|sq| !sq.is_set())
+            .map(|sq| usize::from(sq.possibilities_number()))
+            .sum();
+        assert_eq!(out.matches("<text").count(), 2 + candidates);
+        assert!(out.contains(r#"font-size="22.5" fill="dimgray""#));
+    }
+}
@@ -0,0 +1,238 @@
+//! Serde support, enabled with the `serde` feature.
+//!
+//! A `SudokuGrid` serializes to its compact 81 character string, with `.`
+//! for empty squares. The [`full`] module keeps every square together with
+//! its candidate mask, for use with `#[serde(with = "...")]`. Grids
+//! deserialize from either form. Even/odd markings use their 81 character
+//! string too.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::{SerializeSeq, SerializeStruct};
+
+use crate::{SudokuGrid, SudokuSquare};
+use crate::constraint::EvenOdd;
+use crate::square::{DIGIT_MASK, SET_BIT};
+
+
+impl Serialize for SudokuSquare {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut state = serializer.serialize_struct("SudokuSquare", 4)?;
+        state.serialize_field("row", &self.row())?;
+        state.serialize_field("col", &self.col())?;
+        state.serialize_field("value", &self.get().ok())?;
+        state.serialize_field("candidates", &self.digit_bits())?;
+        state.end()
+    }
+}
+
+
+#[derive(Deserialize)]
+#[serde(rename = "SudokuSquare")]
+struct SquareFields {
+    row: u8,
+    col: u8,
+    value: Option<u8>,
+    candidates: u16
+}
+
+
+impl<'de> Deserialize<'de> for SudokuSquare {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SudokuSquare, D::Error>
+    {
+        let fields = SquareFields::deserialize(deserializer)?;
+        if !(1..=9).contains(&fields.row) || !(1..=9).contains(&fields.col) {
+            return Err(de::Error::custom(format!(
+                "invalid position ({}, {})", fields.row, fields.col
+            )));
+        }
+
+        let mut sq = SudokuSquare::new(fields.row, fields.col);
+        match fields.value {
+            Some(digit) if (1..=9).contains(&digit) => sq.set_value_unchecked(digit),
+            Some(digit) => return Err(de::Error::custom(format!("invalid digit {}", digit))),
+            None => {
+                let (position, bits) = sq.raw();
+                let bits = (bits & !(DIGIT_MASK | SET_BIT)) | (fields.candidates & DIGIT_MASK);
+                sq = SudokuSquare::from_raw(position, bits);
+            }
+        }
+        Ok(sq)
+    }
+}
+
+
+impl Serialize for SudokuGrid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.serialize_str(&self.to_line_string())
+    }
+}
+
+
+struct GridVisitor;
+
+impl<'de> Visitor<'de> for GridVisitor {
+    type Value = SudokuGrid;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.write_str("an 81 character puzzle string or a sequence of 81 squares")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<SudokuGrid, E>
+    {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<SudokuGrid, A::Error>
+    {
+        let mut grid = SudokuGrid::new();
+        let mut seen = [false; 81];
+        while let Some(sq) = seq.next_element::<SudokuSquare>()? {
+            let index = sq.pos().index();
+            if seen[index] {
+                return Err(de::Error::custom(format!(
+                    "square ({}, {}) given twice", sq.row(), sq.col()
+                )));
+            }
+            seen[index] = true;
+            grid.0[index] = sq;
+        }
+        grid.sync_boards();
+        match seen.iter().filter(|&&s| s).count() {
+            81 => Ok(grid),
+            n => Err(de::Error::invalid_length(n, &self))
+        }
+    }
+}
+
+
+impl<'de> Deserialize<'de> for SudokuGrid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SudokuGrid, D::Error>
+    {
+        deserializer.deserialize_str(GridVisitor)
+    }
+}
+
+
+impl Serialize for EvenOdd {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        serializer.collect_str(self)
+    }
+}
+
+
+impl<'de> Deserialize<'de> for EvenOdd {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<EvenOdd, D::Error>
+    {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+
+/// Serialize a grid as its 81 squares, keeping the candidate masks.
+pub mod full {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(grid: &SudokuGrid, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut seq = serializer.serialize_seq(Some(81))?;
+        for sq in grid.0.iter() {
+            seq.serialize_element(sq)?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SudokuGrid, D::Error>
+    {
+        deserializer.deserialize_seq(GridVisitor)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Full(#[serde(with = "full")] SudokuGrid);
+
+    #[test]
+    fn test_compact_round_trip()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 5).unwrap();
+        grid.set((9, 9), 2).unwrap();
+
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json.len(), 83);
+        assert!(json.starts_with("\"5....."));
+
+        let back: SudokuGrid = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.0[0].get().unwrap(), 5);
+        assert_eq!(back.0[80].get().unwrap(), 2);
+    }
+
+    /// A deserializer that, like bincode's, can't say what type comes
+    /// next and so only answers requests for a string.
+    struct StrOnly<'a>(&'a str);
+
+    impl<'de, 'a> Deserializer<'de> for StrOnly<'a> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error>
+        {
+            Err(de::Error::custom("the format is not self-describing"))
+        }
+
+        fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error>
+        {
+            visitor.visit_str(self.0)
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map
+            struct enum identifier ignored_any
+        }
+    }
+
+    #[test]
+    fn test_compact_needs_no_self_description()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((4, 6), 3).unwrap();
+        let line = grid.to_line_string();
+        assert_eq!(SudokuGrid::deserialize(StrOnly(&line)), Ok(grid));
+    }
+
+    #[test]
+    fn test_full_round_trip_keeps_candidates()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 5).unwrap();
+        grid.propagate().unwrap();
+
+        let json = serde_json::to_string(&Full(grid)).unwrap();
+        let back: Full = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.0.0[0].get().unwrap(), 5);
+        assert!(!back.0.0[1].is_possible(5));
+        assert!(back.0.0[1].is_possible(4));
+        assert_eq!(back.0.0[40].get_box(), 5);
+    }
+
+    #[test]
+    fn test_even_odd_round_trip()
+    {
+        let markings: EvenOdd = format!("{:.<81}", "EO").parse().unwrap();
+        let json = serde_json::to_string(&markings).unwrap();
+        assert_eq!(json, format!("\"{:.<81}\"", "EO"));
+        assert_eq!(serde_json::from_str::<EvenOdd>(&json).unwrap(), markings);
+        assert!(serde_json::from_str::<EvenOdd>("\"EO\"").is_err());
+    }
+}
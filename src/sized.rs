@@ -0,0 +1,478 @@
+//! Grids of other sizes, with boxes of `R` rows by `C` columns.
+//!
+//! A side of the grid is `R * C` squares, so 2×2 boxes give the 4×4
+//! children's puzzle, 2×3 boxes the 6×6 one and 4×4 boxes the 16×16
+//! "super" sudoku. Candidates are kept as `u32` masks, enough for sides up
+//! to 32. Digits above 9 are written `A`, `B` and so on, so 16 is `G`, and
+//! `.` or `0` marks an empty square.
+//!
+//! A grid in [`Mode::Latin`] drops the box constraint, leaving a plain
+//! Latin square, so any side `R * C` can be used, such as `SizedGrid<5, 1>`
+//! for the 5×5 base of a Futoshiki.
+//!
+//! Stable Rust can't size an array by `R * C * R * C`, so the squares live
+//! in a `Vec` and hold plain digits; the 9×9 `SudokuGrid` remains the type
+//! with candidates, techniques and the rest of the crate's machinery.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::SudokuError;
+
+
+/// Which units a grid's digits must not repeat in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Mode {
+    /// Rows, columns and boxes.
+    #[default]
+    Sudoku,
+    /// Rows and columns only.
+    Latin
+}
+
+
+/// A grid with boxes of `R` rows by `C` columns.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SizedGrid<const R: usize, const C: usize> {
+    /// Digits in row-major order, 0 for empty.
+    cells: Vec<u8>,
+    mode: Mode
+}
+
+/// The 4×4 grid with 2×2 boxes.
+pub type Grid4 = SizedGrid<2, 2>;
+/// The 6×6 grid with boxes two rows high and three columns wide.
+pub type Grid6 = SizedGrid<2, 3>;
+/// The classic 9×9 grid, without candidates.
+pub type Grid9 = SizedGrid<3, 3>;
+/// The 16×16 grid with 4×4 boxes.
+pub type Grid16 = SizedGrid<4, 4>;
+
+
+fn digit_char(digit: u8) -> char
+{
+    match digit {
+        0 => '.',
+        1..=9 => (b'0' + digit) as char,
+        _ => (b'A' + digit - 10) as char
+    }
+}
+
+fn char_digit(ch: char) -> Option<u8>
+{
+    match ch {
+        '.' | '0' => Some(0),
+        '1'..='9' => Some(ch as u8 - b'0'),
+        'A'..='Z' => Some(ch as u8 - b'A' + 10),
+        'a'..='z' => Some(ch as u8 - b'a' + 10),
+        _ => None
+    }
+}
+
+
+impl<const R: usize, const C: usize> Default for SizedGrid<R, C> {
+    fn default() -> Self
+    {
+        SizedGrid::new()
+    }
+}
+
+
+impl<const R: usize, const C: usize> SizedGrid<R, C> {
+
+    /// The number of squares along a side, and the largest digit.
+    pub const SIDE: usize = R * C;
+
+    pub fn new() -> Self
+    {
+        assert!(Self::SIDE <= 32, "Grids are limited to 32 digits.");
+        SizedGrid { cells: vec![0; Self::SIDE * Self::SIDE], mode: Mode::Sudoku }
+    }
+
+    /// An empty grid checked and solved under `mode`.
+    pub fn with_mode(mode: Mode) -> Self
+    {
+        SizedGrid { mode, ..SizedGrid::new() }
+    }
+
+    pub fn mode(&self) -> Mode
+    {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: Mode)
+    {
+        self.mode = mode;
+    }
+
+    fn search(&self) -> Option<Search>
+    {
+        Search::new(&self.cells, R, C, self.mode == Mode::Sudoku)
+    }
+
+    fn index(row: u8, col: u8) -> Result<usize, SudokuError>
+    {
+        let side = Self::SIDE;
+        if row == 0 || col == 0 || usize::from(row) > side || usize::from(col) > side {
+            return Err(SudokuError::InvalidPosition { row, col });
+        }
+        Ok(side * usize::from(row - 1) + usize::from(col - 1))
+    }
+
+    fn box_of(index: usize) -> usize
+    {
+        let (r, c) = (index / Self::SIDE, index % Self::SIDE);
+        (r / R) * R + c / C
+    }
+
+    /// The digit at `row`, `col`, numbered from 1, or `None` if empty.
+    pub fn get(&self, row: u8, col: u8) -> Result<Option<u8>, SudokuError>
+    {
+        let digit = self.cells[Self::index(row, col)?];
+        Ok(if digit == 0 { None } else { Some(digit) })
+    }
+
+    pub fn set(&mut self, row: u8, col: u8, digit: u8) -> Result<(), SudokuError>
+    {
+        let index = Self::index(row, col)?;
+        if digit == 0 || usize::from(digit) > Self::SIDE {
+            return Err(SudokuError::InvalidDigit { digit: u16::from(digit) });
+        }
+        self.cells[index] = digit;
+        Ok(())
+    }
+
+    pub fn clear(&mut self, row: u8, col: u8) -> Result<(), SudokuError>
+    {
+        let index = Self::index(row, col)?;
+        self.cells[index] = 0;
+        Ok(())
+    }
+
+    /// The digits an empty square could take given its row, column and,
+    /// outside Latin mode, box, or 0 for a set square.
+    pub fn candidates(&self, row: u8, col: u8) -> Result<u32, SudokuError>
+    {
+        let index = Self::index(row, col)?;
+        if self.cells[index] != 0 {
+            return Ok(0);
+        }
+        let side = Self::SIDE;
+        let all = if side == 32 { u32::MAX } else { (1 << side) - 1 };
+        let (r, c, b) = (index / side, index % side, Self::box_of(index));
+        let seen = self.cells.iter().enumerate()
+            .filter(|&(i, &d)| {
+                d != 0 && (i / side == r || i % side == c
+                    || (self.mode == Mode::Sudoku && Self::box_of(i) == b))
+            })
+            .fold(0, |mask, (_, &d)| mask | 1 << (d - 1));
+        Ok(all & !seen)
+    }
+
+    pub fn filled_count(&self) -> usize
+    {
+        self.cells.iter().filter(|&&d| d != 0).count()
+    }
+
+    pub fn is_complete(&self) -> bool
+    {
+        self.cells.iter().all(|&d| d != 0)
+    }
+
+    /// Whether no row, column or, outside Latin mode, box holds a digit
+    /// twice.
+    pub fn is_consistent(&self) -> bool
+    {
+        self.search().is_some()
+    }
+
+    pub fn is_solved(&self) -> bool
+    {
+        self.is_complete() && self.is_consistent()
+    }
+
+    /// Whether the grid is solved under its mode, like `SudokuGrid::check`.
+    pub fn check(&self) -> Result<bool, SudokuError>
+    {
+        Ok(self.is_solved())
+    }
+
+    /// Find a solution by backtracking, keeping the set squares.
+    pub fn solve(&self) -> Result<Self, SudokuError>
+    {
+        let mut search = self.search().ok_or(SudokuError::NoSolution)?;
+        let mut solution = None;
+        search.run(&mut |cells| {
+            solution = Some(cells.to_vec());
+            false
+        });
+        solution.map(|cells| SizedGrid { cells, mode: self.mode }).ok_or(SudokuError::NoSolution)
+    }
+
+    /// Count solutions, stopping once `limit` have been found.
+    pub fn count_solutions(&self, limit: usize) -> usize
+    {
+        let mut count = 0;
+        if let (true, Some(mut search)) = (limit > 0, self.search()) {
+            search.run(&mut |_| {
+                count += 1;
+                count < limit
+            });
+        }
+        count
+    }
+
+    pub fn has_unique_solution(&self) -> bool
+    {
+        self.count_solutions(2) == 1
+    }
+
+    /// The grid as one line of `SIDE * SIDE` characters.
+    pub fn to_line_string(&self) -> String
+    {
+        self.cells.iter().map(|&d| digit_char(d)).collect()
+    }
+}
+
+
+impl<const R: usize, const C: usize> fmt::Display for SizedGrid<R, C> {
+
+    /// Draw the grid with box separators, like `SudokuGrid`, or as a
+    /// single box in Latin mode.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let side = Self::SIDE;
+        let (box_rows, box_cols) = match self.mode {
+            Mode::Sudoku => (R, C),
+            Mode::Latin => (side, side)
+        };
+        let separator = format!("+{}", format!("{}+", "-".repeat(2*box_cols + 1)).repeat(side / box_cols));
+        for (r, row) in self.cells.chunks(side).enumerate() {
+            if r % box_rows == 0 {
+                writeln!(f, "{}", separator)?;
+            }
+            for (c, &digit) in row.iter().enumerate() {
+                if c % box_cols == 0 {
+                    f.write_str("| ")?;
+                }
+                write!(f, "{} ", digit_char(digit))?;
+            }
+            writeln!(f, "|")?;
+        }
+        write!(f, "{}", separator)
+    }
+}
+
+
+impl<const R: usize, const C: usize> FromStr for SizedGrid<R, C> {
+    type Err = SudokuError;
+
+    /// Parse a puzzle of `SIDE * SIDE` characters in row-major order.
+    fn from_str(s: &str) -> Result<Self, SudokuError>
+    {
+        let s = s.trim();
+        let length = s.chars().count();
+        if length != Self::SIDE * Self::SIDE {
+            return Err(SudokuError::InvalidLength { length });
+        }
+
+        let mut grid = SizedGrid::new();
+        for (position, character) in s.chars().enumerate() {
+            match char_digit(character) {
+                Some(digit) if usize::from(digit) <= Self::SIDE => grid.cells[position] = digit,
+                _ => return Err(SudokuError::InvalidCharacter { position, character })
+            }
+        }
+        Ok(grid)
+    }
+}
+
+
+/// Backtracking over row, column and box masks, like the 9×9 search.
+/// Without boxes the box masks stay empty.
+struct Search {
+    cells: Vec<u8>,
+    rows: Vec<u32>,
+    cols: Vec<u32>,
+    boxes: Vec<u32>,
+    side: usize,
+    box_rows: usize,
+    box_cols: usize,
+    use_boxes: bool
+}
+
+
+impl Search {
+
+    fn new(cells: &[u8], box_rows: usize, box_cols: usize, use_boxes: bool) -> Option<Search>
+    {
+        let side = box_rows * box_cols;
+        let mut search = Search {
+            cells: vec![0; cells.len()],
+            rows: vec![0; side],
+            cols: vec![0; side],
+            boxes: vec![0; side],
+            side,
+            box_rows,
+            box_cols,
+            use_boxes
+        };
+        for (i, &digit) in cells.iter().enumerate().filter(|(_, &d)| d != 0) {
+            if search.candidates(i) & (1 << (digit - 1)) == 0 {
+                return None;
+            }
+            search.assign(i, digit);
+        }
+        Some(search)
+    }
+
+    fn units(&self, index: usize) -> (usize, usize, usize)
+    {
+        let (r, c) = (index / self.side, index % self.side);
+        (r, c, (r / self.box_rows) * self.box_rows + c / self.box_cols)
+    }
+
+    fn candidates(&self, index: usize) -> u32
+    {
+        let (r, c, b) = self.units(index);
+        let all = if self.side == 32 { u32::MAX } else { (1 << self.side) - 1 };
+        all & !(self.rows[r] | self.cols[c] | self.boxes[b])
+    }
+
+    fn assign(&mut self, index: usize, digit: u8)
+    {
+        let (r, c, b) = self.units(index);
+        let bit = 1 << (digit - 1);
+        self.cells[index] = digit;
+        self.rows[r] |= bit;
+        self.cols[c] |= bit;
+        if self.use_boxes {
+            self.boxes[b] |= bit;
+        }
+    }
+
+    fn unassign(&mut self, index: usize)
+    {
+        let (r, c, b) = self.units(index);
+        let bit = !(1 << (self.cells[index] - 1));
+        self.cells[index] = 0;
+        self.rows[r] &= bit;
+        self.cols[c] &= bit;
+        if self.use_boxes {
+            self.boxes[b] &= bit;
+        }
+    }
+
+    /// Walk the solutions, calling `found` for each until it returns
+    /// false. Returns false if stopped early.
+    fn run<F: FnMut(&[u8]) -> bool>(&mut self, found: &mut F) -> bool
+    {
+        let mut best: Option<(usize, u32)> = None;
+        for i in (0..self.cells.len()).filter(|&i| self.cells[i] == 0) {
+            let mask = self.candidates(i);
+            if best.is_none_or(|(_, b)| mask.count_ones() < b.count_ones()) {
+                best = Some((i, mask));
+                if mask.count_ones() <= 1 {
+                    break;
+                }
+            }
+        }
+
+        let (index, mut mask) = match best {
+            None => return found(&self.cells),
+            Some(choice) => choice
+        };
+        while mask != 0 {
+            let digit = mask.trailing_zeros() as u8 + 1;
+            mask &= mask - 1;
+            self.assign(index, digit);
+            if !self.run(found) {
+                return false;
+            }
+            self.unassign(index);
+        }
+        true
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_four_by_four()
+    {
+        let grid: Grid4 = "1...\n..2.\n.3..\n...4".replace('\n', "").parse().unwrap();
+        assert_eq!(grid.get(2, 3), Ok(Some(2)));
+        let solution = grid.solve().unwrap();
+        assert!(solution.is_solved());
+        assert_eq!(solution.get(1, 1), Ok(Some(1)));
+        assert!(grid.has_unique_solution());
+        assert!(Grid4::new().count_solutions(1000) > 1);
+        assert_eq!(grid.candidates(1, 2), Ok(0b1010));
+    }
+
+    #[test]
+    fn test_six_by_six_boxes()
+    {
+        let mut grid = Grid6::new();
+        grid.set(1, 1, 6).unwrap();
+        grid.set(2, 3, 6).unwrap();
+        assert!(!grid.is_consistent());
+        grid.clear(2, 3).unwrap();
+        grid.set(2, 4, 6).unwrap();
+        assert!(grid.is_consistent());
+        assert!(grid.set(1, 2, 7).is_err());
+        assert!(grid.solve().unwrap().is_solved());
+    }
+
+    #[test]
+    fn test_sixteen_by_sixteen()
+    {
+        let solution = Grid16::new().solve().unwrap();
+        assert!(solution.is_solved());
+        let line = solution.to_line_string();
+        assert!(line.contains('G'));
+
+        let mut puzzle: Grid16 = line.parse().unwrap();
+        puzzle.clear(16, 16).unwrap();
+        puzzle.clear(1, 1).unwrap();
+        assert!(puzzle.has_unique_solution());
+        assert_eq!(puzzle.solve(), Ok(solution));
+    }
+
+    #[test]
+    fn test_latin_mode()
+    {
+        let mut grid = Grid4::new();
+        grid.set(1, 1, 1).unwrap();
+        grid.set(2, 2, 1).unwrap();
+        assert!(!grid.is_consistent());
+        grid.set_mode(Mode::Latin);
+        assert!(grid.is_consistent());
+        assert_eq!(grid.candidates(1, 2), Ok(0b1110));
+        let solution = grid.solve().unwrap();
+        assert_eq!(solution.check(), Ok(true));
+        assert_eq!(solution.mode(), Mode::Latin);
+
+        // Cyclic Latin squares break the box rule but are valid in Latin mode.
+        let mut square: Grid4 = "1234234134124123".parse().unwrap();
+        assert_eq!(square.check(), Ok(false));
+        square.set_mode(Mode::Latin);
+        assert_eq!(square.check(), Ok(true));
+
+        assert_eq!(Grid4::with_mode(Mode::Latin).count_solutions(usize::MAX), 576);
+        assert_eq!(Grid4::new().count_solutions(usize::MAX), 288);
+        assert!(SizedGrid::<5, 1>::with_mode(Mode::Latin).solve().unwrap().is_solved());
+    }
+
+    #[test]
+    fn test_display()
+    {
+        let grid: Grid4 = "1.......2.......".parse().unwrap();
+        let drawn = grid.to_string();
+        assert!(drawn.starts_with("+-----+-----+\n| 1 . | . . |"));
+        assert_eq!(drawn.lines().count(), 7);
+    }
+}
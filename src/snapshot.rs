@@ -0,0 +1,66 @@
+use crate::{SudokuGrid, SudokuSquare};
+
+
+/// The squares of a grid, digits, candidates and given flags, as 81
+/// `u16`s. Taking one and restoring it is a plain copy, so a search can
+/// branch from a grid and return to it without cloning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GridState([u16; 81]);
+
+
+impl GridState {
+
+    /// The state of each square in row-major order.
+    pub fn bits(&self) -> &[u16; 81]
+    {
+        &self.0
+    }
+}
+
+
+impl SudokuGrid {
+
+    /// Record the state of every square, to be put back with `restore`.
+    pub fn snapshot(&self) -> GridState
+    {
+        GridState(std::array::from_fn(|i| self.0[i].raw().1))
+    }
+
+    /// Put every square back as it was when `state` was taken.
+    pub fn restore(&mut self, state: &GridState)
+    {
+        for (sq, &bits) in self.0.iter_mut().zip(state.0.iter()) {
+            *sq = SudokuSquare::from_raw(sq.raw().0, bits);
+        }
+        self.sync_boards();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_undoes_changes()
+    {
+        let mut grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        grid.mark_givens();
+        let original = grid.clone();
+        let state = grid.snapshot();
+
+        grid.set((1, 3), 4).unwrap();
+        grid.propagate().unwrap();
+        assert_ne!(grid.snapshot(), state);
+
+        grid.restore(&state);
+        assert_eq!(grid.snapshot(), state);
+        assert_eq!(grid, original);
+        assert!(grid.iter().zip(original.iter()).all(|(a, b)| a.digit_bits() == b.digit_bits()));
+        assert!(grid[crate::Pos::from_index(0)].is_given());
+        assert_eq!(grid.digit_board(5), original.digit_board(5));
+    }
+}
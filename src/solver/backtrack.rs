@@ -0,0 +1,383 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::{SudokuGrid, SudokuError};
+
+use super::config::{Budget, Heuristic};
+
+
+/// Depth-first search over plain digit values (0 for empty), keeping the
+/// used digits of each row, column and box as bitmasks.
+pub(crate) struct Search {
+    values: [u8; 81],
+    rows: [u16; 9],
+    cols: [u16; 9],
+    boxes: [u16; 9]
+}
+
+
+#[inline(always)]
+fn box_of(index: usize) -> usize
+{
+    3*(index / 27) + (index % 9) / 3
+}
+
+
+impl Search {
+
+    /// Set up a search from digit values, or `None` if two givens clash.
+    pub(crate) fn new(values: &[u8; 81]) -> Option<Search>
+    {
+        let mut search = Search {
+            values: [0; 81],
+            rows: [0; 9],
+            cols: [0; 9],
+            boxes: [0; 9]
+        };
+        for (i, &v) in values.iter().enumerate() {
+            if v != 0 {
+                if search.candidates(i) & (0x0001 << (v - 1)) == 0 {
+                    return None;
+                }
+                search.assign(i, v);
+            }
+        }
+        Some(search)
+    }
+
+    pub(crate) fn from_grid(grid: &SudokuGrid) -> Option<Search>
+    {
+        Search::new(&grid_values(grid))
+    }
+
+    fn candidates(&self, index: usize) -> u16
+    {
+        !(self.rows[index / 9] | self.cols[index % 9] | self.boxes[box_of(index)]) & 0x01FF
+    }
+
+    fn assign(&mut self, index: usize, digit: u8)
+    {
+        let bit = 0x0001 << (digit - 1);
+        self.values[index] = digit;
+        self.rows[index / 9] |= bit;
+        self.cols[index % 9] |= bit;
+        self.boxes[box_of(index)] |= bit;
+    }
+
+    fn unassign(&mut self, index: usize)
+    {
+        let bit = !(0x0001 << (self.values[index] - 1));
+        self.values[index] = 0;
+        self.rows[index / 9] &= bit;
+        self.cols[index % 9] &= bit;
+        self.boxes[box_of(index)] &= bit;
+    }
+
+    /// The empty square with the fewest candidates, `None` when full.
+    fn choose(&self) -> Option<(usize, u16)>
+    {
+        let mut best: Option<(usize, u16)> = None;
+        for i in (0..81).filter(|&i| self.values[i] == 0) {
+            let mask = self.candidates(i);
+            if best.is_none_or(|(_, b)| mask.count_ones() < b.count_ones()) {
+                best = Some((i, mask));
+                if mask.count_ones() <= 1 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    /// The square to guess in next under `heuristic`, with ties between
+    /// squares broken by `rng` when there is one.
+    fn choose_by(&self, heuristic: Heuristic, rng: Option<&mut StdRng>) -> Option<(usize, u16)>
+    {
+        match (heuristic, rng) {
+            (Heuristic::FirstEmpty, _) => (0..81)
+                .find(|&i| self.values[i] == 0)
+                .map(|i| (i, self.candidates(i))),
+            (Heuristic::MinimumRemaining, None) => self.choose(),
+            (Heuristic::MinimumRemaining, Some(rng)) => {
+                let fewest = (0..81)
+                    .filter(|&i| self.values[i] == 0)
+                    .map(|i| self.candidates(i).count_ones())
+                    .min()?;
+                let tied: Vec<usize> = (0..81)
+                    .filter(|&i| self.values[i] == 0 && self.candidates(i).count_ones() == fewest)
+                    .collect();
+                tied.choose(rng).map(|&i| (i, self.candidates(i)))
+            }
+        }
+    }
+
+    /// Search for one solution under the given settings, leaving the
+    /// search holding it. Returns false when there is none.
+    pub(crate) fn find(
+        &mut self,
+        heuristic: Heuristic,
+        rng: &mut Option<StdRng>,
+        budget: &mut Budget
+    ) -> Result<bool, SudokuError>
+    {
+        Ok(!self.walk(heuristic, rng, budget, 0, &mut |_| false)?)
+    }
+
+    /// Like `run`, under the given settings and within `budget`.
+    pub(crate) fn walk<F>(
+        &mut self,
+        heuristic: Heuristic,
+        rng: &mut Option<StdRng>,
+        budget: &mut Budget,
+        depth: usize,
+        found: &mut F
+    ) -> Result<bool, SudokuError>
+    where
+        F: FnMut(&[u8; 81]) -> bool
+    {
+        budget.spend()?;
+        let (index, mask) = match self.choose_by(heuristic, rng.as_mut()) {
+            None => return Ok(found(&self.values)),
+            Some(choice) => choice
+        };
+
+        let mut digits = mask_digits(mask);
+        if let Some(rng) = rng.as_mut() {
+            digits.shuffle(rng);
+        }
+        for digit in digits {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(depth, row = index / 9 + 1, col = index % 9 + 1, digit, "branch");
+            self.assign(index, digit);
+            budget.place(mask.count_ones(), depth + 1);
+            if !self.walk(heuristic, rng, budget, depth + 1, found)? {
+                return Ok(false);
+            }
+            self.unassign(index);
+            budget.backtrack();
+        }
+        Ok(true)
+    }
+
+    pub(crate) fn values(&self) -> &[u8; 81]
+    {
+        &self.values
+    }
+
+    /// Walk the solutions, trying digits in the order given by `order` and
+    /// calling `found` for each one. Stops when `found` returns false, in
+    /// which case the search is left holding that solution.
+    pub(crate) fn run<O, F>(&mut self, order: &mut O, found: &mut F) -> bool
+    where
+        O: FnMut(u16) -> Vec<u8>,
+        F: FnMut(&[u8; 81]) -> bool
+    {
+        let (index, mask) = match self.choose() {
+            None => return found(&self.values),
+            Some(choice) => choice
+        };
+
+        for digit in order(mask) {
+            self.assign(index, digit);
+            if !self.run(order, found) {
+                return false;
+            }
+            self.unassign(index);
+        }
+        true
+    }
+
+    /// Count solutions, stopping once `limit` have been found.
+    pub(crate) fn count(&mut self, limit: usize) -> usize
+    {
+        let mut count = 0;
+        if limit > 0 {
+            self.run(&mut mask_digits, &mut |_| {
+                count += 1;
+                count < limit
+            });
+        }
+        count
+    }
+}
+
+
+pub(crate) fn mask_digits(mask: u16) -> Vec<u8>
+{
+    (1..=9).filter(|d| mask & (0x0001 << (d - 1)) != 0).collect()
+}
+
+/// Lazily walks the solutions of a grid by backtracking, using an
+/// explicit stack so each call to `next` resumes where the last stopped.
+pub struct Solutions {
+    base: SudokuGrid,
+    search: Option<Search>,
+    stack: Vec<(usize, u16)>,
+    started: bool
+}
+
+
+impl Solutions {
+
+    pub(crate) fn new(grid: &SudokuGrid) -> Solutions
+    {
+        Solutions {
+            base: grid.clone(),
+            search: Search::from_grid(grid),
+            stack: Vec::new(),
+            started: false
+        }
+    }
+
+    fn solution(&self, values: &[u8; 81]) -> SudokuGrid
+    {
+        fill(&self.base, values)
+    }
+}
+
+
+impl Iterator for Solutions {
+    type Item = SudokuGrid;
+
+    fn next(&mut self) -> Option<SudokuGrid>
+    {
+        let mut search = self.search.take()?;
+
+        if !self.started {
+            self.started = true;
+            match search.choose() {
+                None => return Some(self.solution(&search.values)),
+                Some(choice) => self.stack.push(choice)
+            }
+        }
+
+        while let Some(top) = self.stack.last_mut() {
+            let (index, mask) = *top;
+            if search.values[index] != 0 {
+                search.unassign(index);
+            }
+            if mask == 0 {
+                self.stack.pop();
+                continue;
+            }
+
+            top.1 &= mask - 1;
+            search.assign(index, mask.trailing_zeros() as u8 + 1);
+            match search.choose() {
+                None => {
+                    let grid = self.solution(&search.values);
+                    self.search = Some(search);
+                    return Some(grid);
+                },
+                Some(choice) => self.stack.push(choice)
+            }
+        }
+        None
+    }
+}
+
+
+pub(crate) fn is_unique(values: &[u8; 81]) -> bool
+{
+    Search::new(values).is_some_and(|mut s| s.count(2) == 1)
+}
+
+/// Copy `base`, filling its empty squares from `values`. Set squares keep
+/// their state, givens included.
+pub(crate) fn fill(base: &SudokuGrid, values: &[u8; 81]) -> SudokuGrid
+{
+    let mut grid = base.clone();
+    for (i, &v) in values.iter().enumerate() {
+        if !grid.0[i].is_set() {
+            grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, v);
+        }
+    }
+    grid
+}
+
+/// The digits of the set squares, 0 for the rest.
+pub(crate) fn grid_values(grid: &SudokuGrid) -> [u8; 81]
+{
+    let mut values = [0; 81];
+    for (v, sq) in values.iter_mut().zip(grid.0.iter()) {
+        *v = sq.get().unwrap_or(0);
+    }
+    values
+}
+
+/// A complete, valid grid of digits chosen at random.
+pub(crate) fn random_solution<R: Rng + ?Sized>(rng: &mut R) -> [u8; 81]
+{
+    let mut search = Search::new(&[0; 81]).unwrap();
+    let mut order = |mask: u16| {
+        let mut digits = mask_digits(mask);
+        digits.shuffle(rng);
+        digits
+    };
+    search.run(&mut order, &mut |_| false);
+    search.values
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_empty_grid_stops_at_limit()
+    {
+        let mut search = Search::new(&[0; 81]).unwrap();
+        assert_eq!(search.count(5), 5);
+    }
+
+    #[test]
+    fn test_solutions_iterator()
+    {
+        let mut grid = SudokuGrid::new();
+        for col in 1..=9 {
+            grid.set((1, col), col).unwrap();
+        }
+        let first: Vec<SudokuGrid> = Solutions::new(&grid).take(3).collect();
+        assert_eq!(first.len(), 3);
+        assert!(first.iter().all(|g| g.check().unwrap()));
+        assert_ne!(first[0], first[1]);
+        assert_ne!(first[1], first[2]);
+
+        let full = first[0].clone();
+        assert_eq!(Solutions::new(&full).count(), 1);
+
+        grid.set((2, 1), 1).unwrap();
+        assert_eq!(Solutions::new(&grid).count(), 0);
+    }
+
+    #[test]
+    fn test_solutions_match_count()
+    {
+        let grid: SudokuGrid = concat!(
+            "............................59761423.2685379171392485696",
+            "1537284287419635345286179"
+        ).parse().unwrap();
+        let expected = grid.count_solutions(1000);
+        assert_eq!(expected, 144);
+        assert_eq!(Solutions::new(&grid).count(), expected);
+    }
+
+    #[test]
+    fn test_conflicting_givens()
+    {
+        let mut values = [0; 81];
+        values[0] = 3;
+        values[8] = 3;
+        assert!(Search::new(&values).is_none());
+    }
+
+    #[test]
+    fn test_random_solution_is_valid()
+    {
+        let values = random_solution(&mut rand::thread_rng());
+        let mut search = Search::new(&values).unwrap();
+        assert!(values.iter().all(|&v| v != 0));
+        assert_eq!(search.count(2), 1);
+    }
+}
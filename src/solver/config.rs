@@ -0,0 +1,506 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::{SudokuGrid, SudokuError};
+use crate::constraint::ConstraintSet;
+use crate::techniques::Technique;
+
+use super::{LogicalSolver, SolverBackend, backtrack, constrained, dlx};
+
+
+/// How the backtracking search picks the next square to guess in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Heuristic {
+    /// The empty square with the fewest candidates.
+    #[default]
+    MinimumRemaining,
+    /// The first empty square in row-major order.
+    FirstEmpty
+}
+
+
+/// A flag shared between a running solve and whoever may want to stop it,
+/// for instance a GUI thread. Clones refer to the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+
+impl CancelToken {
+
+    pub fn new() -> CancelToken
+    {
+        CancelToken::default()
+    }
+
+    /// Ask every search holding this token to stop. Searches notice within
+    /// a few dozen nodes and fail with `SudokuError::Cancelled`.
+    pub fn cancel(&self)
+    {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool
+    {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+
+impl PartialEq for CancelToken {
+
+    fn eq(&self, other: &CancelToken) -> bool
+    {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+
+impl Eq for CancelToken {}
+
+
+/// Settings for `SudokuGrid::solve_with`, built up from the defaults:
+///
+/// ```
+/// use std::time::Duration;
+/// use bitsudoku::solver::{Heuristic, SolverConfig};
+///
+/// let config = SolverConfig::new()
+///     .heuristic(Heuristic::FirstEmpty)
+///     .node_limit(100_000)
+///     .time_limit(Duration::from_millis(50))
+///     .seed(7);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SolverConfig {
+    backend: SolverBackend,
+    heuristic: Heuristic,
+    techniques: Vec<Technique>,
+    node_limit: Option<u64>,
+    time_limit: Option<Duration>,
+    seed: Option<u64>,
+    cancel: Option<CancelToken>,
+    constraints: Option<ConstraintSet>
+}
+
+
+impl SolverConfig {
+
+    /// Plain backtracking with no logical pass, no limits and no
+    /// randomness.
+    pub fn new() -> SolverConfig
+    {
+        SolverConfig::default()
+    }
+
+    pub fn backend(mut self, backend: SolverBackend) -> SolverConfig
+    {
+        self.backend = backend;
+        self
+    }
+
+    /// The square choice for backtracking. Dancing links always takes
+    /// the constraint with the fewest options.
+    pub fn heuristic(mut self, heuristic: Heuristic) -> SolverConfig
+    {
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Apply these techniques before searching, filling what they can.
+    pub fn techniques(mut self, techniques: &[Technique]) -> SolverConfig
+    {
+        self.techniques = techniques.to_vec();
+        self
+    }
+
+    /// Give up with `LimitExceeded` after visiting this many nodes.
+    pub fn node_limit(mut self, nodes: u64) -> SolverConfig
+    {
+        self.node_limit = Some(nodes);
+        self
+    }
+
+    /// Give up with `LimitExceeded` once the search has run this long.
+    pub fn time_limit(mut self, limit: Duration) -> SolverConfig
+    {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Break ties between squares and order the guesses at random, from
+    /// this seed. Backtracking only.
+    pub fn seed(mut self, seed: u64) -> SolverConfig
+    {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Stop with `Cancelled` once `token` is cancelled.
+    pub fn cancel_token(mut self, token: CancelToken) -> SolverConfig
+    {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Solve under these rules instead of plain rows, columns and boxes.
+    /// The search then propagates through the constraints and guesses
+    /// under the configured heuristic whatever the backend, and the
+    /// techniques, which assume the standard rules, are skipped.
+    pub fn constraints(mut self, constraints: ConstraintSet) -> SolverConfig
+    {
+        self.constraints = Some(constraints);
+        self
+    }
+
+    pub(crate) fn budget(&self) -> Budget
+    {
+        Budget {
+            max_nodes: self.node_limit,
+            deadline: self.time_limit.map(|limit| Instant::now() + limit),
+            cancel: self.cancel.clone(),
+            nodes: 0,
+            stats: SolveStats::default()
+        }
+    }
+}
+
+
+/// What a solve took. Placements in squares with a single option left
+/// count as propagations, the rest as guesses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    pub guesses: u64,
+    pub backtracks: u64,
+    pub propagations: u64,
+    /// The most placements the search held at once.
+    pub max_depth: usize,
+    pub duration: Duration
+}
+
+
+/// The node and time limits of a running search, and its statistics.
+pub(crate) struct Budget {
+    max_nodes: Option<u64>,
+    deadline: Option<Instant>,
+    cancel: Option<CancelToken>,
+    pub(crate) nodes: u64,
+    pub(crate) stats: SolveStats
+}
+
+
+impl Budget {
+
+    pub(crate) fn unlimited() -> Budget
+    {
+        Budget {
+            max_nodes: None,
+            deadline: None,
+            cancel: None,
+            nodes: 0,
+            stats: SolveStats::default()
+        }
+    }
+
+    /// Record a placement chosen from `options` possibilities, bringing
+    /// the search to `depth` placements.
+    pub(crate) fn place(&mut self, options: u32, depth: usize)
+    {
+        if options > 1 {
+            self.stats.guesses += 1;
+        } else {
+            self.stats.propagations += 1;
+        }
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+    }
+
+    pub(crate) fn backtrack(&mut self)
+    {
+        self.stats.backtracks += 1;
+    }
+
+    /// Count a node, failing if that goes over a limit. The clock and the
+    /// cancel token are read at the first node and every 64 after.
+    pub(crate) fn spend(&mut self) -> Result<(), SudokuError>
+    {
+        self.nodes += 1;
+        if self.max_nodes.is_some_and(|max| self.nodes > max) {
+            return Err(SudokuError::LimitExceeded);
+        }
+        if !(self.nodes - 1).is_multiple_of(64) {
+            return Ok(());
+        }
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            return Err(SudokuError::Cancelled);
+        }
+        if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            return Err(SudokuError::LimitExceeded);
+        }
+        Ok(())
+    }
+}
+
+
+impl SudokuGrid {
+
+    /// Find a solution using the given settings. Set squares are kept as
+    /// they are, and so are the squares filled by the logical pass.
+    pub fn solve_with(&self, config: &SolverConfig) -> Result<SudokuGrid, SudokuError>
+    {
+        self.solve_with_stats(config).map(|(grid, _)| grid)
+    }
+
+    /// Solve by backtracking until a solution is found or `token` is
+    /// cancelled from another thread.
+    pub fn solve_cancelable(&self, token: &CancelToken) -> Result<SudokuGrid, SudokuError>
+    {
+        self.solve_with(&SolverConfig::new().cancel_token(token.clone()))
+    }
+
+    /// Like `solve_with`, also reporting how much work the solve took.
+    /// Squares filled by the logical pass count as propagations.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn solve_with_stats(&self, config: &SolverConfig)
+        -> Result<(SudokuGrid, SolveStats), SudokuError>
+    {
+        let start = Instant::now();
+        let mut budget = config.budget();
+        let grid = self.logical_pass(config, &mut budget)?;
+
+        let solution = match (&config.constraints, config.backend) {
+            (Some(constraints), _) => {
+                let mut rng = config.seed.map(StdRng::seed_from_u64);
+                let mut solution = None;
+                let mut start = constrained::blank_candidates(&grid);
+                constrained::walk(constraints, &mut start, config.heuristic, &mut rng, &mut budget, 0, &mut |g| {
+                    solution = Some(g.clone());
+                    false
+                })?;
+                solution.ok_or(SudokuError::NoSolution)?
+            },
+            (None, SolverBackend::Backtracking) => {
+                let mut rng = config.seed.map(StdRng::seed_from_u64);
+                let values = backtrack::grid_values(&grid);
+                let mut search = backtrack::Search::new(&values).ok_or(SudokuError::NoSolution)?;
+                if !search.find(config.heuristic, &mut rng, &mut budget)? {
+                    return Err(SudokuError::NoSolution);
+                }
+                backtrack::fill(&grid, search.values())
+            },
+            (None, SolverBackend::DancingLinks) => dlx::solve_within(&grid, &mut budget)?
+        };
+        budget.stats.duration = start.elapsed();
+        Ok((solution, budget.stats))
+    }
+
+    /// Count solutions using the given settings, stopping once `limit`
+    /// have been found. Fails with `LimitExceeded` rather than returning
+    /// a partial count when the node or time limit runs out first.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(limit = limit)))]
+    pub fn count_solutions_with(&self, config: &SolverConfig, limit: usize)
+        -> Result<usize, SudokuError>
+    {
+        let mut budget = config.budget();
+        let grid = match self.logical_pass(config, &mut budget) {
+            Ok(grid) => grid,
+            Err(SudokuError::NoSolution) => return Ok(0),
+            Err(err) => return Err(err)
+        };
+        if limit == 0 {
+            return Ok(0);
+        }
+
+        match (&config.constraints, config.backend) {
+            (Some(constraints), _) => {
+                let mut rng = config.seed.map(StdRng::seed_from_u64);
+                let mut count = 0;
+                let mut start = constrained::blank_candidates(&grid);
+                constrained::walk(constraints, &mut start, config.heuristic, &mut rng, &mut budget, 0, &mut |_| {
+                    count += 1;
+                    count < limit
+                })?;
+                Ok(count)
+            },
+            (None, SolverBackend::Backtracking) => {
+                let mut rng = config.seed.map(StdRng::seed_from_u64);
+                let values = backtrack::grid_values(&grid);
+                let mut search = match backtrack::Search::new(&values) {
+                    Some(search) => search,
+                    None => return Ok(0)
+                };
+                let mut count = 0;
+                search.walk(config.heuristic, &mut rng, &mut budget, 0, &mut |_| {
+                    count += 1;
+                    count < limit
+                })?;
+                Ok(count)
+            },
+            (None, SolverBackend::DancingLinks) => dlx::count_within(&grid, limit, &mut budget)
+        }
+    }
+
+    /// Whether the grid has exactly one solution, within the limits of
+    /// `config`. Suited to checking puzzles from untrusted sources.
+    pub fn has_unique_solution_with(&self, config: &SolverConfig) -> Result<bool, SudokuError>
+    {
+        Ok(self.count_solutions_with(config, 2)? == 1)
+    }
+
+    /// Apply the configured techniques to a copy of the grid, counting
+    /// their placements as propagations.
+    fn logical_pass(&self, config: &SolverConfig, budget: &mut Budget)
+        -> Result<SudokuGrid, SudokuError>
+    {
+        let mut grid = self.clone();
+        if !config.techniques.is_empty() && config.constraints.is_none() {
+            let steps = LogicalSolver::with_techniques(&config.techniques)
+                .solve(&mut grid)
+                .map_err(|_| SudokuError::NoSolution)?;
+            budget.stats.propagations = steps.iter().map(|s| s.placements.len() as u64).sum();
+        }
+        Ok(grid)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::UniqueDigits;
+
+    fn puzzle() -> SudokuGrid
+    {
+        concat!(
+            "..............3.85..1.2.......5.7.....4...1...9.......5......",
+            "73..2.1........4...9"
+        ).parse().unwrap()
+    }
+
+    #[test]
+    fn test_configurations_agree()
+    {
+        let grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        let expected = grid.solve().unwrap();
+        let configs = [
+            SolverConfig::new(),
+            SolverConfig::new().heuristic(Heuristic::FirstEmpty),
+            SolverConfig::new().seed(11),
+            SolverConfig::new().backend(SolverBackend::DancingLinks),
+            SolverConfig::new().techniques(&[Technique::NakedSingle, Technique::HiddenSingle]),
+            SolverConfig::new().constraints(ConstraintSet::standard())
+        ];
+        for config in configs.iter() {
+            assert_eq!(grid.solve_with(config), Ok(expected.clone()));
+        }
+    }
+
+    #[test]
+    fn test_stats()
+    {
+        let grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+
+        let (_, stats) = grid.solve_with_stats(&SolverConfig::new()).unwrap();
+        assert_eq!(stats.guesses, 0);
+        assert_eq!(stats.propagations, 51);
+        assert_eq!(stats.max_depth, 51);
+
+        let config = SolverConfig::new().techniques(&[Technique::NakedSingle]);
+        let (_, stats) = grid.solve_with_stats(&config).unwrap();
+        assert_eq!(stats.propagations, 51);
+        assert_eq!(stats.max_depth, 0);
+
+        let (_, stats) = puzzle().solve_with_stats(&SolverConfig::new()).unwrap();
+        assert!(stats.guesses > 0 && stats.backtracks > 0);
+        let (_, dlx) = puzzle().solve_with_stats(
+            &SolverConfig::new().backend(SolverBackend::DancingLinks)
+        ).unwrap();
+        assert_eq!(dlx.max_depth, 64);
+    }
+
+    #[test]
+    fn test_node_limit()
+    {
+        let grid = puzzle();
+        for &backend in [SolverBackend::Backtracking, SolverBackend::DancingLinks].iter() {
+            let config = SolverConfig::new().backend(backend).node_limit(10);
+            assert_eq!(grid.solve_with(&config), Err(SudokuError::LimitExceeded));
+        }
+    }
+
+    #[test]
+    fn test_limited_counting()
+    {
+        let grid = puzzle();
+        for &backend in [SolverBackend::Backtracking, SolverBackend::DancingLinks].iter() {
+            let config = SolverConfig::new().backend(backend);
+            assert_eq!(grid.has_unique_solution_with(&config), Ok(true));
+            assert_eq!(SudokuGrid::new().count_solutions_with(&config, 5), Ok(5));
+            assert_eq!(
+                grid.count_solutions_with(&config.node_limit(10), 2),
+                Err(SudokuError::LimitExceeded)
+            );
+        }
+    }
+
+    #[test]
+    fn test_cancel()
+    {
+        let token = CancelToken::new();
+        let grid = puzzle();
+        assert!(grid.solve_cancelable(&token).is_ok());
+
+        let other = token.clone();
+        other.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(grid.solve_cancelable(&token), Err(SudokuError::Cancelled));
+    }
+
+    #[test]
+    fn test_time_limit()
+    {
+        let config = SolverConfig::new().time_limit(Duration::from_secs(0));
+        let grid = puzzle().solve_with(&config);
+        assert_eq!(grid, Err(SudokuError::LimitExceeded));
+    }
+
+    #[test]
+    fn test_seed_varies_solution_of_empty_grid()
+    {
+        let empty = SudokuGrid::new();
+        let a = empty.solve_with(&SolverConfig::new().seed(1)).unwrap();
+        let b = empty.solve_with(&SolverConfig::new().seed(2)).unwrap();
+        assert!(a.check().unwrap() && b.check().unwrap());
+        assert_ne!(a, b);
+        assert_eq!(a, empty.solve_with(&SolverConfig::new().seed(1)).unwrap());
+    }
+
+    #[test]
+    fn test_constraints()
+    {
+        let standard = SolverConfig::new().constraints(ConstraintSet::standard());
+        let grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        assert_eq!(grid.solve_with(&standard), grid.solve());
+        assert_eq!(grid.count_solutions_with(&standard, 5), Ok(1));
+
+        // Without boxes the puzzle loses its unique solution.
+        let latin = ConstraintSet::empty()
+            .with(UniqueDigits::rows())
+            .with(UniqueDigits::columns());
+        let config = SolverConfig::new().constraints(latin.clone());
+        assert_eq!(grid.count_solutions_with(&config, 2), Ok(2));
+        let solution = grid.solve_with(&config).unwrap();
+        assert!(latin.check(&solution));
+    }
+}
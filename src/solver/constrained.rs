@@ -0,0 +1,73 @@
+//! Backtracking over the rules of a `ConstraintSet`, for variants that
+//! the row, column and box searches don't know about.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::{SudokuGrid, SudokuError};
+use crate::constraint::ConstraintSet;
+
+use super::backtrack::mask_digits;
+use super::config::{Budget, Heuristic};
+
+
+/// A copy of the set squares of `grid` with every empty square's
+/// candidates restored, ready to be searched.
+pub(crate) fn blank_candidates(grid: &SudokuGrid) -> SudokuGrid
+{
+    let mut grid = grid.clone();
+    for sq in grid.0.iter_mut().filter(|sq| !sq.is_set()) {
+        sq.unset();
+    }
+    grid
+}
+
+/// Propagate the constraints, then guess in the square chosen by
+/// `heuristic` and recurse, calling `found` with each solution until it
+/// returns false. Returns false if stopped early. The grid is searched in
+/// place and left in an unspecified state.
+pub(crate) fn walk<F>(
+    constraints: &ConstraintSet,
+    grid: &mut SudokuGrid,
+    heuristic: Heuristic,
+    rng: &mut Option<StdRng>,
+    budget: &mut Budget,
+    depth: usize,
+    found: &mut F
+) -> Result<bool, SudokuError>
+where
+    F: FnMut(&SudokuGrid) -> bool
+{
+    budget.spend()?;
+    if constraints.propagate(grid).is_err() {
+        return Ok(true);
+    }
+
+    let mut empty = grid.0.iter().filter(|sq| !sq.is_set());
+    let choice = match heuristic {
+        Heuristic::FirstEmpty => empty.next(),
+        Heuristic::MinimumRemaining => empty.min_by_key(|sq| sq.possibilities_number())
+    };
+    let (row, col, mask) = match choice {
+        None => return Ok(found(grid)),
+        Some(sq) => (sq.row(), sq.col(), sq.digit_bits())
+    };
+
+    let mut digits = mask_digits(mask);
+    if let Some(rng) = rng.as_mut() {
+        digits.shuffle(rng);
+    }
+    let state = grid.snapshot();
+    for digit in digits {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(depth, row, col, digit, "branch");
+        grid.restore(&state);
+        grid.set_unchecked(row, col, digit);
+        budget.place(mask.count_ones(), depth + 1);
+        if !walk(constraints, grid, heuristic, rng, budget, depth + 1, found)? {
+            return Ok(false);
+        }
+        budget.backtrack();
+    }
+    Ok(true)
+}
@@ -0,0 +1,284 @@
+//! Knuth's dancing links over the exact cover form of sudoku.
+//!
+//! Each of the 729 placements of a digit in a square is a row of the
+//! matrix covering four of its 324 columns: the square is filled, and the
+//! digit appears in the square's row, column and box.
+
+use crate::{SudokuGrid, SudokuError};
+
+use super::backtrack::{fill, grid_values};
+use super::config::Budget;
+
+
+const COLUMNS: usize = 324;
+const ROOT: usize = 0;
+
+
+/// The matrix as circular doubly linked lists. Node 0 is the root, nodes
+/// 1 to 324 head the columns and each placement then takes four nodes.
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    size: [usize; COLUMNS + 1],
+    covered: [bool; COLUMNS + 1],
+    chosen: Vec<usize>,
+    values: [u8; 81]
+}
+
+
+/// The first node of the row placing `digit` in square `index`.
+#[inline(always)]
+fn row_node(index: usize, digit: u8) -> usize
+{
+    COLUMNS + 1 + 4*(9*index + usize::from(digit - 1))
+}
+
+
+impl Links {
+
+    /// Build the matrix and select the rows of the set digits, or `None`
+    /// if two of them clash.
+    fn new(values: &[u8; 81]) -> Option<Links>
+    {
+        let nodes = COLUMNS + 1 + 4*729;
+        let mut links = Links {
+            left: (0..nodes).collect(),
+            right: (0..nodes).collect(),
+            up: (0..nodes).collect(),
+            down: (0..nodes).collect(),
+            column: vec![0; nodes],
+            size: [0; COLUMNS + 1],
+            covered: [false; COLUMNS + 1],
+            chosen: Vec::with_capacity(81),
+            values: *values
+        };
+
+        for h in 0..=COLUMNS {
+            links.left[h] = if h == 0 { COLUMNS } else { h - 1 };
+            links.right[h] = if h == COLUMNS { 0 } else { h + 1 };
+        }
+
+        for index in 0..81 {
+            let (r, c) = (index / 9, index % 9);
+            let b = 3*(r / 3) + c / 3;
+            for digit in 1..=9 {
+                let d = usize::from(digit - 1);
+                let first = row_node(index, digit);
+                let heads = [1 + index, 82 + 9*r + d, 163 + 9*c + d, 244 + 9*b + d];
+                for (k, &head) in heads.iter().enumerate() {
+                    let node = first + k;
+                    links.left[node] = first + (k + 3) % 4;
+                    links.right[node] = first + (k + 1) % 4;
+                    links.column[node] = head;
+                    links.up[node] = links.up[head];
+                    links.down[node] = head;
+                    links.down[links.up[head]] = node;
+                    links.up[head] = node;
+                    links.size[head] += 1;
+                }
+            }
+        }
+
+        for (index, &digit) in values.iter().enumerate() {
+            if digit == 0 {
+                continue;
+            }
+            let first = row_node(index, digit);
+            if (first..first + 4).any(|node| links.covered[links.column[node]]) {
+                return None;
+            }
+            for node in first..first + 4 {
+                links.cover(links.column[node]);
+            }
+        }
+        Some(links)
+    }
+
+    fn cover(&mut self, head: usize)
+    {
+        self.covered[head] = true;
+        self.right[self.left[head]] = self.right[head];
+        self.left[self.right[head]] = self.left[head];
+        let mut i = self.down[head];
+        while i != head {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, head: usize)
+    {
+        let mut i = self.up[head];
+        while i != head {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        self.right[self.left[head]] = head;
+        self.left[self.right[head]] = head;
+        self.covered[head] = false;
+    }
+
+    /// The uncovered column with the fewest rows, `None` when all are
+    /// covered.
+    fn choose(&self) -> Option<usize>
+    {
+        let mut best: Option<usize> = None;
+        let mut h = self.right[ROOT];
+        while h != ROOT {
+            if best.is_none_or(|b| self.size[h] < self.size[b]) {
+                best = Some(h);
+                if self.size[h] <= 1 {
+                    break;
+                }
+            }
+            h = self.right[h];
+        }
+        best
+    }
+
+    /// Walk the exact covers, calling `found` with the digits of each.
+    /// Stops when `found` returns false, leaving `values` at that cover.
+    fn run<F>(&mut self, budget: &mut Budget, found: &mut F) -> Result<bool, SudokuError>
+    where
+        F: FnMut(&[u8; 81]) -> bool
+    {
+        budget.spend()?;
+        let head = match self.choose() {
+            None => {
+                let mut values = self.values;
+                for &node in self.chosen.iter() {
+                    let placement = (node - COLUMNS - 1) / 4;
+                    values[placement / 9] = (placement % 9 + 1) as u8;
+                }
+                if found(&values) {
+                    return Ok(true);
+                }
+                self.values = values;
+                return Ok(false);
+            },
+            Some(head) => head
+        };
+
+        self.cover(head);
+        let mut i = self.down[head];
+        let options = self.size[head] as u32;
+        while i != head {
+            self.chosen.push(i);
+            budget.place(options, self.chosen.len());
+            let mut j = self.right[i];
+            while j != i {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+
+            let more = self.run(budget, found)?;
+
+            let mut j = self.left[i];
+            while j != i {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            self.chosen.pop();
+            if more {
+                budget.backtrack();
+            } else {
+                self.uncover(head);
+                return Ok(false);
+            }
+            i = self.down[i];
+        }
+        self.uncover(head);
+        Ok(true)
+    }
+}
+
+
+/// Find a solution with dancing links. Set squares are kept as they are.
+pub fn solve(grid: &SudokuGrid) -> Result<SudokuGrid, SudokuError>
+{
+    solve_within(grid, &mut Budget::unlimited())
+}
+
+pub(crate) fn solve_within(grid: &SudokuGrid, budget: &mut Budget) -> Result<SudokuGrid, SudokuError>
+{
+    let mut links = Links::new(&grid_values(grid)).ok_or(SudokuError::NoSolution)?;
+    if links.run(budget, &mut |_| false)? {
+        return Err(SudokuError::NoSolution);
+    }
+    Ok(fill(grid, &links.values))
+}
+
+/// Count the solutions of the grid with dancing links, stopping once
+/// `limit` have been found.
+pub fn count_solutions(grid: &SudokuGrid, limit: usize) -> usize
+{
+    count_within(grid, limit, &mut Budget::unlimited()).unwrap_or(0)
+}
+
+pub(crate) fn count_within(grid: &SudokuGrid, limit: usize, budget: &mut Budget)
+    -> Result<usize, SudokuError>
+{
+    let mut count = 0;
+    if limit > 0 {
+        if let Some(mut links) = Links::new(&grid_values(grid)) {
+            links.run(budget, &mut |_| {
+                count += 1;
+                count < limit
+            })?;
+        }
+    }
+    Ok(count)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_matches_backtracking()
+    {
+        let grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        assert_eq!(solve(&grid), grid.solve());
+    }
+
+    #[test]
+    fn test_count_solutions()
+    {
+        let grid: SudokuGrid = concat!(
+            "............................59761423.2685379171392485696",
+            "1537284287419635345286179"
+        ).parse().unwrap();
+        assert_eq!(count_solutions(&grid, 1000), 144);
+        assert_eq!(count_solutions(&grid, 10), 10);
+        assert_eq!(count_solutions(&SudokuGrid::new(), 3), 3);
+    }
+
+    #[test]
+    fn test_clashing_givens()
+    {
+        let mut grid = SudokuGrid::new();
+        grid.set((1, 1), 2).unwrap();
+        grid.set((2, 2), 2).unwrap();
+        assert_eq!(solve(&grid), Err(SudokuError::NoSolution));
+        assert_eq!(count_solutions(&grid, 2), 0);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{SudokuGrid, SudokuError};
+use crate::techniques::{self, Elimination, Technique};
+
+
+/// One step of a logical solve: the technique that was applied, the
+/// squares it filled as `(row, col, digit)` and the candidates it removed.
+/// `cells` are the squares that make up the pattern the technique found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveStep {
+    pub technique: Technique,
+    pub placements: Vec<(u8, u8, u8)>,
+    pub eliminations: Vec<Elimination>,
+    pub cells: Vec<(u8, u8)>
+}
+
+
+/// Solves a grid the way a person would, trying techniques from easiest
+/// to hardest and recording every step that makes progress.
+#[derive(Debug, Clone)]
+pub struct LogicalSolver {
+    techniques: Vec<Technique>
+}
+
+
+impl Default for LogicalSolver {
+    fn default() -> LogicalSolver
+    {
+        LogicalSolver::new()
+    }
+}
+
+
+impl LogicalSolver {
+
+    pub fn new() -> LogicalSolver
+    {
+        LogicalSolver { techniques: Technique::ALL.to_vec() }
+    }
+
+    /// A solver restricted to the given techniques, which are always tried
+    /// in order of difficulty.
+    pub fn with_techniques(techniques: &[Technique]) -> LogicalSolver
+    {
+        let mut techniques = techniques.to_vec();
+        techniques.sort();
+        techniques.dedup();
+        LogicalSolver { techniques }
+    }
+
+    pub fn techniques(&self) -> &[Technique]
+    {
+        &self.techniques
+    }
+
+    /// Apply steps until the grid is full or no technique makes progress,
+    /// returning the steps taken. The grid is left in its final state.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn solve(&self, grid: &mut SudokuGrid) -> Result<Vec<SolveStep>, SudokuError>
+    {
+        grid.eliminate_all()?;
+
+        let mut steps = Vec::new();
+        while let Some(step) = self.next_step(grid)? {
+            steps.push(step);
+        }
+        Ok(steps)
+    }
+
+    /// Apply the easiest technique that makes progress on the grid. The
+    /// candidates are assumed to be up to date with the set squares.
+    pub fn next_step(&self, grid: &mut SudokuGrid) -> Result<Option<SolveStep>, SudokuError>
+    {
+        for &technique in self.techniques.iter() {
+            if let Some(step) = apply(technique, grid) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    ?technique,
+                    placements = step.placements.len(),
+                    eliminations = step.eliminations.len(),
+                    "technique applied"
+                );
+                if let Some(sq) = grid.0.iter().find(
+                    |sq| !sq.is_set() && sq.possibilities_number() == 0
+                ) {
+                    return Err(SudokuError::NoCandidates { row: sq.row(), col: sq.col() });
+                }
+                return Ok(Some(step));
+            }
+        }
+        Ok(None)
+    }
+
+}
+
+
+fn apply(technique: Technique, grid: &mut SudokuGrid) -> Option<SolveStep>
+{
+    let deductions = match technique {
+        Technique::NakedSingle => {
+            let sq = grid.0.iter().find(|sq| !sq.is_set() && sq.possibilities_number() == 1)?;
+            let digit = (1..=9).find(|&d| sq.is_possible(d))?;
+            return Some(place(grid, technique, (sq.row(), sq.col(), digit)));
+        },
+        Technique::HiddenSingle => {
+            let single = *grid.find_hidden_singles().first()?;
+            return Some(place(grid, technique, single));
+        },
+        Technique::NakedPair => techniques::naked_subset_deductions(grid, 2),
+        Technique::NakedTriple => techniques::naked_subset_deductions(grid, 3),
+        Technique::NakedQuad => techniques::naked_subset_deductions(grid, 4),
+        Technique::XWing => techniques::basic_fish(grid, 2),
+        Technique::Swordfish => techniques::basic_fish(grid, 3),
+        Technique::Jellyfish => techniques::basic_fish(grid, 4),
+        Technique::XYWing => techniques::xy_wing(grid),
+        Technique::XYZWing => techniques::xyz_wing(grid),
+        Technique::SimpleColouring => techniques::simple_colouring(grid)
+    };
+
+    if deductions.is_empty() {
+        return None;
+    }
+
+    let mut step = SolveStep {
+        technique,
+        placements: Vec::new(),
+        eliminations: Vec::new(),
+        cells: Vec::new()
+    };
+    for deduction in deductions {
+        step.eliminations.extend(deduction.eliminations);
+        for cell in deduction.cells {
+            if !step.cells.contains(&cell) {
+                step.cells.push(cell);
+            }
+        }
+    }
+    Some(step)
+}
+
+fn place(grid: &mut SudokuGrid, technique: Technique, placement: (u8, u8, u8)) -> SolveStep
+{
+    let (row, col, digit) = placement;
+    grid.place(row, col, digit);
+    SolveStep {
+        technique,
+        placements: vec![placement],
+        eliminations: Vec::new(),
+        cells: vec![(row, col)]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_easy_puzzle()
+    {
+        let mut grid = SudokuGrid::new();
+        let puzzle = [
+            5, 3, 0, 0, 7, 0, 0, 0, 0,
+            6, 0, 0, 1, 9, 5, 0, 0, 0,
+            0, 9, 8, 0, 0, 0, 0, 6, 0,
+            8, 0, 0, 0, 6, 0, 0, 0, 3,
+            4, 0, 0, 8, 0, 3, 0, 0, 1,
+            7, 0, 0, 0, 2, 0, 0, 0, 6,
+            0, 6, 0, 0, 0, 0, 2, 8, 0,
+            0, 0, 0, 4, 1, 9, 0, 0, 5,
+            0, 0, 0, 0, 8, 0, 0, 7, 9
+        ];
+        for (i, &v) in puzzle.iter().enumerate() {
+            if v != 0 {
+                grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, v);
+            }
+        }
+
+        let steps = LogicalSolver::new().solve(&mut grid).unwrap();
+        assert!(grid.check().unwrap());
+        assert_eq!(steps.iter().map(|s| s.placements.len()).sum::<usize>(), 51);
+        assert!(steps.iter().all(|s| s.technique <= Technique::HiddenSingle));
+    }
+
+    #[test]
+    fn test_solve_complete_grid_has_no_steps()
+    {
+        let mut grid = sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ];
+        assert!(LogicalSolver::new().solve(&mut grid).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_steps_are_traced()
+    {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tracing::{Event, Metadata, Subscriber, span};
+
+        /// Counts the events from `solver::logical`.
+        struct Counter(Arc<AtomicUsize>);
+
+        impl Subscriber for Counter {
+            fn enabled(&self, _: &Metadata) -> bool { true }
+            fn new_span(&self, _: &span::Attributes) -> span::Id { span::Id::from_u64(1) }
+            fn record(&self, _: &span::Id, _: &span::Record) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+            fn event(&self, event: &Event)
+            {
+                if event.metadata().target() == "bitsudoku::solver::logical" {
+                    self.0.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut grid: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+        let steps = tracing::subscriber::with_default(Counter(Arc::clone(&count)), || {
+            LogicalSolver::new().solve(&mut grid).unwrap()
+        });
+        assert_eq!(count.load(Ordering::Relaxed), steps.len());
+    }
+}
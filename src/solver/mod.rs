@@ -0,0 +1,101 @@
+//! Solvers for sudoku grids.
+
+pub(crate) mod backtrack;
+mod config;
+mod constrained;
+pub mod dlx;
+mod logical;
+
+pub use backtrack::Solutions;
+pub use config::{CancelToken, Heuristic, SolveStats, SolverConfig};
+pub use logical::{LogicalSolver, SolveStep};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{SudokuGrid, SudokuError};
+
+
+/// The search behind a brute-force solve.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SolverBackend {
+    /// Backtracking over row, column and box masks, guessing in the
+    /// square with the fewest candidates.
+    #[default]
+    Backtracking,
+    /// Knuth's Algorithm X with dancing links, see `solver::dlx`.
+    DancingLinks
+}
+
+
+impl SolverBackend {
+
+    /// Find a solution of the grid. Set squares are kept as they are.
+    pub fn solve(self, grid: &SudokuGrid) -> Result<SudokuGrid, SudokuError>
+    {
+        match self {
+            SolverBackend::Backtracking => grid.solve(),
+            SolverBackend::DancingLinks => dlx::solve(grid)
+        }
+    }
+
+    /// Count the solutions of the grid, stopping once `limit` have been
+    /// found.
+    pub fn count_solutions(self, grid: &SudokuGrid, limit: usize) -> usize
+    {
+        match self {
+            SolverBackend::Backtracking => grid.count_solutions(limit),
+            SolverBackend::DancingLinks => dlx::count_solutions(grid, limit)
+        }
+    }
+}
+
+
+/// Solve a batch of puzzles, keeping their order. With the `parallel`
+/// feature the puzzles are spread across threads by rayon.
+pub fn solve_many(grids: &[SudokuGrid]) -> Vec<Result<SudokuGrid, SudokuError>>
+{
+    #[cfg(feature = "parallel")]
+    let results = grids.par_iter().map(SudokuGrid::solve).collect();
+    #[cfg(not(feature = "parallel"))]
+    let results = grids.iter().map(SudokuGrid::solve).collect();
+    results
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_many_keeps_order()
+    {
+        let mut bad = SudokuGrid::new();
+        bad.set((1, 1), 1).unwrap();
+        bad.set((1, 2), 1).unwrap();
+        let puzzle: SudokuGrid = concat!(
+            "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+            ".6....28....419..5....8..79"
+        ).parse().unwrap();
+
+        let results = solve_many(&[puzzle.clone(), bad, puzzle]);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].as_ref().unwrap().check().unwrap());
+        assert_eq!(results[1], Err(SudokuError::NoSolution));
+        assert_eq!(results[0], results[2]);
+    }
+
+    #[test]
+    fn test_backends_agree()
+    {
+        let grid: SudokuGrid = concat!(
+            "..............3.85..1.2.......5.7.....4...1...9.......5......",
+            "73..2.1........4...9"
+        ).parse().unwrap();
+        let solution = SolverBackend::Backtracking.solve(&grid).unwrap();
+        assert_eq!(SolverBackend::DancingLinks.solve(&grid).unwrap(), solution);
+        for &backend in [SolverBackend::Backtracking, SolverBackend::DancingLinks].iter() {
+            assert_eq!(backend.count_solutions(&grid, 2), 1);
+        }
+    }
+}
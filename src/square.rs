@@ -1,11 +1,13 @@
 use std::clone::Clone;
+use std::convert::TryInto;
 use std::marker::Copy;
 
-use super::SudokuError;
+use super::{Digit, Pos, SudokuError};
 
 pub(crate) static ROW_MASK: u8 = 0xF0;
 pub(crate) static COL_MASK: u8 = 0x0F;
 pub(crate) static SET_BIT: u16 = 0x0200;
+pub(crate) static GIVEN_BIT: u16 = 0x0400;
 pub(crate) static DIGIT_MASK: u16 = 0x01FF;
 pub(crate) static BOX_MASK: u16 = 0x7800;
 
@@ -27,9 +29,10 @@ fn is_pow_2(num: u16) -> bool
  * Bits as follows:
  * 1-9   possiblilies of each digit
  * 10    digit set
- * 11-14 box
+ * 11    digit is a given clue
+ * 12-15 box
  */
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SudokuSquare(u8, u16);
 
 
@@ -75,21 +78,70 @@ impl SudokuSquare {
         }
 
         let mut sq = SudokuSquare::new(row, col);
-        sq.1 = SET_BIT | (0x0001 << (value - 1));
+        sq.set_value(value)?;
         Ok(sq)
     }
 
     pub(crate) fn set_position(&mut self, row: u8, col: u8)
     {
         self.0 = ((row & 0x0F) << 4) + (col & 0x0F);
+        self.1 = (self.1 & !BOX_MASK) | SudokuSquare::get_box_index(row, col);
     }
 
-    pub(crate) fn set_value(&mut self, value: u8)
+    /// Set the square to `value`, a `Digit` or a `u8` from 1 to 9. An
+    /// already set square is left alone with `IsAlreadySet`; use
+    /// `force_value` to overwrite it.
+    pub fn set_value<D>(&mut self, value: D) -> Result<(), SudokuError>
+    where
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        if self.is_set() {
+            return Err(SudokuError::IsAlreadySet);
+        }
+        self.force_value(value)
+    }
+
+    /// Set the square to `value` whether or not it is already set, which
+    /// also drops any given mark.
+    pub fn force_value<D>(&mut self, value: D) -> Result<(), SudokuError>
+    where
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        let digit: Digit = value.try_into()?;
+        self.set_value_unchecked(digit.get());
+        Ok(())
+    }
+
+    pub(crate) fn set_value_unchecked(&mut self, value: u8)
     {
         let idx = SudokuSquare::get_box_index(self.row(), self.col());
         self.1 = SET_BIT | idx | (0x0001 << (value - 1));
     }
 
+    /// Whether the digit is one of the puzzle's original clues.
+    pub fn is_given(&self) -> bool
+    {
+        (self.1 & GIVEN_BIT) != 0
+    }
+
+    /// Mark the digit of a set square as a given clue.
+    pub fn set_given(&mut self) -> Result<(), SudokuError>
+    {
+        if !self.is_set() {
+            return Err(SudokuError::NotSet);
+        }
+        self.1 |= GIVEN_BIT;
+        Ok(())
+    }
+
+    /// Clear the digit and restore every candidate.
+    pub fn unset(&mut self)
+    {
+        self.1 = (self.1 & BOX_MASK) | DIGIT_MASK;
+    }
+
     pub fn row(&self) -> u8
     {
         (self.0 & ROW_MASK) >> 4
@@ -100,6 +152,11 @@ impl SudokuSquare {
         self.0 & COL_MASK
     }
 
+    pub fn pos(&self) -> Pos
+    {
+        Pos::new_unchecked(self.row(), self.col())
+    }
+
     pub fn get_box(&self) -> u8
     {
         ((self.1 & BOX_MASK) >> 11) as u8
@@ -110,9 +167,11 @@ impl SudokuSquare {
         (self.1 & SET_BIT) != 0
     }
 
-    pub fn is(&self, digit: u8) -> bool
+    /// Whether the square is set to `digit`. Values that aren't digits
+    /// never match.
+    pub fn is<D: TryInto<Digit>>(&self, digit: D) -> bool
     {
-        self.is_set() && (self.0 & (0x0001 << (digit - 1)) != 0)
+        self.is_set() && self.is_possible(digit)
     }
 
     pub fn get(&self) -> Result<u8, SudokuError>
@@ -134,20 +193,28 @@ impl SudokuSquare {
         }
     }
 
+    /// The digit of a set square, `None` for an empty one.
+    pub fn digit(&self) -> Option<Digit>
+    {
+        self.get().ok().and_then(Digit::new)
+    }
+
     pub fn set(&mut self) -> Result<u8, SudokuError>
     {
-        if !is_pow_2(self.1) {
-            return Err(SudokuError::NonUniqueSet);
-        } else if self.is_set() {
+        if self.is_set() {
             return Err(SudokuError::IsAlreadySet);
+        } else if !is_pow_2(self.1 & DIGIT_MASK) {
+            return Err(SudokuError::NonUniqueSet);
         }
         self.1 |= SET_BIT;
         self.get()
     }
 
-    pub fn is_possible(&self, value: u8) -> bool
+    /// Whether `value` is a candidate, or the digit of a set square.
+    /// Values that aren't digits never are.
+    pub fn is_possible<D: TryInto<Digit>>(&self, value: D) -> bool
     {
-        self.1 & (0x0001 << (value - 1)) != 0
+        value.try_into().is_ok_and(|digit: Digit| self.1 & digit.bit() != 0)
     }
 
     pub fn possibilities_number(&self) -> u8
@@ -155,14 +222,74 @@ impl SudokuSquare {
         (self.1 & DIGIT_MASK).count_ones() as u8
     }
 
-    pub fn remove_possibility(&mut self, value: u8)
+    /// Remove `value` from the candidates.
+    pub fn remove_possibility<D>(&mut self, value: D) -> Result<(), SudokuError>
+    where
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        let digit: Digit = value.try_into()?;
+        self.1 &= !digit.bit();
+        Ok(())
+    }
+
+    /// Like `remove_possibility`, for a digit known to be from 1 to 9.
+    pub(crate) fn remove_possibility_unchecked(&mut self, value: u8)
     {
         self.1 &= !(0x0001 << (value - 1));
     }
 
+    /// Make `value` a candidate again. A set square is left alone.
+    pub fn add_possibility<D>(&mut self, value: D) -> Result<(), SudokuError>
+    where
+        D: TryInto<Digit>,
+        SudokuError: From<D::Error>
+    {
+        let digit: Digit = value.try_into()?;
+        if !self.is_set() {
+            self.1 |= digit.bit();
+        }
+        Ok(())
+    }
+
+    /// Make every digit a candidate again. A set square is left alone;
+    /// `unset` clears it as well.
+    pub fn reset_candidates(&mut self)
+    {
+        self.set_candidates(DIGIT_MASK);
+    }
+
+    /// Replace the candidates with the digits whose bits are set in
+    /// `mask`, bit `d - 1` for digit `d`. A set square is left alone.
+    pub fn set_candidates(&mut self, mask: u16)
+    {
+        if !self.is_set() {
+            self.1 = (self.1 & !DIGIT_MASK) | (mask & DIGIT_MASK);
+        }
+    }
+
+    /// Keep only the candidates whose bits are set in `mask`. A set
+    /// square is left alone.
+    pub fn intersect_candidates(&mut self, mask: u16)
+    {
+        if !self.is_set() {
+            self.1 &= mask | !DIGIT_MASK;
+        }
+    }
+
+    /// Remove the candidates whose bits are set in `mask`. A set square is
+    /// left alone.
+    pub fn remove_candidates(&mut self, mask: u16)
+    {
+        if !self.is_set() {
+            self.1 &= !(mask & DIGIT_MASK);
+        }
+    }
+
+    /// The same as `intersect_candidates`.
     pub fn apply_mask(&mut self, mask: u16)
     {
-        self.1 &= mask & DIGIT_MASK;
+        self.intersect_candidates(mask);
     }
 
     pub fn digit_bits(&self) -> u16
@@ -170,6 +297,30 @@ impl SudokuSquare {
         self.1 & DIGIT_MASK
     }
 
+    /// The candidates as bits, bit `d - 1` for digit `d`. A set square
+    /// has none; `digit_bits` gives the bit of its digit instead.
+    pub fn candidate_mask(&self) -> u16
+    {
+        if self.is_set() { 0 } else { self.1 & DIGIT_MASK }
+    }
+
+    /// The candidate digits in increasing order, none for a set square.
+    pub fn candidates(&self) -> impl Iterator<Item = u8>
+    {
+        let mask = self.candidate_mask();
+        (1..=9).filter(move |&d| mask & (0x0001 << (d - 1)) != 0)
+    }
+
+    pub(crate) fn raw(&self) -> (u8, u16)
+    {
+        (self.0, self.1)
+    }
+
+    pub(crate) fn from_raw(position: u8, bits: u16) -> SudokuSquare
+    {
+        SudokuSquare(position, bits)
+    }
+
 }
 
 
@@ -277,6 +428,145 @@ mod tests {
 
     }
 
+    #[test]
+    fn test_equality()
+    {
+        let mut sq = SudokuSquare::new(2, 3);
+        assert_eq!(sq, SudokuSquare::new(2, 3));
+        assert_ne!(sq, SudokuSquare::new(3, 2));
+
+        sq.remove_possibility(4).unwrap();
+        assert_ne!(sq, SudokuSquare::new(2, 3));
+    }
+
+    #[test]
+    fn test_given()
+    {
+        let mut sq = SudokuSquare::new(1, 1);
+        assert!(sq.set_given().is_err());
+
+        sq.set_value(3).unwrap();
+        assert!(!sq.is_given());
+        sq.set_given().unwrap();
+        assert!(sq.is_given());
+        assert_eq!(sq.get().unwrap(), 3);
+        assert_eq!(sq.get_box(), 1);
+
+        sq.unset();
+        assert!(!sq.is_given());
+    }
+
+    #[test]
+    fn test_set_value_validates()
+    {
+        let mut sq = SudokuSquare::new(1, 1);
+        assert_eq!(sq.set_value(0), Err(SudokuError::InvalidDigit { digit: 0 }));
+        assert_eq!(sq.set_value(10), Err(SudokuError::InvalidDigit { digit: 10 }));
+        assert!(!sq.is_set());
+
+        sq.set_value(4).unwrap();
+        assert_eq!(sq.set_value(5), Err(SudokuError::IsAlreadySet));
+        assert_eq!(sq.get(), Ok(4));
+        sq.force_value(5).unwrap();
+        assert_eq!(sq.get(), Ok(5));
+        assert_eq!(sq.force_value(12), Err(SudokuError::InvalidDigit { digit: 12 }));
+        assert!(SudokuSquare::with_value(1, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_candidates()
+    {
+        let mut sq = SudokuSquare::new(1, 1);
+        assert_eq!(sq.candidate_mask(), DIGIT_MASK);
+        sq.remove_possibility(2).unwrap();
+        sq.remove_possibility(9).unwrap();
+        assert_eq!(sq.candidate_mask(), 0x00FD);
+        assert_eq!(sq.candidates().collect::<Vec<u8>>(), vec![1, 3, 4, 5, 6, 7, 8]);
+
+        sq.force_value(3).unwrap();
+        assert_eq!(sq.candidate_mask(), 0);
+        assert_eq!(sq.candidates().count(), 0);
+    }
+
+    #[test]
+    fn test_restore_candidates()
+    {
+        let mut sq = SudokuSquare::new(5, 5);
+        sq.remove_possibility(3).unwrap();
+        sq.remove_possibility(7).unwrap();
+        sq.add_possibility(3).unwrap();
+        assert_eq!(sq.candidates().collect::<Vec<u8>>(), vec![1, 2, 3, 4, 5, 6, 8, 9]);
+
+        sq.set_candidates(0xFE01);
+        assert_eq!(sq.candidate_mask(), 0x0001);
+        assert_eq!(sq.get_box(), 5);
+        sq.reset_candidates();
+        assert_eq!(sq.candidate_mask(), DIGIT_MASK);
+
+        sq.force_value(2).unwrap();
+        sq.add_possibility(4).unwrap();
+        sq.set_candidates(0x00F0);
+        sq.reset_candidates();
+        assert_eq!(sq.get(), Ok(2));
+    }
+
+    #[test]
+    fn test_add_possibility_rejects_bad_digits()
+    {
+        let mut sq = SudokuSquare::new(4, 4);
+        sq.remove_possibility(1).unwrap();
+        let before = sq;
+        assert_eq!(sq.add_possibility(0), Err(SudokuError::InvalidDigit { digit: 0 }));
+        assert_eq!(sq.add_possibility(10), Err(SudokuError::InvalidDigit { digit: 10 }));
+        assert_eq!(sq, before);
+
+        for bad in [0u8, 10, 11] {
+            assert_eq!(sq.remove_possibility(bad), Err(SudokuError::InvalidDigit { digit: u16::from(bad) }));
+        }
+        assert_eq!(sq, before);
+        let mut placed = SudokuSquare::new(4, 4);
+        placed.set_value(6).unwrap();
+        placed.set_given().unwrap();
+        assert!(placed.remove_possibility(11).is_err());
+        assert!(placed.is_set() && placed.is_given() && placed.is(6));
+        assert!(!placed.is(0) && !placed.is(10));
+        assert!(!sq.is_set());
+        assert_eq!(sq.get_box(), 5);
+    }
+
+    #[test]
+    fn test_candidate_masks_keep_other_bits()
+    {
+        let mut sq = SudokuSquare::new(9, 9);
+        sq.intersect_candidates(0x0007);
+        assert_eq!(sq.candidate_mask(), 0x0007);
+        assert_eq!((sq.row(), sq.col(), sq.get_box()), (9, 9, 9));
+        sq.remove_candidates(0xFE02);
+        assert_eq!(sq.candidate_mask(), 0x0005);
+        assert_eq!(sq.get_box(), 9);
+        sq.apply_mask(0x0004);
+        assert_eq!(sq.candidates().collect::<Vec<u8>>(), vec![3]);
+
+        let mut given = SudokuSquare::with_value(2, 2, 6).unwrap();
+        given.set_given().unwrap();
+        given.intersect_candidates(0x0001);
+        given.remove_candidates(DIGIT_MASK);
+        assert!(given.is_given());
+        assert_eq!(given.get(), Ok(6));
+    }
+
+    #[test]
+    fn test_unset()
+    {
+        let mut sq = SudokuSquare::with_value(4, 5, 3).unwrap();
+        assert!(sq.is_set());
+
+        sq.unset();
+        assert!(!sq.is_set());
+        assert_eq!(sq.possibilities_number(), 9);
+        assert_eq!(sq.get_box(), 5);
+    }
+
     #[test]
     fn test_box_correctly_set()
     {
@@ -0,0 +1,87 @@
+//! `proptest` strategies for grids, so code built on the crate can be
+//! property tested against its invariants. `SudokuGrid` also implements
+//! `Arbitrary`, producing the partial grids of `partial_grid`.
+
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::SudokuGrid;
+use crate::generator::{Symmetry, generate_with_rng};
+use crate::solver::backtrack::{fill, random_solution};
+
+
+fn solution(seed: u64) -> SudokuGrid
+{
+    fill(&SudokuGrid::new(), &random_solution(&mut StdRng::seed_from_u64(seed)))
+}
+
+
+/// Complete, valid grids.
+pub fn solved_grid() -> impl Strategy<Value = SudokuGrid>
+{
+    any::<u64>().prop_map(solution)
+}
+
+/// Grids with some squares of a valid solution filled, so always solvable
+/// though not always uniquely. Shrinks towards fewer filled squares.
+pub fn partial_grid() -> impl Strategy<Value = SudokuGrid>
+{
+    (any::<u64>(), vec(any::<bool>(), 81)).prop_map(|(seed, keep)| {
+        let values = random_solution(&mut StdRng::seed_from_u64(seed));
+        let mut grid = SudokuGrid::new();
+        for (i, _) in keep.iter().enumerate().filter(|(_, &keep)| keep) {
+            grid.set_unchecked((i / 9 + 1) as u8, (i % 9 + 1) as u8, values[i]);
+        }
+        grid
+    })
+}
+
+/// Puzzles with a unique solution, from the generator. Slower to produce
+/// than the other strategies.
+pub fn puzzle() -> impl Strategy<Value = SudokuGrid>
+{
+    any::<u64>().prop_map(|seed| generate_with_rng(&mut StdRng::seed_from_u64(seed), Symmetry::None))
+}
+
+
+impl Arbitrary for SudokuGrid {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<SudokuGrid>;
+
+    fn arbitrary_with(_: ()) -> BoxedStrategy<SudokuGrid>
+    {
+        partial_grid().boxed()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_solved_grids(grid in solved_grid())
+        {
+            prop_assert!(grid.is_solved());
+        }
+
+        #[test]
+        fn test_partial_grids(grid in any::<SudokuGrid>())
+        {
+            prop_assert!(grid.is_consistent());
+            prop_assert!(grid.count_solutions(1) == 1);
+        }
+
+        #[test]
+        fn test_puzzles(grid in puzzle())
+        {
+            prop_assert!(grid.has_unique_solution());
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Lookup tables of grid geometry, built at compile time.
+//!
+//! Squares are indexed 0 to 80 in row-major order and units 0 to 26 in
+//! the order of `Unit::all`: rows, then columns, then boxes.
+
+const fn box_index(index: usize) -> usize
+{
+    3*(index / 27) + (index % 9) / 3
+}
+
+const fn compute_unit_cells() -> [[u8; 9]; 27]
+{
+    let mut cells = [[0; 9]; 27];
+    let mut i = 0;
+    while i < 81 {
+        let (r, c, b) = (i / 9, i % 9, box_index(i));
+        cells[r][c] = i as u8;
+        cells[9 + c][r] = i as u8;
+        cells[18 + b][3*(r % 3) + c % 3] = i as u8;
+        i += 1;
+    }
+    cells
+}
+
+const fn compute_units_of() -> [[u8; 3]; 81]
+{
+    let mut units = [[0; 3]; 81];
+    let mut i = 0;
+    while i < 81 {
+        units[i] = [(i / 9) as u8, (9 + i % 9) as u8, (18 + box_index(i)) as u8];
+        i += 1;
+    }
+    units
+}
+
+const fn compute_peers() -> [[u8; 20]; 81]
+{
+    let mut peers = [[0; 20]; 81];
+    let mut i = 0;
+    while i < 81 {
+        let mut n = 0;
+        let mut j = 0;
+        while j < 81 {
+            if j != i && (i / 9 == j / 9 || i % 9 == j % 9 || box_index(i) == box_index(j)) {
+                peers[i][n] = j as u8;
+                n += 1;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    peers
+}
+
+const fn compute_unit_masks() -> [u128; 27]
+{
+    let cells = compute_unit_cells();
+    let mut masks = [0; 27];
+    let mut u = 0;
+    while u < 27 {
+        let mut k = 0;
+        while k < 9 {
+            masks[u] |= 1 << cells[u][k];
+            k += 1;
+        }
+        u += 1;
+    }
+    masks
+}
+
+const fn compute_peer_masks() -> [u128; 81]
+{
+    let peers = compute_peers();
+    let mut masks = [0; 81];
+    let mut i = 0;
+    while i < 81 {
+        let mut k = 0;
+        while k < 20 {
+            masks[i] |= 1 << peers[i][k];
+            k += 1;
+        }
+        i += 1;
+    }
+    masks
+}
+
+const fn compute_zobrist() -> [[u64; 9]; 81]
+{
+    // Splitmix64 from a fixed seed, so hashes are stable between runs.
+    let mut keys = [[0; 9]; 81];
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut i = 0;
+    while i < 81 {
+        let mut d = 0;
+        while d < 9 {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            keys[i][d] = z ^ (z >> 31);
+            d += 1;
+        }
+        i += 1;
+    }
+    keys
+}
+
+
+/// The squares of each unit.
+pub static UNIT_CELLS: [[u8; 9]; 27] = compute_unit_cells();
+
+/// The row, column and box unit of each square.
+pub static UNITS_OF: [[u8; 3]; 81] = compute_units_of();
+
+/// The 20 squares sharing a unit with each square, in increasing order.
+pub static PEERS: [[u8; 20]; 81] = compute_peers();
+
+/// The squares of each unit as an 81-bit mask, bit `i` for square `i`.
+pub static UNIT_MASKS: [u128; 27] = compute_unit_masks();
+
+/// The peers of each square as an 81-bit mask.
+pub static PEER_MASKS: [u128; 81] = compute_peer_masks();
+
+/// A random key for each square and digit, XORed together over the set
+/// squares to give `SudokuGrid::position_hash`.
+pub static ZOBRIST: [[u64; 9]; 81] = compute_zobrist();
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Unit;
+
+    #[test]
+    fn test_unit_cells_match_units()
+    {
+        for (k, unit) in Unit::all().enumerate() {
+            let cells: Vec<usize> = UNIT_CELLS[k].iter().map(|&i| usize::from(i)).collect();
+            assert_eq!(cells, unit.indices().to_vec());
+        }
+    }
+
+    #[test]
+    fn test_peers()
+    {
+        assert_eq!(&PEERS[0][..8], &[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(&PEERS[0][8..], &[9, 10, 11, 18, 19, 20, 27, 36, 45, 54, 63, 72]);
+        for i in 0..81 {
+            assert!(!PEERS[i].contains(&(i as u8)));
+            for &u in UNITS_OF[i].iter() {
+                assert!(UNIT_CELLS[usize::from(u)].contains(&(i as u8)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_masks()
+    {
+        assert_eq!(UNIT_MASKS[0], 0x01FF);
+        assert!(UNIT_MASKS.iter().all(|m| m.count_ones() == 9));
+        for (i, mask) in PEER_MASKS.iter().enumerate() {
+            assert_eq!(mask.count_ones(), 20);
+            assert_eq!(mask & (1 << i), 0);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{SudokuGrid, is_peer, unit_indices};
+
+use super::{Deduction, Elimination};
+
+
+fn has_candidate(grid: &SudokuGrid, index: usize, digit: u8) -> bool
+{
+    let sq = grid.0[index];
+    !sq.is_set() && sq.is_possible(digit)
+}
+
+/// Pairs of squares that are the only two places for `digit` in a unit.
+fn strong_links(grid: &SudokuGrid, digit: u8) -> Vec<(usize, usize)>
+{
+    let mut links = Vec::new();
+    for unit in unit_indices() {
+        if unit.iter().any(|&i| grid.0[i].is(digit)) {
+            continue;
+        }
+        let places: Vec<usize> = unit.iter().copied()
+            .filter(|&i| has_candidate(grid, i, digit))
+            .collect();
+        if places.len() == 2 && !links.contains(&(places[0], places[1])) {
+            links.push((places[0], places[1]));
+        }
+    }
+    links
+}
+
+/// Two-colour each connected chain of strong links, returning the chains
+/// as `(index, colour)` lists.
+fn colour_chains(links: &[(usize, usize)]) -> Vec<Vec<(usize, bool)>>
+{
+    let mut colours: [Option<bool>; 81] = [None; 81];
+    let mut chains = Vec::new();
+
+    for &(start, _) in links.iter() {
+        if colours[start].is_some() {
+            continue;
+        }
+        colours[start] = Some(true);
+        let mut chain = vec![(start, true)];
+        let mut k = 0;
+        while k < chain.len() {
+            let (cell, colour) = chain[k];
+            for &(a, b) in links.iter() {
+                let other = if a == cell { b } else if b == cell { a } else { continue };
+                if colours[other].is_none() {
+                    colours[other] = Some(!colour);
+                    chain.push((other, !colour));
+                }
+            }
+            k += 1;
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+/// Simple colouring over the strong links of each digit. When two squares
+/// of the same colour see each other that colour is false and the digit
+/// is removed from all of its squares; otherwise the digit is removed from
+/// any square that sees both colours. Each deduction lists the chain.
+pub fn simple_colouring(grid: &mut SudokuGrid) -> Vec<Deduction>
+{
+    let mut deductions = Vec::new();
+    for digit in 1..=9 {
+        for chain in colour_chains(&strong_links(grid, digit)) {
+            if chain.len() < 3 {
+                continue;
+            }
+
+            let wrapped = [true, false].iter().copied().find(|&colour| {
+                chain.iter().any(|&(a, ca)| chain.iter().any(
+                    |&(b, cb)| a != b && ca == colour && cb == colour && is_peer(a, b)
+                ))
+            });
+
+            let targets: Vec<usize> = match wrapped {
+                Some(colour) => chain.iter()
+                    .filter(|&&(_, c)| c == colour)
+                    .map(|&(i, _)| i)
+                    .collect(),
+                None => (0..81).filter(|&i| {
+                    has_candidate(grid, i, digit)
+                        && chain.iter().all(|&(c, _)| c != i)
+                        && chain.iter().any(|&(c, col)| col && is_peer(c, i))
+                        && chain.iter().any(|&(c, col)| !col && is_peer(c, i))
+                }).collect()
+            };
+
+            if targets.is_empty() {
+                continue;
+            }
+
+            let mut eliminations = Vec::new();
+            for i in targets {
+                let sq = &mut grid.0[i];
+                sq.remove_possibility_unchecked(digit);
+                eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit });
+            }
+            let cells = chain.iter()
+                .map(|&(i, _)| (grid.0[i].row(), grid.0[i].col()))
+                .collect();
+            deductions.push(Deduction { cells, eliminations });
+        }
+    }
+    deductions
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn only_in(grid: &mut SudokuGrid, unit: &[usize], keep: &[usize], digit: u8)
+    {
+        for &i in unit.iter() {
+            if !keep.contains(&i) {
+                grid.0[i].remove_possibility(digit).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_colour_trap()
+    {
+        let mut grid = SudokuGrid::new();
+        let row = |r: usize| (0..9).map(|c| 9*r + c).collect::<Vec<_>>();
+        let col = |c: usize| (0..9).map(|r| 9*r + c).collect::<Vec<_>>();
+
+        // Chain for 4: (1, 1) = (1, 5) in row 1, (1, 5) = (7, 5) in
+        // column 5, (7, 5) = (7, 2) in row 7. (1, 1) and (7, 2) have
+        // opposite colours, so (2, 2) can't hold a 4.
+        only_in(&mut grid, &row(0), &[0, 4], 4);
+        only_in(&mut grid, &col(4), &[4, 58], 4);
+        only_in(&mut grid, &row(6), &[58, 55], 4);
+
+        let deductions = simple_colouring(&mut grid);
+        let removed: Vec<(u8, u8)> = deductions.iter()
+            .flat_map(|d| d.eliminations.iter().map(|e| (e.row, e.col)))
+            .collect();
+        assert!(removed.contains(&(2, 2)));
+        assert!(!grid.0[10].is_possible(4));
+        assert!(grid.0[0].is_possible(4));
+    }
+}
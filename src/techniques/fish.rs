@@ -0,0 +1,107 @@
+use crate::SudokuGrid;
+use crate::tables::UNIT_MASKS;
+
+use super::{Deduction, Elimination, combinations};
+
+
+fn cell_index(line: usize, pos: usize, by_row: bool) -> usize
+{
+    if by_row { 9*line + pos } else { 9*pos + line }
+}
+
+/// Bits 0-8 mark the positions along the line where `digit` is still a
+/// candidate. A line that already has `digit` placed gives zero.
+fn line_positions(grid: &SudokuGrid, placed: u128, digit: u8, line: usize, by_row: bool) -> u16
+{
+    let unit = if by_row { line } else { 9 + line };
+    if placed & UNIT_MASKS[unit] != 0 {
+        return 0;
+    }
+
+    let mut mask = 0u16;
+    for pos in 0..9 {
+        let sq = grid.0[cell_index(line, pos, by_row)];
+        if !sq.is_set() && sq.is_possible(digit) {
+            mask |= 0x0001 << pos;
+        }
+    }
+    mask
+}
+
+/// Find basic fish of the given size (2 for X-Wing, 3 for Swordfish and 4
+/// for Jellyfish) over rows and columns, removing the fish digit from the
+/// cover lines. Each deduction lists the cells of the fish.
+pub fn basic_fish(grid: &mut SudokuGrid, size: usize) -> Vec<Deduction>
+{
+    assert!((2..=4).contains(&size), "Fish size must be 2, 3 or 4");
+
+    let mut deductions = Vec::new();
+    for digit in 1..=9 {
+        let placed = grid.digit_board(digit);
+        for &by_row in [true, false].iter() {
+            let lines: Vec<(usize, u16)> = (0..9)
+                .map(|line| (line, line_positions(grid, placed, digit, line, by_row)))
+                .filter(|&(_, m)| m.count_ones() >= 2 && m.count_ones() as usize <= size)
+                .collect();
+
+            for subset in combinations(lines.len(), size) {
+                let base: Vec<usize> = subset.iter().map(|&k| lines[k].0).collect();
+                let cover = subset.iter().fold(0u16, |m, &k| m | lines[k].1);
+                if cover.count_ones() as usize != size {
+                    continue;
+                }
+
+                let mut eliminations = Vec::new();
+                for pos in (0..9).filter(|p| cover & (0x0001 << p) != 0) {
+                    for line in (0..9).filter(|l| !base.contains(l)) {
+                        let sq = &mut grid.0[cell_index(line, pos, by_row)];
+                        if !sq.is_set() && sq.is_possible(digit) {
+                            sq.remove_possibility_unchecked(digit);
+                            eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit });
+                        }
+                    }
+                }
+
+                if !eliminations.is_empty() {
+                    let mut cells = Vec::new();
+                    for &k in subset.iter() {
+                        let (line, mask) = lines[k];
+                        for pos in (0..9).filter(|p| mask & (0x0001 << p) != 0) {
+                            let sq = grid.0[cell_index(line, pos, by_row)];
+                            cells.push((sq.row(), sq.col()));
+                        }
+                    }
+                    deductions.push(Deduction { cells, eliminations });
+                }
+            }
+        }
+    }
+    deductions
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_x_wing_in_rows()
+    {
+        let mut grid = SudokuGrid::new();
+        // Rows 2 and 6 can only take 7 in columns 3 and 8.
+        for &row in [1usize, 5].iter() {
+            for col in 0..9 {
+                if col != 2 && col != 7 {
+                    grid.0[9*row + col].remove_possibility(7).unwrap();
+                }
+            }
+        }
+
+        let deductions = basic_fish(&mut grid, 2);
+        assert_eq!(deductions.len(), 1);
+        assert_eq!(deductions[0].cells, vec![(2, 3), (2, 8), (6, 3), (6, 8)]);
+        assert_eq!(deductions[0].eliminations.len(), 14);
+        assert!(!grid.0[2].is_possible(7));
+        assert!(grid.0[9 + 2].is_possible(7));
+    }
+}
@@ -0,0 +1,137 @@
+//! Human-style solving techniques.
+//!
+//! Each technique works on the candidate masks held by the grid, so the
+//! candidates should be brought up to date with `SudokuGrid::propagate`
+//! before a technique is applied.
+
+mod colouring;
+mod fish;
+mod subsets;
+mod wings;
+
+pub use colouring::simple_colouring;
+pub use fish::basic_fish;
+pub use subsets::naked_subsets;
+pub(crate) use subsets::naked_subset_deductions;
+pub use wings::{xy_wing, xyz_wing};
+
+use std::fmt;
+
+
+/// The techniques known to the logical solver, ordered from easiest to
+/// hardest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    NakedPair,
+    NakedTriple,
+    NakedQuad,
+    XWing,
+    Swordfish,
+    Jellyfish,
+    XYWing,
+    XYZWing,
+    SimpleColouring
+}
+
+impl Technique {
+
+    pub const ALL: [Technique; 11] = [
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::NakedPair,
+        Technique::NakedTriple,
+        Technique::NakedQuad,
+        Technique::XWing,
+        Technique::Swordfish,
+        Technique::Jellyfish,
+        Technique::XYWing,
+        Technique::XYZWing,
+        Technique::SimpleColouring
+    ];
+
+    pub fn name(&self) -> &'static str
+    {
+        match self {
+            Technique::NakedSingle => "Naked Single",
+            Technique::HiddenSingle => "Hidden Single",
+            Technique::NakedPair => "Naked Pair",
+            Technique::NakedTriple => "Naked Triple",
+            Technique::NakedQuad => "Naked Quad",
+            Technique::XWing => "X-Wing",
+            Technique::Swordfish => "Swordfish",
+            Technique::Jellyfish => "Jellyfish",
+            Technique::XYWing => "XY-Wing",
+            Technique::XYZWing => "XYZ-Wing",
+            Technique::SimpleColouring => "Simple Colouring"
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        f.write_str(self.name())
+    }
+}
+
+
+/// A candidate removed from a square by a technique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elimination {
+    pub row: u8,
+    pub col: u8,
+    pub digit: u8
+}
+
+
+/// The cells that define a pattern together with the candidates that the
+/// pattern removed from the rest of the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deduction {
+    pub cells: Vec<(u8, u8)>,
+    pub eliminations: Vec<Elimination>
+}
+
+
+/// All `k` element combinations of `0..n`, in lexicographic order.
+pub(crate) fn combinations(n: usize, k: usize) -> Vec<Vec<usize>>
+{
+    let mut result = Vec::new();
+    if k > n {
+        return result;
+    }
+
+    let mut current: Vec<usize> = (0..k).collect();
+    loop {
+        result.push(current.clone());
+
+        let mut i = k;
+        while i > 0 && current[i - 1] == n - k + i - 1 {
+            i -= 1;
+        }
+        if i == 0 {
+            return result;
+        }
+        current[i - 1] += 1;
+        for j in i..k {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combinations_count()
+    {
+        assert_eq!(combinations(9, 2).len(), 36);
+        assert_eq!(combinations(9, 4).len(), 126);
+        assert_eq!(combinations(3, 3), vec![vec![0, 1, 2]]);
+        assert!(combinations(2, 3).is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{SudokuGrid, unit_indices};
+
+use super::{Deduction, Elimination, combinations};
+
+
+/// Find naked subsets of the given size (2 for pairs, 3 for triples and
+/// 4 for quads) and remove their digits from the other squares of the
+/// unit. Returns the candidates that were removed.
+pub fn naked_subsets(grid: &mut SudokuGrid, size: usize) -> Vec<Elimination>
+{
+    naked_subset_deductions(grid, size).into_iter()
+        .flat_map(|d| d.eliminations)
+        .collect()
+}
+
+/// As `naked_subsets`, keeping the squares of each subset.
+pub(crate) fn naked_subset_deductions(grid: &mut SudokuGrid, size: usize) -> Vec<Deduction>
+{
+    assert!((2..=4).contains(&size), "Subset size must be 2, 3 or 4");
+
+    let mut deductions = Vec::new();
+    for unit in unit_indices() {
+        let open: Vec<usize> = unit.iter().copied().filter(|&i| {
+            let sq = grid.0[i];
+            !sq.is_set() && sq.possibilities_number() >= 2
+                && usize::from(sq.possibilities_number()) <= size
+        }).collect();
+
+        for subset in combinations(open.len(), size) {
+            let cells: Vec<usize> = subset.iter().map(|&k| open[k]).collect();
+            let mask = cells.iter().fold(0u16, |m, &i| m | grid.0[i].digit_bits());
+            if mask.count_ones() as usize != size {
+                continue;
+            }
+
+            let mut eliminations = Vec::new();
+            for &i in unit.iter() {
+                let sq = &mut grid.0[i];
+                if sq.is_set() || cells.contains(&i) {
+                    continue;
+                }
+                for digit in 1..=9 {
+                    if mask & (0x0001 << (digit - 1)) != 0 && sq.is_possible(digit) {
+                        sq.remove_possibility_unchecked(digit);
+                        eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit });
+                    }
+                }
+            }
+
+            if !eliminations.is_empty() {
+                let cells = cells.iter().map(|&i| (grid.0[i].row(), grid.0[i].col())).collect();
+                deductions.push(Deduction { cells, eliminations });
+            }
+        }
+    }
+    deductions
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naked_pair_in_row()
+    {
+        let mut grid = SudokuGrid::new();
+        for i in 0..9 {
+            if i != 0 && i != 1 && i != 2 {
+                grid.0[i].remove_possibility(1).unwrap();
+                grid.0[i].remove_possibility(2).unwrap();
+            }
+        }
+        // Squares (1, 1) and (1, 2) can only hold 1 or 2.
+        for digit in 3..=9 {
+            grid.0[0].remove_possibility(digit).unwrap();
+            grid.0[1].remove_possibility(digit).unwrap();
+        }
+
+        let eliminations = naked_subsets(&mut grid, 2);
+        assert!(eliminations.contains(&Elimination { row: 1, col: 3, digit: 1 }));
+        assert!(eliminations.contains(&Elimination { row: 1, col: 3, digit: 2 }));
+        assert!(!grid.0[2].is_possible(1));
+        assert!(!grid.0[2].is_possible(2));
+    }
+
+    #[test]
+    fn test_no_subsets_in_solved_grid()
+    {
+        let mut grid = sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ];
+        assert!(naked_subsets(&mut grid, 3).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{SudokuGrid, is_peer};
+
+use super::{Deduction, Elimination};
+
+
+fn mask_digit(mask: u16) -> u8
+{
+    mask.trailing_zeros() as u8 + 1
+}
+
+/// Look for a pivot with `pivot_size` candidates and two bivalue pincers
+/// that both see it and share a digit `z` with each other. `z` is removed
+/// from every square that sees all of `seen` (the pincers, and the pivot
+/// too for an XYZ-Wing).
+fn wings(grid: &mut SudokuGrid, pivot_size: u8) -> Vec<Deduction>
+{
+    let mut deductions = Vec::new();
+    for pivot in 0..81 {
+        let p_sq = grid.0[pivot];
+        if p_sq.is_set() || p_sq.possibilities_number() != pivot_size {
+            continue;
+        }
+        let p_mask = p_sq.digit_bits();
+
+        let pincers: Vec<usize> = (0..81).filter(|&i| {
+            let sq = grid.0[i];
+            i != pivot && is_peer(pivot, i) && !sq.is_set()
+                && sq.possibilities_number() == 2
+        }).collect();
+
+        for (k, &a) in pincers.iter().enumerate() {
+            for &b in pincers[k + 1..].iter() {
+                let a_mask = grid.0[a].digit_bits();
+                let b_mask = grid.0[b].digit_bits();
+                let z_mask = a_mask & b_mask;
+                if a_mask == b_mask || z_mask.count_ones() != 1
+                    || (a_mask | b_mask | p_mask).count_ones() != 3 {
+                    continue;
+                }
+
+                let seen = if pivot_size == 2 {
+                    if z_mask & p_mask != 0 {
+                        continue;
+                    }
+                    vec![a, b]
+                } else {
+                    if a_mask | b_mask != p_mask {
+                        continue;
+                    }
+                    vec![pivot, a, b]
+                };
+
+                let z = mask_digit(z_mask);
+                let mut eliminations = Vec::new();
+                for i in 0..81 {
+                    if i == pivot || i == a || i == b
+                        || !seen.iter().all(|&s| is_peer(s, i)) {
+                        continue;
+                    }
+                    let sq = &mut grid.0[i];
+                    if !sq.is_set() && sq.is_possible(z) {
+                        sq.remove_possibility_unchecked(z);
+                        eliminations.push(Elimination { row: sq.row(), col: sq.col(), digit: z });
+                    }
+                }
+
+                if !eliminations.is_empty() {
+                    let cells = [pivot, a, b].iter()
+                        .map(|&i| (grid.0[i].row(), grid.0[i].col()))
+                        .collect();
+                    deductions.push(Deduction { cells, eliminations });
+                }
+            }
+        }
+    }
+    deductions
+}
+
+/// Find XY-Wings: a bivalue pivot `xy` seeing pincers `xz` and `yz`.
+/// The digit `z` is removed from squares that see both pincers. The cells
+/// of each deduction are listed pivot first.
+pub fn xy_wing(grid: &mut SudokuGrid) -> Vec<Deduction>
+{
+    wings(grid, 2)
+}
+
+/// Find XYZ-Wings: a pivot `xyz` seeing pincers `xz` and `yz`. The digit
+/// `z` is removed from squares that see the pivot and both pincers.
+pub fn xyz_wing(grid: &mut SudokuGrid) -> Vec<Deduction>
+{
+    wings(grid, 3)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restrict(grid: &mut SudokuGrid, index: usize, digits: &[u8])
+    {
+        for digit in 1..=9 {
+            if !digits.contains(&digit) {
+                grid.0[index].remove_possibility(digit).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_xy_wing()
+    {
+        let mut grid = SudokuGrid::new();
+        // Pivot (1, 1) = {1, 2}, pincers (1, 5) = {1, 3} and (5, 1) = {2, 3}.
+        restrict(&mut grid, 0, &[1, 2]);
+        restrict(&mut grid, 4, &[1, 3]);
+        restrict(&mut grid, 36, &[2, 3]);
+
+        let deductions = xy_wing(&mut grid);
+        assert_eq!(deductions.len(), 1);
+        assert_eq!(deductions[0].cells, vec![(1, 1), (1, 5), (5, 1)]);
+        assert_eq!(deductions[0].eliminations, vec![Elimination { row: 5, col: 5, digit: 3 }]);
+        assert!(!grid.0[40].is_possible(3));
+    }
+
+    #[test]
+    fn test_xyz_wing()
+    {
+        let mut grid = SudokuGrid::new();
+        // Pivot (1, 1) = {1, 2, 3}, pincers (1, 5) = {1, 3} and (2, 2) = {2, 3}.
+        restrict(&mut grid, 0, &[1, 2, 3]);
+        restrict(&mut grid, 4, &[1, 3]);
+        restrict(&mut grid, 10, &[2, 3]);
+
+        let deductions = xyz_wing(&mut grid);
+        assert_eq!(deductions.len(), 1);
+        let removed: Vec<(u8, u8)> = deductions[0].eliminations.iter()
+            .map(|e| (e.row, e.col))
+            .collect();
+        assert_eq!(removed, vec![(1, 2), (1, 3)]);
+    }
+}
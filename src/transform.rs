@@ -0,0 +1,264 @@
+//! Validity-preserving transformations of the grid.
+//!
+//! Each transformation returns a new grid; squares keep their digits and
+//! candidates and move to their new positions.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::{SudokuGrid, SudokuError};
+
+
+/// Check that `a` and `b` are numbered from 1 up to `limit` and, when
+/// `same_group` is set, lie in the same group of three.
+fn check_swap(a: u8, b: u8, limit: u8, same_group: bool) -> Result<(usize, usize), SudokuError>
+{
+    if a == 0 || b == 0 || a > limit || b > limit
+        || (same_group && (a - 1) / 3 != (b - 1) / 3) {
+        return Err(SudokuError::InvalidSwap { first: a, second: b });
+    }
+    Ok((usize::from(a - 1), usize::from(b - 1)))
+}
+
+fn swap_index(i: usize, a: usize, b: usize) -> usize
+{
+    if i == a { b } else if i == b { a } else { i }
+}
+
+
+impl SudokuGrid {
+
+    /// Build a grid where the square at `(r, c)` moves to `f(r, c)`, using
+    /// 0-based coordinates. `f` must be a permutation of the squares.
+    pub(crate) fn remap<F: Fn(usize, usize) -> (usize, usize)>(&self, f: F) -> SudokuGrid
+    {
+        let mut grid = SudokuGrid::new();
+        for (i, sq) in self.0.iter().enumerate() {
+            let (r, c) = f(i / 9, i % 9);
+            let mut sq = *sq;
+            sq.set_position((r + 1) as u8, (c + 1) as u8);
+            grid.0[9*r + c] = sq;
+        }
+        grid.sync_boards();
+        grid
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate90(&self) -> SudokuGrid
+    {
+        self.remap(|r, c| (c, 8 - r))
+    }
+
+    pub fn rotate180(&self) -> SudokuGrid
+    {
+        self.remap(|r, c| (8 - r, 8 - c))
+    }
+
+    /// Reflect in the main diagonal, swapping rows and columns.
+    pub fn transpose(&self) -> SudokuGrid
+    {
+        self.remap(|r, c| (c, r))
+    }
+
+    /// Reflect in the horizontal centre line, swapping top and bottom.
+    pub fn mirror_horizontal(&self) -> SudokuGrid
+    {
+        self.remap(|r, c| (8 - r, c))
+    }
+
+    /// Reflect in the vertical centre line, swapping left and right.
+    pub fn mirror_vertical(&self) -> SudokuGrid
+    {
+        self.remap(|r, c| (r, 8 - c))
+    }
+
+    /// Swap two rows of the same band.
+    pub fn swap_rows(&self, a: u8, b: u8) -> Result<SudokuGrid, SudokuError>
+    {
+        let (a, b) = check_swap(a, b, 9, true)?;
+        Ok(self.remap(|r, c| (swap_index(r, a, b), c)))
+    }
+
+    /// Swap two columns of the same stack.
+    pub fn swap_cols(&self, a: u8, b: u8) -> Result<SudokuGrid, SudokuError>
+    {
+        let (a, b) = check_swap(a, b, 9, true)?;
+        Ok(self.remap(|r, c| (r, swap_index(c, a, b))))
+    }
+
+    /// Swap two bands, numbered 1 to 3 from the top.
+    pub fn swap_bands(&self, a: u8, b: u8) -> Result<SudokuGrid, SudokuError>
+    {
+        let (a, b) = check_swap(a, b, 3, false)?;
+        Ok(self.remap(|r, c| (3*swap_index(r / 3, a, b) + r % 3, c)))
+    }
+
+    /// Swap two stacks, numbered 1 to 3 from the left.
+    pub fn swap_stacks(&self, a: u8, b: u8) -> Result<SudokuGrid, SudokuError>
+    {
+        let (a, b) = check_swap(a, b, 3, false)?;
+        Ok(self.remap(|r, c| (r, 3*swap_index(c / 3, a, b) + c % 3)))
+    }
+
+    /// Rename the digits, with `mapping[d - 1]` replacing `d` in set
+    /// squares and candidates alike. `mapping` must be a permutation of 1
+    /// to 9.
+    pub fn relabel(&self, mapping: &[u8; 9]) -> Result<SudokuGrid, SudokuError>
+    {
+        let mut seen = 0u16;
+        for &d in mapping.iter() {
+            if d == 0 || d > 9 {
+                return Err(SudokuError::InvalidRelabeling);
+            }
+            seen |= 0x0001 << (d - 1);
+        }
+        if seen != 0x01FF {
+            return Err(SudokuError::InvalidRelabeling);
+        }
+
+        let mut grid = SudokuGrid::new();
+        for (new, old) in grid.0.iter_mut().zip(self.0.iter()) {
+            match old.get() {
+                Ok(digit) => {
+                    new.set_value_unchecked(mapping[usize::from(digit - 1)]);
+                    if old.is_given() {
+                        new.set_given()?;
+                    }
+                },
+                Err(_) => {
+                    for digit in (1..=9).filter(|&d| !old.is_possible(d)) {
+                        new.remove_possibility_unchecked(mapping[usize::from(digit - 1)]);
+                    }
+                }
+            }
+        }
+        grid.sync_boards();
+        Ok(grid)
+    }
+
+    /// Apply a random symmetry of sudoku drawn from `rng`: an optional
+    /// transpose, shuffles of the bands, stacks and the lines within them,
+    /// and a relabeling of the digits.
+    pub fn scramble<R: Rng + ?Sized>(&self, rng: &mut R) -> SudokuGrid
+    {
+        let mut lines = [[0usize; 9]; 2];
+        for line in lines.iter_mut() {
+            let mut groups = [0, 1, 2];
+            groups.shuffle(rng);
+            for (g, &group) in groups.iter().enumerate() {
+                let mut within = [0, 1, 2];
+                within.shuffle(rng);
+                for k in 0..3 {
+                    line[3*g + k] = 3*group + within[k];
+                }
+            }
+        }
+        let transpose: bool = rng.gen();
+        let [rows, cols] = lines;
+
+        let mut digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        digits.shuffle(rng);
+        self.remap(|r, c| {
+            let (r, c) = if transpose { (c, r) } else { (r, c) };
+            (rows[r], cols[c])
+        }).relabel(&digits).expect("Shuffled digits are a permutation.")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution() -> SudokuGrid
+    {
+        sudoku_grid![
+            9, 8, 5, 4, 2, 3, 7, 1, 6,
+            1, 3, 4, 6, 7, 9, 5, 8, 2,
+            6, 2, 7, 8, 1, 5, 3, 9, 4,
+            3, 7, 6, 9, 4, 2, 8, 5, 1,
+            5, 1, 9, 7, 8, 6, 2, 4, 3,
+            8, 4, 2, 3, 5, 1, 9, 6, 7,
+            4, 9, 3, 5, 6, 7, 1, 2, 8,
+            2, 5, 8, 1, 3, 4, 6, 7, 9,
+            7, 6, 1, 2, 9, 8, 4, 3, 5
+        ]
+    }
+
+    #[test]
+    fn test_transforms_stay_valid()
+    {
+        let grid = solution();
+        for t in [
+            grid.rotate90(), grid.rotate180(), grid.transpose(),
+            grid.mirror_horizontal(), grid.mirror_vertical()
+        ].iter() {
+            assert!(t.check().unwrap());
+            for (i, sq) in t.iter().enumerate() {
+                assert_eq!((sq.row(), sq.col()), ((i / 9 + 1) as u8, (i % 9 + 1) as u8));
+            }
+        }
+    }
+
+    #[test]
+    fn test_swaps_stay_valid()
+    {
+        let grid = solution();
+        let swapped = [
+            grid.swap_rows(4, 6).unwrap(),
+            grid.swap_cols(1, 3).unwrap(),
+            grid.swap_bands(1, 3).unwrap(),
+            grid.swap_stacks(2, 3).unwrap()
+        ];
+        for t in swapped.iter() {
+            assert!(t.check().unwrap());
+        }
+
+        assert_eq!(swapped[0].get_row(4)[0].get().unwrap(), 8);
+        assert_eq!(swapped[2].0[0].get().unwrap(), 4);
+        assert!(grid.swap_rows(3, 4).is_err());
+        assert!(grid.swap_bands(1, 4).is_err());
+    }
+
+    #[test]
+    fn test_relabel()
+    {
+        let grid = solution();
+        let relabelled = grid.relabel(&[9, 8, 7, 6, 5, 4, 3, 2, 1]).unwrap();
+        assert!(relabelled.check().unwrap());
+        assert_eq!(relabelled.0[0].get().unwrap(), 1);
+        assert!(grid.relabel(&[1, 1, 3, 4, 5, 6, 7, 8, 9]).is_err());
+
+        let mut partial = SudokuGrid::new();
+        partial.0[0].remove_possibility(2).unwrap();
+        let relabelled = partial.relabel(&[2, 3, 1, 4, 5, 6, 7, 8, 9]).unwrap();
+        assert!(!relabelled.0[0].is_possible(3));
+        assert!(relabelled.0[0].is_possible(2));
+    }
+
+    #[test]
+    fn test_scramble()
+    {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let grid = solution();
+        let scrambled = grid.scramble(&mut StdRng::seed_from_u64(3));
+        assert!(scrambled.check().unwrap());
+        assert!(scrambled.is_isomorphic_to(&grid));
+        assert_eq!(scrambled, grid.scramble(&mut StdRng::seed_from_u64(3)));
+    }
+
+    #[test]
+    fn test_transform_positions()
+    {
+        let grid = solution();
+        // The top-left 9 moves to the top-right corner on a clockwise turn.
+        assert_eq!(grid.rotate90().0[8].get().unwrap(), 9);
+        assert_eq!(grid.rotate180().0[80].get().unwrap(), 9);
+        assert_eq!(grid.transpose().0[1].get().unwrap(), 1);
+        assert_eq!(grid.mirror_horizontal().0[0].get().unwrap(), 7);
+        assert_eq!(grid.mirror_vertical().0[0].get().unwrap(), 6);
+        assert_eq!(grid.rotate90().rotate90(), grid.rotate180());
+    }
+}
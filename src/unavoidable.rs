@@ -0,0 +1,167 @@
+//! Unavoidable sets of a solved grid.
+//!
+//! A set of squares is unavoidable when its digits can be rearranged to
+//! give another solution that agrees everywhere else, so a puzzle for the
+//! grid with no clue in the set has at least two solutions. Sets are found
+//! by clearing every square holding one of a few digits and listing the
+//! other ways to fill them back in; each differs from the grid on an
+//! unavoidable set.
+
+use crate::{Pos, SudokuGrid};
+use crate::solver::backtrack::{Search, grid_values, mask_digits};
+
+
+/// Solutions listed per group of cleared digits. A group with more than
+/// this many only contributes the sets found among the first ones.
+const SOLUTIONS_PER_GROUP: usize = 64;
+
+
+/// The squares of an unavoidable set, see `SudokuGrid::unavoidable_sets`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnavoidableSet(u128);
+
+
+impl UnavoidableSet {
+
+    /// The squares as an 81-bit mask, bit `i` for square `i` in row-major
+    /// order as with `SudokuGrid::digit_board`.
+    pub fn mask(&self) -> u128
+    {
+        self.0
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool
+    {
+        self.0 & (1 << pos.index()) != 0
+    }
+
+    /// The squares in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = Pos>
+    {
+        let mask = self.0;
+        (0..81).filter(move |&i| mask & (1 << i) != 0).map(Pos::from_index)
+    }
+
+    /// Whether `clues`, a mask like `mask`, has a square in the set. A
+    /// puzzle needs this for every set to have a unique solution.
+    pub fn is_hit_by(&self, clues: u128) -> bool
+    {
+        self.0 & clues != 0
+    }
+}
+
+
+/// Minimal unavoidable sets of the solution `values`, found by clearing
+/// each group of up to `digits` digits, smallest first.
+pub(crate) fn unavoidable_masks(values: &[u8; 81], digits: usize) -> Vec<u128>
+{
+    let mut found = Vec::new();
+    for group in 1u16..0x0200 {
+        let size = group.count_ones() as usize;
+        if size < 2 || size > digits {
+            continue;
+        }
+        let cleared: [u8; 81] = std::array::from_fn(|i| {
+            if group & (0x0001 << (values[i] - 1)) != 0 { 0 } else { values[i] }
+        });
+        let mut search = match Search::new(&cleared) {
+            Some(search) => search,
+            None => continue
+        };
+        let mut listed = 0;
+        search.run(&mut mask_digits, &mut |solution| {
+            let diff = (0..81).filter(|&i| solution[i] != values[i])
+                .fold(0u128, |m, i| m | (1 << i));
+            if diff != 0 {
+                found.push(diff);
+            }
+            listed += 1;
+            listed < SOLUTIONS_PER_GROUP
+        });
+    }
+
+    // Keep only the sets with no smaller one inside them.
+    found.sort_by_key(|m| (m.count_ones(), *m));
+    found.dedup();
+    let mut minimal: Vec<u128> = Vec::new();
+    for mask in found {
+        if minimal.iter().all(|&m| m & mask != m) {
+            minimal.push(mask);
+        }
+    }
+    minimal
+}
+
+
+impl SudokuGrid {
+
+    /// Small unavoidable sets of a solved grid, smallest first: those
+    /// found by refilling the squares of every two or three digits. Each
+    /// is minimal among the sets found, but the list is not every
+    /// unavoidable set of the grid. A puzzle made from the grid needs a
+    /// clue in each of them, which lets a generator rule out clue patterns
+    /// without a uniqueness check. A grid that isn't solved has none.
+    pub fn unavoidable_sets(&self) -> Vec<UnavoidableSet>
+    {
+        if !self.is_solved() {
+            return Vec::new();
+        }
+        unavoidable_masks(&grid_values(self), 3).into_iter().map(UnavoidableSet).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution() -> SudokuGrid
+    {
+        "985423716134679582627815394376942851519786243842351967493567128258134679761298435"
+            .parse().unwrap()
+    }
+
+    #[test]
+    fn test_sets_are_unavoidable()
+    {
+        let grid = solution();
+        let sets = grid.unavoidable_sets();
+        assert!(!sets.is_empty());
+        assert!(sets.windows(2).all(|pair| pair[0].len() <= pair[1].len()));
+        assert!(sets.iter().all(|set| set.len() >= 4));
+
+        for set in sets.iter().take(20) {
+            let mut puzzle = grid.clone();
+            for pos in set.cells() {
+                assert!(set.contains(pos));
+                puzzle.clear(pos).unwrap();
+            }
+            assert!(puzzle.count_solutions(2) > 1);
+        }
+        for (i, a) in sets.iter().enumerate() {
+            assert!(sets[i + 1..].iter().all(|b| a.mask() & b.mask() != a.mask()));
+        }
+    }
+
+    #[test]
+    fn test_unique_puzzle_hits_every_set()
+    {
+        let grid = solution();
+        let mut puzzle = grid.clone();
+        puzzle.minimize().unwrap();
+        let clues = (0..81).filter(|&i| puzzle.0[i].is_set()).fold(0u128, |m, i| m | (1 << i));
+        assert!(grid.unavoidable_sets().iter().all(|set| set.is_hit_by(clues)));
+
+        assert!(puzzle.unavoidable_sets().is_empty());
+    }
+}
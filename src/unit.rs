@@ -0,0 +1,101 @@
+use crate::Pos;
+
+
+/// One of the 27 groups of nine squares that must hold every digit once.
+/// Rows, columns and boxes are numbered from 1, boxes across then down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Row(u8),
+    Col(u8),
+    Box(u8)
+}
+
+
+impl Unit {
+
+    /// All 27 units: the rows, then the columns, then the boxes.
+    pub fn all() -> impl Iterator<Item = Unit>
+    {
+        (1..=9).map(Unit::Row)
+            .chain((1..=9).map(Unit::Col))
+            .chain((1..=9).map(Unit::Box))
+    }
+
+    /// Indices into the grid of the squares in this unit.
+    pub(crate) fn indices(&self) -> [usize; 9]
+    {
+        match *self {
+            Unit::Row(r) => {
+                let offset = Pos::new(r, 1).unwrap_or_else(|| panic!("Invalid row {}.", r)).index();
+                std::array::from_fn(|k| offset + k)
+            },
+            Unit::Col(c) => {
+                let offset = Pos::new(1, c).unwrap_or_else(|| panic!("Invalid column {}.", c)).index();
+                std::array::from_fn(|k| 9*k + offset)
+            },
+            Unit::Box(b) => {
+                if !(1..=9).contains(&b) {
+                    panic!("Invalid box {}.", b);
+                }
+                let b = (b - 1) as usize;
+                let offset = 27*(b / 3) + 3*(b % 3);
+                std::array::from_fn(|k| offset + 9*(k / 3) + k % 3)
+            }
+        }
+    }
+
+    /// Whether the square at `row`, `col` belongs to this unit.
+    pub fn contains(&self, row: u8, col: u8) -> bool
+    {
+        match *self {
+            Unit::Row(r) => r == row,
+            Unit::Col(c) => c == col,
+            Unit::Box(b) => Pos::new(row, col).is_some_and(|pos| pos.box_id() == b)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_units_cover_each_square_three_times()
+    {
+        let mut counts = [0; 81];
+        for unit in Unit::all() {
+            for i in unit.indices().iter() {
+                counts[*i] += 1;
+            }
+        }
+        assert_eq!(Unit::all().count(), 27);
+        assert!(counts.iter().all(|&c| c == 3));
+    }
+
+    #[test]
+    fn test_contains()
+    {
+        assert!(Unit::Box(5).contains(4, 6));
+        assert!(!Unit::Box(5).contains(4, 7));
+        for unit in Unit::all() {
+            for &i in unit.indices().iter() {
+                assert!(unit.contains((i / 9 + 1) as u8, (i % 9 + 1) as u8));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid box 0.")]
+    fn test_box_zero_panics()
+    {
+        Unit::Box(0).indices();
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid box 10.")]
+    fn test_box_ten_panics()
+    {
+        Unit::Box(10).indices();
+    }
+}
@@ -0,0 +1,134 @@
+//! Bindings for JavaScript through wasm-bindgen.
+//!
+//! Puzzles go in and out as 81 character strings with `.` for empty
+//! squares, richer results as JSON text, and errors as message strings,
+//! so nothing needs to be known about the crate's types on the JS side.
+
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::SudokuGrid;
+use crate::generator;
+use crate::rating::Difficulty;
+
+
+fn parse_grid(puzzle: &str) -> Result<SudokuGrid, String>
+{
+    puzzle.parse().map_err(|err: crate::SudokuError| err.to_string())
+}
+
+/// Parse a puzzle and write it back out in the standard form.
+#[wasm_bindgen]
+pub fn parse(puzzle: &str) -> Result<String, String>
+{
+    Ok(parse_grid(puzzle)?.to_line_string())
+}
+
+/// Solve a puzzle, giving the first solution found.
+#[wasm_bindgen]
+pub fn solve(puzzle: &str) -> Result<String, String>
+{
+    parse_grid(puzzle)?.solve()
+        .map(|grid| grid.to_line_string())
+        .map_err(|err| err.to_string())
+}
+
+/// Whether a puzzle is completely and correctly filled in.
+#[wasm_bindgen]
+pub fn check(puzzle: &str) -> Result<bool, String>
+{
+    Ok(parse_grid(puzzle)?.is_solved())
+}
+
+/// The pairs of squares that hold the same digit in a shared unit, as a
+/// JSON array of `[[row, col], [row, col], digit]`.
+#[wasm_bindgen]
+pub fn conflicts(puzzle: &str) -> Result<String, String>
+{
+    let pairs: Vec<Value> = parse_grid(puzzle)?.conflicts().into_iter()
+        .map(|(a, b, digit)| json!([[a.0, a.1], [b.0, b.1], digit]))
+        .collect();
+    Ok(Value::from(pairs).to_string())
+}
+
+/// The next logical move as a JSON object with `technique`, `placement`,
+/// `eliminations` and `cells`, or `null` when there is none.
+#[wasm_bindgen]
+pub fn hint(puzzle: &str) -> Result<String, String>
+{
+    let hint = match parse_grid(puzzle)?.hint() {
+        Some(hint) => hint,
+        None => return Ok(Value::Null.to_string())
+    };
+    let eliminations: Vec<Value> = hint.eliminations.iter()
+        .map(|e| json!([e.row, e.col, e.digit]))
+        .collect();
+    let cells: Vec<Value> = hint.cells.iter().map(|&(r, c)| json!([r, c])).collect();
+    Ok(json!({
+        "technique": hint.technique.name(),
+        "placement": hint.placement.map(|(r, c, d)| json!([r, c, d])),
+        "eliminations": eliminations,
+        "cells": cells
+    }).to_string())
+}
+
+/// Generate a puzzle, optionally at a difficulty named as in
+/// `Difficulty`'s display form, ignoring case.
+#[wasm_bindgen]
+pub fn generate(difficulty: Option<String>) -> Result<String, String>
+{
+    let grid = match difficulty {
+        None => generator::generate(),
+        Some(name) => {
+            let difficulty = Difficulty::from_name(&name)
+                .ok_or_else(|| format!("unknown difficulty {:?}", name))?;
+            generator::generate_with_difficulty(difficulty).map_err(|err| err.to_string())?
+        }
+    };
+    Ok(grid.to_line_string())
+}
+
+/// The puzzle for a day as a JSON object with `puzzle` and `difficulty`.
+#[wasm_bindgen]
+pub fn daily(date: u32) -> String
+{
+    let daily = generator::daily(date);
+    json!({
+        "puzzle": daily.puzzle.to_line_string(),
+        "difficulty": daily.difficulty.to_string()
+    }).to_string()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = concat!(
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6",
+        ".6....28....419..5....8..79"
+    );
+
+    #[test]
+    fn test_round_trip_through_strings()
+    {
+        let solution = solve(PUZZLE).unwrap();
+        assert!(check(&solution).unwrap());
+        assert!(!check(PUZZLE).unwrap());
+        assert_eq!(parse(&PUZZLE.replace('.', "0")).unwrap(), PUZZLE);
+        assert!(parse("123").is_err());
+    }
+
+    #[test]
+    fn test_json_results()
+    {
+        let hint: Value = serde_json::from_str(&hint(PUZZLE).unwrap()).unwrap();
+        assert!(hint["technique"].is_string());
+        assert!(hint["cells"].is_array());
+
+        let clash = format!("11{}", &PUZZLE[2..]);
+        let pairs: Value = serde_json::from_str(&conflicts(&clash).unwrap()).unwrap();
+        assert_eq!(pairs[0], json!([[1, 1], [1, 2], 1]));
+        assert!(generate(Some("sideways".to_string())).is_err());
+    }
+}